@@ -0,0 +1,219 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Benchmarks of the registry loading and query paths, over a synthetic workspace.
+use std::path::{Path, PathBuf};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use serde_json::json;
+use zenoh::key_expr::keyexpr;
+use zenoh_plugin_ros2_types_registry::bench::Registry;
+
+// Size of the synthetic workspace: NB_PACKAGES packages of TYPES_PER_PACKAGE messages each
+const NB_PACKAGES: usize = 100;
+const TYPES_PER_PACKAGE: usize = 100;
+
+// Create a synthetic ROS 2 share directory, where each message has a few fields
+// and (except the first one of each package) a nested type dependency
+fn create_workspace() -> PathBuf {
+    let share_dir = std::env::temp_dir().join(format!(
+        "ros2-types-registry-bench-{}/share",
+        std::process::id()
+    ));
+    for p in 0..NB_PACKAGES {
+        let msg_dir = share_dir.join(format!("bench_pkg_{p}")).join("msg");
+        std::fs::create_dir_all(&msg_dir).expect("Failed to create workspace directory");
+        for t in 0..TYPES_PER_PACKAGE {
+            write_type(&msg_dir, p, t);
+        }
+    }
+    share_dir
+}
+
+fn write_type(msg_dir: &Path, p: usize, t: usize) {
+    let type_name = format!("bench_pkg_{p}/msg/Type{t}");
+    let base_name = format!("bench_pkg_{p}/msg/Type0");
+    let field = |name: &str, type_id: u64, nested_type_name: &str| {
+        json!({
+            "name": name,
+            "type": {
+                "type_id": type_id,
+                "capacity": 0,
+                "string_capacity": 0,
+                "nested_type_name": nested_type_name,
+            },
+            "default_value": "",
+        })
+    };
+    let base_fields = vec![field("id", 6, ""), field("label", 17, "")];
+
+    let (definition, description, referenced, hashes) = if t == 0 {
+        (
+            "int32 id\nstring label\n".to_string(),
+            json!({ "type_name": type_name, "fields": base_fields }),
+            json!([]),
+            json!([{ "type_name": type_name, "hash_string": hash_string(p, t) }]),
+        )
+    } else {
+        let mut fields = base_fields.clone();
+        fields.push(field("value", 11, ""));
+        fields.push(field("base", 1, &base_name));
+        (
+            "int32 id\nstring label\nfloat64 value\nType0 base\n".to_string(),
+            json!({ "type_name": type_name, "fields": fields }),
+            json!([{ "type_name": base_name, "fields": base_fields }]),
+            json!([
+                { "type_name": type_name, "hash_string": hash_string(p, t) },
+                { "type_name": base_name, "hash_string": hash_string(p, 0) },
+            ]),
+        )
+    };
+    let hashed_description = json!({
+        "type_description_msg": {
+            "type_description": description,
+            "referenced_type_descriptions": referenced,
+        },
+        "type_hashes": hashes,
+    });
+
+    let msg_path = msg_dir.join(format!("Type{t}.msg"));
+    std::fs::write(&msg_path, definition).expect("Failed to write definition file");
+    std::fs::write(
+        msg_path.with_extension("json"),
+        hashed_description.to_string(),
+    )
+    .expect("Failed to write JSON file");
+}
+
+fn hash_string(p: usize, t: usize) -> String {
+    format!("RIHS01_{:064x}", p * TYPES_PER_PACKAGE + t)
+}
+
+fn load_registry(share_dir: &PathBuf) -> Registry<'static> {
+    let mut registry = Registry::new();
+    registry.load_types_from_dir(share_dir);
+    registry
+}
+
+fn bench_loading(c: &mut Criterion, share_dir: &PathBuf) {
+    let mut group = c.benchmark_group("loading");
+    group.sample_size(10);
+    group.bench_function("load_types_from_dir", |b| {
+        b.iter(|| load_registry(black_box(share_dir)))
+    });
+    group.bench_function("load_types_from_dir (lazy definitions)", |b| {
+        b.iter(|| {
+            let mut registry = Registry::new().with_lazy_definitions(128);
+            registry.load_types_from_dir(black_box(share_dir));
+            registry
+        })
+    });
+    group.bench_function("index and warm up", |b| {
+        b.iter(|| {
+            let mut registry = Registry::new().with_warm_up();
+            registry.load_types_from_dir(black_box(share_dir));
+            while registry.warm_up_step(64) > 0 {}
+            registry
+        })
+    });
+    group.finish();
+}
+
+fn bench_matching(c: &mut Criterion, registry: &Registry) {
+    let mut group = c.benchmark_group("matching");
+    for ke in [
+        "bench_pkg_42/msg/Type42",
+        "bench_pkg_42/**",
+        "*/msg/Type42",
+        "**",
+    ] {
+        let ke = keyexpr::new(ke).expect("Invalid key expression");
+        group.bench_function(ke.as_str(), |b| {
+            b.iter(|| registry.iter_types(black_box(ke)).count())
+        });
+    }
+    group.bench_function("get_type_by_hash", |b| {
+        let hash = hash_string(42, 42);
+        b.iter(|| registry.get_type_by_hash(black_box(&hash)).is_some())
+    });
+    group.finish();
+}
+
+fn bench_mcap(c: &mut Criterion, registry: &Registry) {
+    let mut group = c.benchmark_group("mcap");
+    let t = registry
+        .get_type("bench_pkg_42/msg/Type42")
+        .expect("Type not found");
+    group.bench_function("build_mcap_schema", |b| {
+        b.iter(|| registry.build_mcap_schema(black_box(t)))
+    });
+    group.bench_function("get_mcap_schema (memoized)", |b| {
+        b.iter(|| {
+            registry
+                .get_mcap_schema(black_box(t))
+                .map(|schema| schema.len())
+        })
+    });
+    group.finish();
+}
+
+fn bench_serialization(c: &mut Criterion, registry: &Registry) {
+    let mut group = c.benchmark_group("serialization");
+    let t = registry
+        .get_type("bench_pkg_42/msg/Type42")
+        .expect("Type not found");
+    group.bench_function("type_description", |b| {
+        b.iter(|| {
+            serde_json::to_string(black_box(
+                &t.type_description.type_description_msg.type_description,
+            ))
+        })
+    });
+    group.bench_function("full_type_description", |b| {
+        b.iter(|| serde_json::to_string(black_box(&t.type_description)))
+    });
+    group.bench_function("full_type_description (cached)", |b| {
+        b.iter(|| black_box(t).get_full_description_json().map(str::len))
+    });
+    group.bench_function("all types of a package", |b| {
+        let ke = keyexpr::new("bench_pkg_42/**").expect("Invalid key expression");
+        b.iter_batched(
+            || registry.iter_types(ke).collect::<Vec<_>>(),
+            |types| {
+                types
+                    .iter()
+                    .map(|t| serde_json::to_string(&t.type_description).map_or(0, |s| s.len()))
+                    .sum::<usize>()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    let share_dir = create_workspace();
+    bench_loading(c, &share_dir);
+
+    let registry = load_registry(&share_dir);
+    bench_matching(c, &registry);
+    bench_mcap(c, &registry);
+    bench_serialization(c, &registry);
+
+    if let Some(workspace_dir) = share_dir.parent() {
+        let _ = std::fs::remove_dir_all(workspace_dir);
+    }
+}
+
+criterion_group!(registry_benches, benches);
+criterion_main!(registry_benches);
@@ -0,0 +1,40 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Environment variable giving the snapshot file to embed with the "embedded" feature
+#[cfg(feature = "embedded")]
+const EMBEDDED_SNAPSHOT_VAR: &str = "ROS2_TYPES_REGISTRY_SNAPSHOT";
+
+fn main() {
+    // generate the gRPC service code (requires protoc)
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/registry.proto")
+        .expect("Failed to compile proto/registry.proto");
+
+    // embed the snapshot file (exported with --export-snapshot) in the binary, passing its
+    // absolute path to include_bytes!()
+    #[cfg(feature = "embedded")]
+    {
+        println!("cargo:rerun-if-env-changed={EMBEDDED_SNAPSHOT_VAR}");
+        let path = std::env::var(EMBEDDED_SNAPSHOT_VAR).unwrap_or_else(|_| {
+            panic!("The 'embedded' feature requires {EMBEDDED_SNAPSHOT_VAR} to be set to the snapshot file to embed")
+        });
+        let path = std::fs::canonicalize(&path)
+            .unwrap_or_else(|e| panic!("Invalid {EMBEDDED_SNAPSHOT_VAR} '{path}': {e}"));
+        println!("cargo:rerun-if-changed={}", path.display());
+        println!(
+            "cargo:rustc-env=ROS2_TYPES_REGISTRY_EMBEDDED_SNAPSHOT={}",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::{
+    fs::{File, OpenOptions},
+    io::{LineWriter, Write},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use zenoh::query::Query;
+
+// A structured access log, writing one JSON object per line for each received query
+pub(crate) struct AccessLog {
+    writer: Mutex<LineWriter<File>>,
+}
+
+// An entry of the access log
+#[derive(Debug, Serialize)]
+pub(crate) struct AccessLogEntry {
+    pub timestamp_ms: u128, // reception time of the query, in ms since UNIX epoch
+    pub requester_zid: Option<String>, // Zenoh ID of the querier, if known
+    pub key_expr: String,   // the queried key expression
+    pub format: Option<String>, // the requested format, if any
+    pub matched: usize,     // number of matching entries
+    pub bytes_sent: usize,  // total size of the replies payloads
+    pub duration_us: u128,  // time spent handling the query, in µs
+    #[serde(skip)]
+    start: Instant,
+}
+
+// Statistics on the handling of a query, returned by the query handlers
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct QueryStats {
+    pub matched: usize,
+    pub bytes_sent: usize,
+}
+
+impl AccessLog {
+    // Open (or create) the access log file in append mode
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Mutex::new(LineWriter::new(file)),
+        })
+    }
+
+    // Start an entry for a received query, to be completed with finish() once handled
+    pub fn start(&self, query: &Query) -> AccessLogEntry {
+        AccessLogEntry {
+            timestamp_ms: timestamp_ms(SystemTime::now()),
+            requester_zid: requester_zid(query),
+            key_expr: query.key_expr().to_string(),
+            format: query.parameters().get("format").map(str::to_string),
+            matched: 0,
+            bytes_sent: 0,
+            duration_us: 0,
+            start: Instant::now(),
+        }
+    }
+
+    // Complete an entry with the query handling statistics and write it
+    pub fn finish(&self, mut entry: AccessLogEntry, stats: QueryStats) {
+        entry.matched = stats.matched;
+        entry.bytes_sent = stats.bytes_sent;
+        entry.duration_us = entry.start.elapsed().as_micros();
+        self.log(&entry);
+    }
+
+    fn log(&self, entry: &AccessLogEntry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!("Failed to serialize access log entry: {e}");
+                return;
+            }
+        };
+        let mut writer = match self.writer.lock() {
+            Ok(writer) => writer,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Err(e) = writeln!(writer, "{line}") {
+            tracing::warn!("Failed to write access log entry: {e}");
+        }
+    }
+}
+
+// Return the Zenoh ID of the querier, if provided in the query's source info
+pub(crate) fn requester_zid(query: &Query) -> Option<String> {
+    query
+        .source_info()
+        .and_then(|info| info.source_id())
+        .map(|id| id.zid().to_string())
+}
+
+// Return the time elapsed since UNIX epoch for a SystemTime, in ms
+fn timestamp_ms(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis()
+}
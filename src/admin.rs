@@ -0,0 +1,397 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use zenoh::{bytes::Encoding, key_expr::keyexpr, query::Query};
+
+use crate::{
+    access_log::QueryStats,
+    args::Args,
+    auth, compression, keformat_ros2_admin, mcap_check, query_reply_ke,
+    registry::{Maintenance, Registry},
+    rescan_registry, send_reply, send_reply_err, send_reply_err_details,
+    stats::Statistics,
+    strip_namespace,
+    telemetry::{self, LogFilterHandle},
+    ErrorCode, ErrorDetails,
+};
+
+// Default number of entries in the popularity report
+const DEFAULT_TOP_N: usize = 10;
+
+// Operations available in the @ros2_admin/** key space
+const ADMIN_OPERATIONS: &[&str] = &[
+    "stats/popularity",
+    "stats/latency",
+    "stats/history_gc",
+    "log_level",
+    "diagnostics/unresolved_dependencies",
+    "diagnostics/dangling_references",
+    "inventory",
+    "zstd_dictionary",
+    "validate_mcap",
+    "pause",
+    "resume",
+    "stats/reset",
+    "reload",
+];
+
+// The maintenance state replied by the pause and resume operations
+#[derive(Debug, Serialize)]
+struct MaintenanceState<'a> {
+    paused: Option<&'a Maintenance>,
+}
+
+// An entry of the unresolved dependencies report
+#[derive(Debug, Serialize)]
+struct UnresolvedDependencies<'a> {
+    type_name: &'a str,
+    missing: Vec<&'a str>,
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+pub(crate) async fn handle_ros2_admin_query(
+    query: Query,
+    registry: &mut Registry<'_>,
+    statistics: &mut Statistics,
+    log_filter_handle: Option<&LogFilterHandle>,
+    args: &Args,
+) -> QueryStats {
+    tracing::debug!("Received query: {}", query.key_expr());
+    let mut stats = QueryStats::default();
+    if !auth::is_authorized(&query, args.admin_token.as_deref()) {
+        send_reply_err(
+            &query,
+            ErrorCode::Unauthorized,
+            "A valid token is required to query '@ros2_admin/**'",
+        )
+        .await;
+        return stats;
+    }
+    let ke = match keformat_ros2_admin::parse(strip_namespace(query.key_expr())) {
+        Ok(ke) => ke,
+        Err(_) => {
+            tracing::error!(
+                "Received a query on '{}' but it doesn't match the '@ros2_admin/**' queryable!",
+                query.key_expr()
+            );
+            return stats;
+        }
+    };
+
+    let operation = ke.operation().map(|op| op.as_str()).unwrap_or_default();
+    match operation {
+        "stats/popularity" => {
+            let top_n = match query.parameters().get("top").map(str::parse::<usize>) {
+                Some(Ok(n)) => n,
+                Some(Err(e)) => {
+                    send_reply_err_details(
+                        &query,
+                        ErrorCode::InvalidParameter,
+                        format!("Invalid 'top' parameter: {e}"),
+                        Some(ErrorDetails {
+                            parameter: Some("top"),
+                            value: query.parameters().get("top").map(ToOwned::to_owned),
+                            ..Default::default()
+                        }),
+                    )
+                    .await;
+                    return stats;
+                }
+                None => DEFAULT_TOP_N,
+            };
+            send_json_reply(
+                &query,
+                registry,
+                &statistics.top_types(top_n),
+                "popularity report",
+                &mut stats,
+            )
+            .await
+        }
+        "stats/latency" => {
+            send_json_reply(
+                &query,
+                registry,
+                statistics.latencies(),
+                "latency histograms",
+                &mut stats,
+            )
+            .await
+        }
+        "stats/history_gc" => {
+            send_json_reply(
+                &query,
+                registry,
+                registry.get_history_gc_stats(),
+                "history GC statistics",
+                &mut stats,
+            )
+            .await
+        }
+        "log_level" => {
+            // the logging of a registry running as a zenohd plugin is managed by zenohd
+            let Some(log_filter_handle) = log_filter_handle else {
+                send_reply_err(
+                    &query,
+                    ErrorCode::NotAllowed,
+                    "The log filter is managed by the zenohd hosting the registry",
+                )
+                .await;
+                return stats;
+            };
+            // change the log filter if a 'filter' parameter is set, then reply the current one
+            if let Some(filter) = query.parameters().get("filter") {
+                if !require_admin_token(&query, args, operation).await {
+                    return stats;
+                }
+                if let Err(e) = telemetry::set_log_filter(log_filter_handle, filter) {
+                    send_reply_err(&query, ErrorCode::InvalidParameter, e).await;
+                    return stats;
+                }
+                tracing::info!("Log filter changed to '{filter}'");
+            }
+            match telemetry::get_log_filter(log_filter_handle) {
+                Ok(filter) => {
+                    stats.matched = 1;
+                    stats.bytes_sent += send_reply(
+                        &query,
+                        query_reply_ke(&query, registry.get_namespace()),
+                        filter,
+                        Encoding::TEXT_PLAIN,
+                    )
+                    .await;
+                }
+                Err(e) => send_reply_err(&query, ErrorCode::Internal, e).await,
+            }
+        }
+        "diagnostics/unresolved_dependencies" => {
+            let report: Vec<UnresolvedDependencies> = registry
+                .get_unresolved_dependencies()
+                .into_iter()
+                .map(|(type_name, missing)| UnresolvedDependencies { type_name, missing })
+                .collect();
+            send_json_reply(
+                &query,
+                registry,
+                &report,
+                "unresolved dependencies report",
+                &mut stats,
+            )
+            .await
+        }
+        "diagnostics/dangling_references" => {
+            // the references found by the last check of the loaded types (at startup, after the
+            // warm-up, and after each change of the content)
+            send_json_reply(
+                &query,
+                registry,
+                registry.get_dangling_references(),
+                "dangling references report",
+                &mut stats,
+            )
+            .await
+        }
+        "inventory" => {
+            // the inventory manifest (type name => type hash) of all the types, to let a mirror
+            // registry fetch only the types it doesn't have
+            let all_types = keyexpr::new("**").expect("valid key expression");
+            registry.load_pending_matching(all_types);
+            let inventory: BTreeMap<&str, &str> = registry
+                .iter_types(all_types)
+                .map(|t| (t.get_type_name(), t.type_hash.as_str()))
+                .collect();
+            send_json_reply(&query, registry, &inventory, "inventory", &mut stats).await
+        }
+        "zstd_dictionary" => {
+            // the dictionary to decompress the replies with `compress=zstd-dict` (no reply if
+            // not configured)
+            if let Some(dictionary) = compression::dictionary() {
+                stats.matched = 1;
+                stats.bytes_sent += send_reply(
+                    &query,
+                    query_reply_ke(&query, registry.get_namespace()),
+                    dictionary,
+                    Encoding::APPLICATION_OCTET_STREAM,
+                )
+                .await;
+            }
+        }
+        "pause" | "resume" => {
+            if !require_admin_token(&query, args, operation).await {
+                return stats;
+            }
+            // pause (with an optional 'reason' parameter) or resume serving the types, then reply
+            // the maintenance state
+            if operation == "pause" {
+                let reason = query.parameters().get("reason").map(ToOwned::to_owned);
+                let maintenance = registry.pause(reason);
+                tracing::warn!("Paused for maintenance: {:?}", maintenance.reason);
+            } else if registry.resume().is_some() {
+                tracing::info!("Resumed after maintenance");
+            }
+            send_json_reply(
+                &query,
+                registry,
+                &MaintenanceState {
+                    paused: registry.get_maintenance(),
+                },
+                "maintenance state",
+                &mut stats,
+            )
+            .await
+        }
+        "stats/reset" => {
+            if !require_admin_token(&query, args, operation).await {
+                return stats;
+            }
+            // reset the popularity and latency statistics, replying the number of queries
+            // recorded until then
+            let queries = statistics.reset();
+            tracing::info!("Statistics reset ({queries} queries recorded)");
+            stats.matched = 1;
+            stats.bytes_sent += send_reply(
+                &query,
+                query_reply_ke(&query, registry.get_namespace()),
+                format!("{{\"queries\":{queries}}}"),
+                Encoding::APPLICATION_JSON,
+            )
+            .await;
+        }
+        "reload" => {
+            if !require_admin_token(&query, args, operation).await {
+                return stats;
+            }
+            // rescan the directories of the registry, replying the changes of its content
+            let changes = match rescan_registry(args, registry) {
+                Ok(changes) => changes,
+                Err(e) => {
+                    send_reply_err(&query, ErrorCode::NotAllowed, e).await;
+                    return stats;
+                }
+            };
+            send_json_reply(&query, registry, &changes, "content changes", &mut stats).await
+        }
+        "validate_mcap" => {
+            // the mismatch report of the Schema records of the MCAP file sent as query payload
+            // (the files of the registry host being not accessible to the clients)
+            let Some(payload) = query.payload() else {
+                send_reply_err(
+                    &query,
+                    ErrorCode::InvalidPayload,
+                    "The MCAP file is required as payload for 'validate_mcap'",
+                )
+                .await;
+                return stats;
+            };
+            let name = query
+                .parameters()
+                .get("name")
+                .unwrap_or_default()
+                .to_string();
+            let content = payload.to_bytes();
+            let report = match mcap_check::check_mcap_content(registry, &content, name) {
+                Ok(report) => report,
+                Err(e) => {
+                    send_reply_err(&query, ErrorCode::InvalidPayload, e).await;
+                    return stats;
+                }
+            };
+            send_json_reply(
+                &query,
+                registry,
+                &report,
+                "MCAP validation report",
+                &mut stats,
+            )
+            .await
+        }
+        _ => {
+            send_reply_err_details(
+                &query,
+                ErrorCode::UnknownOperation,
+                format!(
+                    "Unknown admin operation '{operation}' - available operations are: {:?}",
+                    ADMIN_OPERATIONS
+                ),
+                Some(ErrorDetails::invalid_value(
+                    "operation",
+                    operation,
+                    ADMIN_OPERATIONS,
+                )),
+            )
+            .await
+        }
+    }
+    stats
+}
+
+// Reply a value as JSON to a query, or a `serialization_failed` error mentioning what the value is
+async fn send_json_reply<T: Serialize + ?Sized>(
+    query: &Query,
+    registry: &Registry<'_>,
+    value: &T,
+    what: &str,
+    stats: &mut QueryStats,
+) {
+    match serde_json::to_string(value) {
+        Ok(response) => {
+            stats.matched = 1;
+            stats.bytes_sent += send_reply(
+                query,
+                query_reply_ke(query, registry.get_namespace()),
+                response,
+                Encoding::APPLICATION_JSON,
+            )
+            .await;
+        }
+        Err(e) => {
+            send_reply_err(
+                query,
+                ErrorCode::SerializationFailed,
+                format!("Failed to serialize {what}: {e}"),
+            )
+            .await
+        }
+    }
+}
+
+// Return true if an admin token is configured, otherwise send a `not_allowed` error reply to the
+// query of this operation changing the state of the registry (not to be left open to any peer)
+async fn require_admin_token(query: &Query, args: &Args, operation: &str) -> bool {
+    if args.admin_token.is_some() {
+        return true;
+    }
+    send_reply_err(
+        query,
+        ErrorCode::NotAllowed,
+        format!("The '{operation}' operation requires an admin token to be configured (see '--admin-token')"),
+    )
+    .await;
+    false
+}
+
+// Return true if the types can be served, otherwise (i.e. while paused for maintenance) send a
+// maintenance error reply to the query
+pub(crate) async fn admit_serving(registry: &Registry<'_>, query: &Query) -> bool {
+    let Some(maintenance) = registry.get_maintenance() else {
+        return true;
+    };
+    let message = match &maintenance.reason {
+        Some(reason) => format!("The registry is paused for maintenance: {reason}"),
+        None => "The registry is paused for maintenance".to_string(),
+    };
+    send_reply_err(query, ErrorCode::Maintenance, message).await;
+    false
+}
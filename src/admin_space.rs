@@ -0,0 +1,164 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+// The registry state in the Zenoh admin space, alongside the status of the other plugins:
+// `@/<zid>/<whatami>/status/plugins/ros2_types_registry/{version,config,health,stats}`, with the
+// health and statistics of each tenant under `.../tenants/<NAME>/`. Hosted by zenohd, it's replied
+// via the plugin status; standalone, via a Queryable on this key space.
+use std::{
+    collections::BTreeMap,
+    sync::{OnceLock, RwLock},
+};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use zenoh::{
+    bytes::Encoding,
+    key_expr::{keyexpr, KeyExpr},
+    Session,
+};
+
+use crate::{
+    args::Args, health::HealthStatus, plugin::Ros2TypesRegistryPlugin, registry::Registry,
+    stats::Statistics,
+};
+
+// Interval between the refreshes of the registry state published in the admin space
+pub(crate) const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Number of entries of the popularity report in the statistics
+const TOP_N: usize = 10;
+
+// The state published in the admin space, by key relative to the plugin status key
+static STATE: OnceLock<RwLock<BTreeMap<String, Value>>> = OnceLock::new();
+
+// The configuration of the registry published in the admin space
+#[derive(Debug, Serialize)]
+struct ConfigState<'a> {
+    tenants: Vec<&'a str>,
+    mirror: Option<&'a str>,
+    lazy_definitions: Option<usize>,
+    warm_up: bool,
+    memory_budget_mb: Option<usize>,
+    ignore: &'a [String],
+    follow_links: bool,
+    max_depth: Option<usize>,
+    same_file_system: bool,
+    strict_json: bool,
+    strict_mcap: bool,
+    mcap_dependency_order: &'a str,
+    cache_max_age: u64,
+    rate_limit: Option<u32>,
+    election: bool,
+    prefetch: bool,
+    signed_replies: bool,
+}
+
+fn state() -> &'static RwLock<BTreeMap<String, Value>> {
+    STATE.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+fn set(key: String, value: Value) {
+    if let Ok(mut state) = state().write() {
+        state.insert(key, value);
+    }
+}
+
+// Publish the version and the configuration of the registry
+pub(crate) fn init(args: &Args) {
+    let config = ConfigState {
+        tenants: args.tenant.iter().map(|t| t.name.as_str()).collect(),
+        mirror: args.mirror.as_deref(),
+        lazy_definitions: args.lazy_definitions,
+        warm_up: args.warm_up,
+        memory_budget_mb: args.memory_budget,
+        ignore: &args.ignore,
+        follow_links: !args.no_follow_links,
+        max_depth: args.max_depth,
+        same_file_system: args.same_file_system,
+        strict_json: args.strict_json,
+        strict_mcap: args.strict_mcap,
+        mcap_dependency_order: args.mcap_dependency_order.as_ref(),
+        cache_max_age: args.cache_max_age,
+        rate_limit: args.rate_limit,
+        election: args.election,
+        prefetch: args.prefetch,
+        signed_replies: args.signing_key.is_some(),
+    };
+    set("version".into(), env!("CARGO_PKG_VERSION").into());
+    match serde_json::to_value(config) {
+        Ok(config) => set("config".into(), config),
+        Err(e) => tracing::warn!("Failed to serialize the configuration for the admin space: {e}"),
+    }
+}
+
+// Refresh the health and statistics of a registry
+pub(crate) fn update(registry: &Registry, statistics: &Statistics) {
+    let prefix = match registry.get_namespace() {
+        Some(namespace) => format!("tenants/{namespace}/"),
+        None => String::new(),
+    };
+    let stats = json!({
+        "queries": statistics.latencies().values().map(|h| h.count).sum::<u64>(),
+        "popularity": statistics.top_types(TOP_N),
+        "latency": statistics.latencies(),
+        "history_gc": registry.get_history_gc_stats(),
+    });
+    set(format!("{prefix}stats"), stats);
+    match serde_json::to_value(HealthStatus::of(registry)) {
+        Ok(health) => set(format!("{prefix}health"), health),
+        Err(e) => tracing::warn!("Failed to serialize health status: {e}"),
+    }
+}
+
+// Return the state entries (with their absolute keys) matching a key expression, under the key
+// of the plugin status
+pub(crate) fn responses(plugin_status_key: &str, key_expr: &keyexpr) -> Vec<(String, Value)> {
+    let Ok(state) = state().read() else {
+        return Vec::new();
+    };
+    state
+        .iter()
+        .map(|(key, value)| (format!("{plugin_status_key}/{key}"), value))
+        .filter(|(key, _)| keyexpr::new(key.as_str()).is_ok_and(|ke| ke.intersects(key_expr)))
+        .map(|(key, value)| (key, value.clone()))
+        .collect()
+}
+
+// Reply the queries on the admin space of the standalone registry (the runtime of which doesn't
+// host the registry as a plugin), until the session is closed
+pub(crate) async fn serve(session: Session, whatami: &str) -> Result<(), String> {
+    let plugin_status_key = format!(
+        "@/{}/{whatami}/status/plugins/{}",
+        session.zid(),
+        <Ros2TypesRegistryPlugin as zenoh_plugin_trait::Plugin>::DEFAULT_NAME
+    );
+    let queryable_ke = KeyExpr::try_from(format!("{plugin_status_key}/**"))
+        .map_err(|e| format!("Invalid admin space key expression: {e}"))?;
+    tracing::debug!("Declaring Queryable on '{queryable_ke}'");
+    let queryable = session
+        .declare_queryable(queryable_ke)
+        .await
+        .map_err(|e| format!("Failed to declare queryable for the admin space: {e}"))?;
+    while let Ok(query) = queryable.recv_async().await {
+        for (key, value) in responses(&plugin_status_key, query.key_expr()) {
+            if let Err(e) = query
+                .reply(key, value.to_string())
+                .encoding(Encoding::APPLICATION_JSON)
+                .await
+            {
+                tracing::warn!("Error sending admin space reply: {e}");
+            }
+        }
+    }
+    Ok(())
+}
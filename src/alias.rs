@@ -0,0 +1,75 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Aliases of types or packages, for the long package-rename migrations across a fleet: the queries
+// on `@ros2_types/<alias>` are served with the target types, replied under the queried names, e.g.:
+//   my_vendor_msgs/msg/Imu=sensor_msgs/msg/Imu : a type alias
+//   old_msgs=new_msgs                          : a package alias, for all the types of the package
+use zenoh::key_expr::{keyexpr, OwnedKeyExpr};
+
+use crate::type_name;
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct Alias {
+    from: OwnedKeyExpr,
+    to: OwnedKeyExpr,
+    is_package: bool,
+}
+
+impl Alias {
+    // Create an alias between 2 type names (e.g. "sensor_msgs/msg/Imu") or 2 package names
+    pub fn new(from: &str, to: &str) -> Result<Self, String> {
+        let is_package = !from.contains('/');
+        let parse = |name: &str| {
+            let ke = OwnedKeyExpr::new(type_name::escape(name).into_owned())
+                .map_err(|e| format!("invalid name '{name}': {e}"))?;
+            match (ke.chunks().count(), is_package) {
+                _ if ke.is_wild() => Err(format!("invalid name '{name}': wildcards not allowed")),
+                (1, true) | (3, false) => Ok(ke),
+                _ => Err(format!(
+                    "invalid alias '{from}={to}': expected 2 package names or 2 type names (e.g. 'sensor_msgs/msg/Imu')"
+                )),
+            }
+        };
+        Ok(Alias {
+            from: parse(from)?,
+            to: parse(to)?,
+            is_package,
+        })
+    }
+
+    // If the selection of types (e.g. "old_msgs/msg/*") is on this alias, return the selection of
+    // the target types (e.g. "new_msgs/msg/*")
+    pub fn resolve(&self, selection: &keyexpr) -> Option<OwnedKeyExpr> {
+        if !self.is_package {
+            return (selection == &*self.from).then(|| self.to.clone());
+        }
+        let (package, rest) = selection.as_str().split_once('/')?;
+        (package == self.from.as_str())
+            .then(|| OwnedKeyExpr::new(format!("{}/{rest}", self.to)).ok())
+            .flatten()
+    }
+
+    // Return the name under which a target type is replied (the reverse of resolve())
+    pub fn alias_name(&self, full_name: &keyexpr) -> OwnedKeyExpr {
+        if !self.is_package {
+            return self.from.clone();
+        }
+        match full_name.as_str().split_once('/') {
+            Some((_, rest)) => {
+                OwnedKeyExpr::new(format!("{}/{rest}", self.from)).unwrap_or(full_name.into())
+            }
+            None => full_name.into(),
+        }
+    }
+}
@@ -11,12 +11,34 @@
 //   Julien Enoch, <julien.enoch@zettascale.tech>
 //
 
+use std::{
+    path::{Component, PathBuf},
+    time::Duration,
+};
+
 use clap::Parser;
 use serde_json::json;
-use zenoh::{config::WhatAmI, Config};
+use zenoh::{
+    config::WhatAmI,
+    key_expr::{keyexpr, OwnedKeyExpr},
+    Config,
+};
+
+use crate::{
+    alias::Alias,
+    registry::{McapDependencyOrder, DEFAULT_CACHE_MAX_AGE},
+    subjects::CompatibilityMode,
+};
 
 const DEFAULT_ZENOHD_LOCATOR: &str = "tcp/localhost:7447";
 
+// A set of directories served by its own registry, under its own key namespace
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Tenant {
+    pub name: String,
+    pub roots: Vec<PathBuf>,
+}
+
 #[derive(clap::Parser, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Args {
     /// A configuration file.
@@ -41,12 +63,250 @@ pub struct Args {
     /// Disable the multicast-based scouting mechanism.
     #[arg(long)]
     no_multicast_scouting: bool,
+    /// Open a plain Zenoh session in client mode, connected to an existing router (see `--connect`),
+    /// without the plugins (e.g. the REST API) of the standalone registry, for the robots already
+    /// running zenohd.
+    #[arg(long, conflicts_with_all = ["mode", "listen", "rest_http_port"])]
+    pub client_only: bool,
     /// Configures HTTP interface for the REST API (disabled by default). Accepted values:
     ///   - a port number
     ///   - a string with format `<local_ip>:<port_number>` (to bind the HTTP server to a specific interface)
     ///   - `none` to disable the REST API
     #[arg(long, value_name = "SOCKET")]
     rest_http_port: Option<String>,
+    /// Write a structured access log (JSON lines) of the received queries to this file.
+    #[arg(long, value_name = "FILE")]
+    pub access_log: Option<PathBuf>,
+    /// Log a warning for queries taking longer than this duration to be handled, in milliseconds.
+    #[arg(long, value_name = "MS", default_value = "1000", value_parser = parse_duration_ms)]
+    pub slow_query_threshold: Duration,
+    /// Limit the rate of the queries of each client (identified by its Zenoh ID, in the source info
+    /// of the queries, those without source info sharing a bucket), in queries per second, replying
+    /// a `rate_limited` error to the queries exceeding it (no limit by default).
+    #[arg(long, value_name = "QPS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub rate_limit: Option<u32>,
+    /// Maximum number of queries a client can send in a burst with `--rate-limit`.
+    #[arg(long, value_name = "N", default_value = "20", requires = "rate_limit")]
+    pub rate_limit_burst: u32,
+    /// Don't keep the definitions (.msg/.srv/.action files contents) in memory, but read them on demand,
+    /// caching at most N of them [default N: 128].
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "128")]
+    pub lazy_definitions: Option<usize>,
+    /// Only index the types files at startup, and parse them in background (or on demand when queried).
+    /// The warm-up progress is published on `@ros2_health`.
+    #[arg(long)]
+    pub warm_up: bool,
+    /// Keep the memory used by the parsed types under this budget (in MB), evicting the least recently
+    /// used ones (re-read from disk on demand). Implies `--lazy-definitions` if not set.
+    #[arg(long, value_name = "MB")]
+    pub memory_budget: Option<usize>,
+    /// Maximum number of superseded versions kept in history per type (no limit by default).
+    #[arg(long, value_name = "N")]
+    pub history_max_versions: Option<usize>,
+    /// Maximum time (in seconds) during which a superseded version is kept in history
+    /// (no limit by default).
+    #[arg(long, value_name = "SECONDS")]
+    pub history_max_age: Option<u64>,
+    /// Maximum memory used by the superseded versions kept in history, in MB (no limit by default).
+    #[arg(long, value_name = "MB")]
+    pub history_max_memory: Option<usize>,
+    /// Fail to reply a MCAP schema for types with dependencies not found in the registry, rather than
+    /// skipping those (can be overridden per query with the `strict` parameter).
+    #[arg(long)]
+    pub strict_mcap: bool,
+    /// Ignore the files and directories with a name matching this pattern when loading types,
+    /// with `*` matching any characters and `?` any single character (can be repeated).
+    /// Hidden files and directories and editors backup files are always ignored.
+    #[arg(long, value_name = "PATTERN")]
+    pub ignore: Vec<String>,
+    /// Don't follow the symbolic links to directories and files when loading types.
+    #[arg(long)]
+    pub no_follow_links: bool,
+    /// Maximum depth of the directories walked below each directory of AMENT_PREFIX_PATH when
+    /// loading types (no limit by default).
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+    /// Don't walk into other file systems than the one of each directory of AMENT_PREFIX_PATH
+    /// when loading types.
+    #[arg(long)]
+    pub same_file_system: bool,
+    /// Reject the JSON type descriptions with unknown fields (e.g. added by newer rosidl versions),
+    /// rather than ignoring those fields with a warning.
+    #[arg(long)]
+    pub strict_json: bool,
+    /// Don't declare the registry ready (its `@ros2_types` liveliness token and its `ready` health
+    /// status) while some types reference types not found in the registry.
+    #[arg(long)]
+    pub strict_dependencies: bool,
+    /// Order of the dependencies definitions in the MCAP schemas: `rosbag2` (the order in which
+    /// rosbag2 discovers them) or `lexicographic`.
+    #[arg(long, value_name = "ORDER", default_value = "rosbag2")]
+    pub mcap_dependency_order: McapDependencyOrder,
+    /// Time (in seconds) during which the clients caches can reuse a reply on the registry content
+    /// without revalidating it, announced in the replies attachments.
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_CACHE_MAX_AGE)]
+    pub cache_max_age: u64,
+    /// Export the schemas of all the types to this directory, organized and encoded as expected
+    /// by Foxglove (ready to be bundled into a `.foxe` extension), and exit.
+    #[arg(long, value_name = "DIR")]
+    pub export_foxglove_schemas: Option<PathBuf>,
+    /// Require this shared-secret token for the queries on `@ros2_env/*`, provided as `token`
+    /// parameter or as `token=<TOKEN>` in the query attachment.
+    #[arg(long, value_name = "TOKEN")]
+    pub env_token: Option<String>,
+    /// Require this shared-secret token for the queries on `@ros2_admin/**`, provided as `token`
+    /// parameter or as `token=<TOKEN>` in the query attachment.
+    #[arg(long, value_name = "TOKEN")]
+    pub admin_token: Option<String>,
+    /// Split the replies payloads larger than this size (in bytes) into chunks of this size, sent
+    /// as sequence-numbered replies on `<key_expr>/chunk/<seq>` after a manifest reply.
+    #[arg(long, value_name = "BYTES")]
+    pub max_reply_size: Option<usize>,
+    /// Sign the replies payloads with the ed25519 key of this PKCS#8 file (PEM or DER), the signature
+    /// being sent in the replies attachments.
+    #[arg(long, value_name = "FILE")]
+    pub signing_key: Option<PathBuf>,
+    /// Train a zstd dictionary over the definitions and type descriptions of all the types, write it
+    /// to this file (to be shipped to the clients and loaded with `--zstd-dictionary`), and exit.
+    #[arg(long, value_name = "FILE")]
+    pub train_zstd_dictionary: Option<PathBuf>,
+    /// Compress the replies on `@ros2_types/**` queried with a `compress=zstd-dict` parameter with
+    /// the zstd dictionary of this file (trained with `--train-zstd-dictionary`).
+    #[arg(long, value_name = "FILE")]
+    pub zstd_dictionary: Option<PathBuf>,
+    /// Export a snapshot of all the types (descriptions, hashes and definitions) to this file,
+    /// to be imported on another host with `--import-snapshot`, and exit.
+    #[arg(long, value_name = "FILE")]
+    pub export_snapshot: Option<PathBuf>,
+    /// Load the types from this snapshot file (exported with `--export-snapshot`) rather than
+    /// from AMENT_PREFIX_PATH.
+    #[arg(long, value_name = "FILE", conflicts_with = "memory_budget")]
+    pub import_snapshot: Option<PathBuf>,
+    /// Export the ros1_bridge mapping rules of all the messages convertible to ROS 1 to this YAML
+    /// file, with a report on the fields conversion, and exit.
+    #[arg(long, value_name = "FILE")]
+    pub export_ros1_bridge_mapping: Option<PathBuf>,
+    /// Validate the Schema records of this MCAP file against the current definitions and type
+    /// hashes of the registry, print the mismatch report (as JSON) and exit (with an error if
+    /// any schema doesn't match).
+    #[arg(long, value_name = "FILE")]
+    pub validate_mcap: Option<PathBuf>,
+    /// Rewrite this MCAP file replacing its missing or truncated Schema records with the
+    /// registry's MCAP schemas (the channels, messages and metadata being copied as is), and exit.
+    #[arg(long, value_name = "FILE")]
+    pub repair_mcap: Option<PathBuf>,
+    /// The MCAP file written by `--repair-mcap` [default: `<FILE>` with a `.repaired.mcap`
+    /// extension].
+    #[arg(long, value_name = "FILE", requires = "repair_mcap")]
+    pub repair_mcap_output: Option<PathBuf>,
+    /// Exercise the replies pipeline of every loaded type (JSON descriptions serialization, type
+    /// hash verification, MCAP schema and default instance generation), print the failures report
+    /// (as JSON) and exit (with an error if any type fails).
+    #[arg(long, conflicts_with = "mirror")]
+    pub self_test: bool,
+    /// Mirror the registry served under this key namespace (or without namespace if not set),
+    /// rather than loading the types from AMENT_PREFIX_PATH: only the new or changed types are
+    /// periodically fetched from it.
+    #[arg(
+        long,
+        value_name = "NAMESPACE",
+        num_args = 0..=1,
+        default_missing_value = "",
+        conflicts_with_all = ["import_snapshot", "memory_budget"]
+    )]
+    pub mirror: Option<String>,
+    /// Interval (in seconds) between the synchronizations of the mirror registry.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value = "60",
+        requires = "mirror"
+    )]
+    pub mirror_interval: u64,
+    /// The token required by the upstream registry for its `@ros2_admin/**` key space (see
+    /// `--admin-token`), sent in the attachment of the mirror queries.
+    #[arg(long, value_name = "TOKEN", requires = "mirror")]
+    pub mirror_token: Option<String>,
+    /// Pre-generate the MCAP schemas and JSON descriptions of the types in use on the ROS graph
+    /// (discovered from the rmw_zenoh liveliness tokens), to serve their first requests from cache.
+    #[arg(long)]
+    pub prefetch: bool,
+    /// Take part in an election among redundant registries with identical content: only the leader
+    /// replies to the wildcard queries, the others staying warm standbys.
+    #[arg(long)]
+    pub election: bool,
+    /// Publish and make queryable the MCAP schema of each topic routed by zenoh-bridge-ros2dds,
+    /// under `@ros2_topic_schemas/<topic key expression>`.
+    #[arg(long)]
+    pub bridge_schemas: bool,
+    /// Serve the types found in those directories (separated by `:`, or `;` on Windows) under the
+    /// `<NAME>/` key namespace (e.g. `<NAME>/@ros2_types/**`), with their own registry and
+    /// statistics (can be repeated). The types of AMENT_PREFIX_PATH are still served without
+    /// namespace, if it's defined.
+    #[arg(long, value_name = "NAME=DIRS", value_parser = parse_tenant)]
+    pub tenant: Vec<Tenant>,
+    /// Serve the queries on `@ros2_types/<ALIAS>` with the target type(s), replied under the
+    /// queried names: ALIAS and TARGET are either 2 type names (e.g.
+    /// `my_vendor_msgs/msg/Imu=sensor_msgs/msg/Imu`) or 2 package names (e.g. `old_msgs=new_msgs`)
+    /// (can be repeated).
+    #[arg(long, value_name = "ALIAS=TARGET", value_parser = parse_alias)]
+    pub alias: Vec<Alias>,
+    /// Don't serve the types matching this key expression (e.g. `proprietary_msgs/**`), or of this
+    /// package, even if loaded: the queries on those types get a `not_served` error (can be repeated).
+    #[arg(long, value_name = "PATTERN", value_parser = parse_deny)]
+    pub deny: Vec<OwnedKeyExpr>,
+    /// Load the virtual types defined in this JSON file (a list of type names with their fields,
+    /// referencing primitive types or types of the registry), served like the other types.
+    #[arg(long, value_name = "FILE", conflicts_with = "mirror")]
+    pub virtual_types: Option<PathBuf>,
+    /// Compatibility mode of the subjects versions under `@ros2_subjects/**`, as in the Kafka Schema
+    /// Registry: `NONE`, `BACKWARD`, `BACKWARD_TRANSITIVE`, `FORWARD`, `FORWARD_TRANSITIVE`, `FULL`
+    /// or `FULL_TRANSITIVE`.
+    #[arg(long, value_name = "MODE", default_value = "BACKWARD")]
+    pub subject_compatibility: CompatibilityMode,
+    /// Export the tracing spans to this OpenTelemetry collector endpoint via OTLP/gRPC
+    /// (e.g. `http://localhost:4317`).
+    #[cfg(feature = "otlp")]
+    #[arg(long, value_name = "ENDPOINT")]
+    pub otlp_endpoint: Option<String>,
+    /// Listen on this address for HTTP requests on the dedicated routes
+    /// (`/types/{pkg}/{kind}/{name}`, `/packages`, `/packages/{pkg}`, `/health`), e.g. `0.0.0.0:8080`.
+    #[cfg(feature = "http")]
+    #[arg(long, value_name = "ADDR")]
+    pub http_listen: Option<std::net::SocketAddr>,
+    /// Listen on this address for gRPC requests on the registry service
+    /// (see `proto/registry.proto`), e.g. `0.0.0.0:50051`.
+    #[cfg(feature = "grpc")]
+    #[arg(long, value_name = "ADDR")]
+    pub grpc_listen: Option<std::net::SocketAddr>,
+    /// Listen on this address for Foxglove Studio connections via the Foxglove WebSocket
+    /// protocol (e.g. `0.0.0.0:8765`), advertising the ROS 2 topics with their schemas.
+    #[cfg(feature = "foxglove")]
+    #[arg(long, value_name = "ADDR")]
+    pub foxglove_listen: Option<std::net::SocketAddr>,
+    /// Forward the ROS 2 topics data from Zenoh to the Foxglove Studio subscribers
+    /// (otherwise only the channels and their schemas are advertised).
+    #[cfg(feature = "foxglove")]
+    #[arg(long, requires = "foxglove_listen")]
+    pub foxglove_proxy_data: bool,
+    /// Register the registry as a Windows service (started automatically, as LocalSystem), running
+    /// with the other arguments of this command line, and exit. The paths in those arguments must
+    /// be absolute.
+    #[cfg(windows)]
+    #[arg(long, conflicts_with_all = ["uninstall_service", "service"])]
+    pub install_service: bool,
+    /// Stop and remove the Windows service, and exit.
+    #[cfg(windows)]
+    #[arg(long, conflicts_with = "service")]
+    pub uninstall_service: bool,
+    /// Run under the Windows Service Control Manager (as registered by `--install-service`).
+    #[cfg(windows)]
+    #[arg(long)]
+    pub service: bool,
+    /// The name of the Windows service.
+    #[cfg(windows)]
+    #[arg(long, value_name = "NAME", default_value = "ros2-types-registry")]
+    pub service_name: String,
 }
 
 impl From<Args> for Config {
@@ -92,6 +352,15 @@ impl From<&Args> for Config {
                 .insert_json5("listen/endpoints", &json!(args.listen).to_string())
                 .unwrap();
         }
+        if args.client_only {
+            // even if the configuration file sets another mode or plugins
+            config
+                .insert_json5("mode", &json!(WhatAmI::Client.to_str()).to_string())
+                .unwrap();
+            config
+                .insert_json5("plugins_loading/enabled", &json!(false).to_string())
+                .unwrap();
+        }
         if args.no_multicast_scouting {
             config
                 .insert_json5("scouting/multicast/enabled", &json!(false).to_string())
@@ -126,7 +395,94 @@ impl From<&Args> for Config {
     }
 }
 
-pub(crate) fn parse_args() -> Config {
-    let args = Args::parse();
-    args.into()
+fn parse_duration_ms(s: &str) -> Result<Duration, String> {
+    s.parse::<u64>()
+        .map(Duration::from_millis)
+        .map_err(|e| format!("expected a duration in milliseconds: {e}"))
+}
+
+pub(crate) fn parse_args() -> Args {
+    Args::parse()
+}
+
+// Build the arguments of a registry running as a zenohd plugin from its configuration, where each
+// entry is an option of the command line (e.g. `"rate_limit": 10` for `--rate-limit 10`): `true`
+// for a flag, a list for a repeatable option. The entries set by zenohd (starting with `__`) are
+// ignored.
+pub(crate) fn args_from_config(config: &serde_json::Value) -> Result<Args, String> {
+    let entries = config
+        .as_object()
+        .ok_or("expected a JSON object as configuration")?;
+    let mut cli_args = vec![env!("CARGO_PKG_NAME").to_string()];
+    for (key, value) in entries.iter().filter(|(key, _)| !key.starts_with("__")) {
+        let option = format!("--{}", key.replace('_', "-"));
+        let values = match value {
+            serde_json::Value::Array(values) => values.as_slice(),
+            value => std::slice::from_ref(value),
+        };
+        for value in values {
+            match value {
+                serde_json::Value::Null | serde_json::Value::Bool(false) => {}
+                serde_json::Value::Bool(true) => cli_args.push(option.clone()),
+                serde_json::Value::String(s) => cli_args.extend([option.clone(), s.clone()]),
+                serde_json::Value::Number(n) => cli_args.extend([option.clone(), n.to_string()]),
+                _ => return Err(format!("unsupported value for '{key}': {value}")),
+            }
+        }
+    }
+    Args::try_parse_from(cli_args).map_err(|e| e.to_string())
+}
+
+fn parse_tenant(s: &str) -> Result<Tenant, String> {
+    let (name, dirs) = s
+        .split_once('=')
+        .ok_or("expected NAME=DIRS, with DIRS separated by ':' (';' on Windows)")?;
+    match keyexpr::new(name) {
+        Ok(ke) if !ke.is_wild() && !ke.as_str().split('/').any(|c| c.starts_with('@')) => {}
+        _ => {
+            return Err(format!(
+                "invalid tenant name '{name}': expected a key expression without wildcards nor chunks starting with '@'"
+            ))
+        }
+    }
+    let roots: Vec<PathBuf> = std::env::split_paths(dirs).map(drive_root).collect();
+    if roots.iter().any(|r| r.as_os_str().is_empty()) {
+        return Err(format!("empty directory in '{dirs}'"));
+    }
+    Ok(Tenant {
+        name: name.to_string(),
+        roots,
+    })
+}
+
+// Return the root directory of a drive for a bare Windows drive prefix (e.g. "C:\" for "C:"), which
+// otherwise designates the current directory on this drive (a no-op on the other platforms)
+pub(crate) fn drive_root(path: PathBuf) -> PathBuf {
+    let mut components = path.components();
+    let is_bare_prefix = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Prefix(_)), None)
+    );
+    if !is_bare_prefix {
+        return path;
+    }
+    let mut root = path.into_os_string();
+    root.push(std::path::MAIN_SEPARATOR_STR);
+    root.into()
+}
+
+fn parse_alias(s: &str) -> Result<Alias, String> {
+    let (from, to) = s
+        .split_once('=')
+        .ok_or("expected ALIAS=TARGET, with 2 type names or 2 package names")?;
+    Alias::new(from, to)
+}
+
+fn parse_deny(s: &str) -> Result<OwnedKeyExpr, String> {
+    let ke = OwnedKeyExpr::new(s).map_err(|e| format!("invalid key expression '{s}': {e}"))?;
+    // a package name stands for all its types
+    if !ke.is_wild() && !s.contains('/') {
+        return Ok(ke / keyexpr::new("**").expect("valid key expression"));
+    }
+    Ok(ke)
 }
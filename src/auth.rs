@@ -0,0 +1,45 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Lightweight authorization of the queries on sensitive key spaces, when the Zenoh ACLs aren't
+// deployed: the queries must provide a shared-secret token, either as `token` parameter or as a
+// `token=<token>` entry of the query attachment (entries separated by `;`).
+use zenoh::query::Query;
+
+// Return true if the query provides this token (always true if no token is required)
+pub(crate) fn is_authorized(query: &Query, token: Option<&str>) -> bool {
+    let Some(expected) = token else {
+        return true;
+    };
+    if query
+        .parameters()
+        .get("token")
+        .is_some_and(|t| constant_time_eq(t.as_bytes(), expected.as_bytes()))
+    {
+        return true;
+    }
+    query
+        .attachment()
+        .and_then(|attachment| attachment.try_to_string().ok())
+        .is_some_and(|attachment| {
+            attachment
+                .split(';')
+                .filter_map(|entry| entry.strip_prefix("token="))
+                .any(|t| constant_time_eq(t.as_bytes(), expected.as_bytes()))
+        })
+}
+
+// Compare 2 tokens in a time not depending on their common prefix
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,134 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// A compact binary encoding of a type description, for constrained clients (e.g. zenoh-pico or
+// micro-ROS devices) that can't embed a JSON parser. All integers are little-endian.
+//
+//   header (12 bytes):
+//     magic            [u8; 4]  "RTB1"
+//     strings_count    u16
+//     types_count      u16
+//     type_hash        u16      index of the type hash in the strings table
+//     reserved         u16      0
+//   strings table (strings_count entries):
+//     length           u16
+//     bytes            [u8; length]   UTF-8, not NUL-terminated
+//   types table (types_count entries, the described type first, then its dependencies):
+//     type_name        u16      index in the strings table
+//     fields_count     u16
+//     fields (fields_count entries of 16 bytes):
+//       name           u16      index in the strings table
+//       type_id        u8       FieldTypeId value
+//       reserved       u8       0
+//       capacity       u32
+//       string_capacity u32
+//       nested_type    u16      index in the types table, or NONE
+//       default_value  u16      index in the strings table, or NONE
+use std::collections::HashMap;
+
+use crate::{type_description::IndividualTypeDescription, type_info::TypeInfo};
+
+const MAGIC: &[u8; 4] = b"RTB1";
+// Index value for "no nested type" or "no default value"
+const NONE: u16 = u16::MAX;
+
+// Encode the type description of a type and of its dependencies
+pub(crate) fn encode(t: &TypeInfo) -> Result<Vec<u8>, String> {
+    let description = &t.type_description.type_description_msg;
+    let types: Vec<&IndividualTypeDescription> = std::iter::once(&description.type_description)
+        .chain(description.referenced_type_descriptions.iter())
+        .collect();
+    if types.len() >= NONE as usize {
+        return Err(format!("Too many dependencies for {}", t.full_name));
+    }
+    let type_indexes: HashMap<&str, u16> = types
+        .iter()
+        .enumerate()
+        .map(|(i, d)| (&*d.type_name, i as u16))
+        .collect();
+
+    let mut strings = StringsTable::default();
+    let hash_index = strings.index(&t.type_hash)?;
+    let mut types_table = Vec::new();
+    for d in &types {
+        put_u16(&mut types_table, strings.index(&d.type_name)?);
+        put_u16(
+            &mut types_table,
+            u16::try_from(d.fields.len())
+                .map_err(|_| format!("Too many fields in {}", d.type_name))?,
+        );
+        for field in &d.fields {
+            let field_type = &field.r#type;
+            put_u16(&mut types_table, strings.index(&field.name)?);
+            types_table.push(field_type.type_id as u8);
+            types_table.push(0);
+            types_table.extend_from_slice(&field_type.capacity.to_le_bytes());
+            types_table.extend_from_slice(&field_type.string_capacity.to_le_bytes());
+            let nested_type = match &*field_type.nested_type_name {
+                "" => NONE,
+                name => *type_indexes.get(name).ok_or(format!(
+                    "Nested type {name} of {}.{} not found in referenced type descriptions",
+                    d.type_name, field.name
+                ))?,
+            };
+            put_u16(&mut types_table, nested_type);
+            let default_value = match field.default_value.as_deref() {
+                None | Some("") => NONE,
+                Some(value) => strings.index(value)?,
+            };
+            put_u16(&mut types_table, default_value);
+        }
+    }
+
+    let mut buf = Vec::with_capacity(12 + strings.bytes.len() + types_table.len());
+    buf.extend_from_slice(MAGIC);
+    put_u16(&mut buf, strings.count);
+    put_u16(&mut buf, types.len() as u16);
+    put_u16(&mut buf, hash_index);
+    put_u16(&mut buf, 0);
+    buf.extend_from_slice(&strings.bytes);
+    buf.extend_from_slice(&types_table);
+    Ok(buf)
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+// The strings table, where each distinct string is written once
+#[derive(Default)]
+struct StringsTable<'a> {
+    indexes: HashMap<&'a str, u16>,
+    count: u16,
+    bytes: Vec<u8>,
+}
+
+impl<'a> StringsTable<'a> {
+    // Return the index of a string, adding it to the table if not already present
+    fn index(&mut self, s: &'a str) -> Result<u16, String> {
+        if let Some(index) = self.indexes.get(s) {
+            return Ok(*index);
+        }
+        if self.count == NONE {
+            return Err("Too many strings for the binary schema".into());
+        }
+        let len = u16::try_from(s.len())
+            .map_err(|_| format!("String too long for the binary schema: {s}"))?;
+        put_u16(&mut self.bytes, len);
+        self.bytes.extend_from_slice(s.as_bytes());
+        let index = self.count;
+        self.indexes.insert(s, index);
+        self.count += 1;
+        Ok(index)
+    }
+}
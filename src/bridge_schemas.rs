@@ -0,0 +1,177 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Integration with zenoh-bridge-ros2dds: the topics routed by the bridges are discovered from
+// their liveliness tokens, and the MCAP schema of each topic is published and made queryable
+// under a topic-keyed key space, so that recorders don't need to know the ROS type names:
+//   @ros2_topic_schemas/<zenoh key expression of the topic>
+use std::collections::HashMap;
+
+use zenoh::{
+    bytes::Encoding,
+    key_expr::{
+        format::{kedefine, keformat},
+        OwnedKeyExpr,
+    },
+    query::ReplyKeyExpr,
+    sample::SampleKind,
+    Session,
+};
+
+use crate::{chunking, keformat_ros2_types, rate_limit, type_name};
+
+kedefine!(
+    // Key expression pattern for the MCAP schemas of the topics routed by zenoh-bridge-ros2dds
+    pub(crate) keformat_ros2_topic_schemas: "@ros2_topic_schemas/${topic:**}",
+);
+
+// Liveliness tokens of the publishers routed by zenoh-bridge-ros2dds, with format:
+// @/<zid>/@ros2_lv/MP/<topic key expr>/<type name>/<qos>  (where '/' in names are replaced with '§')
+const KE_BRIDGE_PUBLISHERS_LIVELINESS: &str = "@/*/@ros2_lv/MP/**";
+const BRIDGE_SLASH_REPLACEMENT: char = '§';
+
+// A topic routed by a bridge, with its MCAP schema
+struct TopicSchema {
+    type_name: String,
+    schema: String,
+    publishers: usize,
+}
+
+// Run the announcement of the topics schemas, until the session is closed
+pub(crate) async fn run(session: Session) -> Result<(), String> {
+    let liveliness_subscriber = session
+        .liveliness()
+        .declare_subscriber(KE_BRIDGE_PUBLISHERS_LIVELINESS)
+        .history(true)
+        .await
+        .map_err(|e| {
+            format!("Failed to subscribe to zenoh-bridge-ros2dds liveliness tokens: {e}")
+        })?;
+    let queryable_ke = keformat!(keformat_ros2_topic_schemas::formatter(), topic = "**")
+        .map_err(|e| format!("Failed to build topic schemas key expression: {e}"))?;
+    let queryable = session
+        .declare_queryable(&queryable_ke)
+        .await
+        .map_err(|e| format!("Failed to declare queryable for topic schemas: {e}"))?;
+    tracing::info!("Announcing zenoh-bridge-ros2dds topics schemas on '{queryable_ke}'");
+
+    // the topics schemas, per topic key expression
+    let mut topics: HashMap<OwnedKeyExpr, TopicSchema> = HashMap::new();
+    loop {
+        tokio::select! {
+            sample = liveliness_subscriber.recv_async() => {
+                let Ok(sample) = sample else {
+                    return Err("zenoh-bridge-ros2dds liveliness subscriber closed".into());
+                };
+                let Some((topic, type_name)) = parse_bridge_token(sample.key_expr().as_str()) else {
+                    continue;
+                };
+                let Ok(schema_ke) = keformat!(keformat_ros2_topic_schemas::formatter(), topic = &topic) else {
+                    tracing::warn!("Invalid topic key expression '{topic}' in zenoh-bridge-ros2dds liveliness token");
+                    continue;
+                };
+                match sample.kind() {
+                    SampleKind::Put => {
+                        if let Some(t) = topics.get_mut(&schema_ke) {
+                            t.publishers += 1;
+                            continue;
+                        }
+                        let schema = match get_mcap_schema(&session, &type_name).await {
+                            Ok(schema) => schema,
+                            Err(e) => {
+                                tracing::warn!("No schema announced for topic '{topic}': {e}");
+                                continue;
+                            }
+                        };
+                        tracing::debug!("Announce schema of topic '{topic}' ({type_name}) on '{schema_ke}'");
+                        if let Err(e) = session
+                            .put(schema_ke.clone(), schema.clone())
+                            .encoding(Encoding::TEXT_PLAIN)
+                            .await
+                        {
+                            tracing::warn!("Failed to publish schema of topic '{topic}': {e}");
+                        }
+                        topics.insert(schema_ke, TopicSchema { type_name, schema, publishers: 1 });
+                    }
+                    SampleKind::Delete => {
+                        if let Some(t) = topics.get_mut(&schema_ke) {
+                            t.publishers -= 1;
+                            if t.publishers == 0 {
+                                tracing::debug!("Topic '{topic}' has no more publisher");
+                                topics.remove(&schema_ke);
+                            }
+                        }
+                    }
+                }
+            },
+
+            query = queryable.recv_async() => {
+                let Ok(query) = query else {
+                    return Err("Topic schemas queryable closed".into());
+                };
+                for (schema_ke, t) in &topics {
+                    if !query.key_expr().intersects(schema_ke) {
+                        continue;
+                    }
+                    tracing::trace!("Reply schema of {} on '{schema_ke}'", t.type_name);
+                    if let Err(e) = query
+                        .reply(schema_ke.clone(), t.schema.as_str())
+                        .encoding(Encoding::TEXT_PLAIN)
+                        .await
+                    {
+                        tracing::warn!("Failed to reply schema on '{schema_ke}': {e}");
+                    }
+                }
+            },
+        }
+    }
+}
+
+// Parse a zenoh-bridge-ros2dds liveliness token, returning the topic key expression and the type name
+fn parse_bridge_token(ke: &str) -> Option<(String, String)> {
+    let chunks: Vec<&str> = ke.split('/').collect();
+    if chunks.len() < 6 {
+        return None;
+    }
+    let unescape = |s: &str| s.replace(BRIDGE_SLASH_REPLACEMENT, "/");
+    Some((unescape(chunks[4]), unescape(chunks[5])))
+}
+
+// Get the MCAP schema of a type from the registry, via its own queryable
+async fn get_mcap_schema(session: &Session, name: &str) -> Result<String, String> {
+    let ke = keformat!(
+        keformat_ros2_types::formatter(),
+        type_name = type_name::escape(name)
+    )
+    .map_err(|e| format!("Invalid type name {name}: {e}"))?;
+    let replies = session
+        .get(format!("{ke}?format=Mcap"))
+        .accept_replies(ReplyKeyExpr::Any)
+        .attachment(rate_limit::local_attachment())
+        .await
+        .map_err(|e| format!("Failed to query type {name}: {e}"))?;
+    let Some(reply) = chunking::recv_replies(replies).await.into_iter().next() else {
+        return Err(format!("Type {name} not found in registry"));
+    };
+    match reply {
+        Ok(sample) => sample
+            .payload()
+            .try_to_string()
+            .map(|s| s.into_owned())
+            .map_err(|e| format!("Invalid schema for type {name}: {e}")),
+        Err(e) => Err(format!(
+            "Error getting schema for type {name}: {}",
+            e.payload().try_to_string().unwrap_or_default()
+        )),
+    }
+}
@@ -0,0 +1,135 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+// The JSON Canonicalization Scheme (RFC 8785): no whitespace, the object members sorted by the
+// UTF-16 code units of their names, the strings with the minimal escaping and the numbers
+// serialized as ECMAScript does (as IEEE 754 doubles). Two canonical JSON payloads of the same
+// content are thus byte-for-byte identical, whatever the registry or the run producing them.
+use std::fmt::Write;
+
+use serde_json::Value;
+
+// Canonicalize a JSON document
+pub(crate) fn canonicalize(json: &str) -> Result<String, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid JSON to canonicalize: {e}"))?;
+    let mut canonical = String::with_capacity(json.len());
+    write_value(&mut canonical, &value)?;
+    Ok(canonical)
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), String> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            let n = n
+                .as_f64()
+                .ok_or_else(|| format!("Number {n} not representable as a double"))?;
+            write_number(out, n)?;
+        }
+        Value::String(s) => write_string(out, s),
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, value)?;
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            let mut members: Vec<(&String, &Value)> = members.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (name, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, name);
+                out.push(':');
+                write_value(out, value)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+// Write a string with only the escaping required by JSON (as ECMAScript's JSON.stringify)
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// Write a number as ECMAScript's Number.prototype.toString(), from its shortest round-trip
+// decimal digits
+fn write_number(out: &mut String, n: f64) -> Result<(), String> {
+    if !n.is_finite() {
+        return Err(format!("Number {n} not allowed in JSON"));
+    }
+    if n == 0.0 {
+        out.push('0');
+        return Ok(());
+    }
+    if n < 0.0 {
+        out.push('-');
+    }
+    // e.g. "1.2345e-7" => digits "12345" and exponent -7
+    let scientific = format!("{:e}", n.abs());
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .ok_or_else(|| format!("Unexpected number format {scientific}"))?;
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent
+        .parse()
+        .map_err(|e| format!("Unexpected number format {scientific}: {e}"))?;
+    let k = digits.len() as i32;
+    // position of the decimal point relatively to the digits
+    let n = exponent + 1;
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat('0').take((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat('0').take(-n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let _ = write!(out, "e{}{}", if n > 0 { '+' } else { '-' }, (n - 1).abs());
+    }
+    Ok(())
+}
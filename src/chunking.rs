@@ -0,0 +1,270 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Chunked transfer of the replies exceeding a maximum size (e.g. a full dependency closure of
+// several MB), for constrained transports and clients: a manifest reply is sent on the reply key
+// expression, followed by the sequence-numbered chunks replies on its `<key_expr>/chunk/<seq>`
+// sub-keys, to be reassembled by the client (see `recv_replies` for the in-process ones).
+use std::{collections::HashMap, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    handlers::FifoChannelHandler,
+    key_expr::KeyExpr,
+    query::{Query, Reply},
+};
+
+use crate::{reply::CacheControl, signing, ErrorCode};
+
+// The maximum size of a reply payload, set at startup if configured
+static MAX_REPLY_SIZE: OnceLock<usize> = OnceLock::new();
+
+// The manifest of a chunked reply
+#[derive(Debug, Serialize, Deserialize)]
+struct ChunkManifest<'a> {
+    chunks: usize,    // number of chunks
+    size: usize,      // total size of the payload
+    encoding: String, // encoding of the payload
+    etag: &'a str,    // content hash of the payload
+}
+
+// A reply of the registry to an in-process client, with its payload reassembled if it was chunked
+#[derive(Debug)]
+pub(crate) struct ReplySample {
+    key_expr: KeyExpr<'static>,
+    encoding: Encoding,
+    payload: ZBytes,
+}
+
+impl ReplySample {
+    #[cfg_attr(not(any(feature = "http", feature = "grpc")), allow(dead_code))]
+    pub(crate) fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    #[cfg_attr(not(any(feature = "http", feature = "grpc")), allow(dead_code))]
+    pub(crate) fn encoding(&self) -> &Encoding {
+        &self.encoding
+    }
+
+    pub(crate) fn payload(&self) -> &ZBytes {
+        &self.payload
+    }
+}
+
+// An error reply of the registry to an in-process client
+#[derive(Debug)]
+pub(crate) struct ErrorReply {
+    encoding: Encoding,
+    payload: ZBytes,
+}
+
+impl ErrorReply {
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    pub(crate) fn encoding(&self) -> &Encoding {
+        &self.encoding
+    }
+
+    pub(crate) fn payload(&self) -> &ZBytes {
+        &self.payload
+    }
+}
+
+// Set the maximum size of the replies payloads (in bytes) above which they are chunked
+pub(crate) fn init(max_reply_size: usize) -> Result<(), String> {
+    MAX_REPLY_SIZE
+        .set(max_reply_size.max(1))
+        .map_err(|_| "Maximum reply size already set".to_string())
+}
+
+// Return the size of the chunks if a payload of this size must be chunked
+pub(crate) fn chunk_size(payload_len: usize) -> Option<usize> {
+    MAX_REPLY_SIZE
+        .get()
+        .copied()
+        .filter(|max| payload_len > *max)
+}
+
+// Send a payload as a manifest reply followed by its chunks replies, returning the number of
+// payload bytes sent. The attachment of the manifest reply has the metadata of the whole payload
+// (e.g. its compression), if any, and its signature, if signing is configured.
+pub(crate) async fn send_chunked_reply(
+    query: &Query,
+    key_expr: KeyExpr<'static>,
+    payload: ZBytes,
+    encoding: Encoding,
+    etag: &str,
+    cache_control: &CacheControl,
+    metadata: &[(&str, &str)],
+    chunk_size: usize,
+) -> usize {
+    let bytes = payload.to_bytes();
+    let chunks: Vec<&[u8]> = bytes.chunks(chunk_size).collect();
+    let manifest = ChunkManifest {
+        chunks: chunks.len(),
+        size: bytes.len(),
+        encoding: encoding.to_string(),
+        etag,
+    };
+    let manifest = match serde_json::to_string(&manifest) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            tracing::warn!("Error serializing chunks manifest of {key_expr}: {e}");
+            return 0;
+        }
+    };
+    let mut attachment = cache_control.attachment(etag, false);
+    for (key, value) in metadata {
+        attachment.push_str(&format!(";{key}={value}"));
+    }
+    attachment.push_str(";chunked");
+    if let Some(signature) = signing::sign(&bytes) {
+        attachment.push_str(";signature=");
+        attachment.push_str(&signature);
+    }
+    if let Err(e) = query
+        .reply(key_expr.clone(), manifest)
+        .encoding(Encoding::APPLICATION_JSON)
+        .attachment(attachment)
+        .await
+    {
+        tracing::warn!("Error sending reply for {}: {e}", query.key_expr());
+        return 0;
+    }
+
+    let mut sent = 0;
+    for (seq, chunk) in chunks.iter().enumerate() {
+        let chunk_ke = match KeyExpr::try_from(format!("{key_expr}/chunk/{seq}")) {
+            Ok(ke) => ke,
+            Err(e) => {
+                tracing::warn!("Invalid chunk key expression for {key_expr}: {e}");
+                return sent;
+            }
+        };
+        match query
+            .reply(chunk_ke, chunk.to_vec())
+            .encoding(encoding.clone())
+            .attachment(format!("etag={etag};seq={seq};chunks={}", chunks.len()))
+            .await
+        {
+            Ok(()) => sent += chunk.len(),
+            Err(e) => {
+                tracing::warn!("Error sending chunk {seq} of {key_expr}: {e}");
+                return sent;
+            }
+        }
+    }
+    sent
+}
+
+// Receive all the replies of a query, in order, with the chunked ones reassembled. The query must
+// accept the replies on any key expression (`ReplyKeyExpr::Any`), for the chunks replies to be
+// received on their sub-keys.
+pub(crate) async fn recv_replies(
+    replies: FifoChannelHandler<Reply>,
+) -> Vec<Result<ReplySample, ErrorReply>> {
+    // the replies, with the manifests of the chunked ones to reassemble
+    let mut received: Vec<(Result<ReplySample, ErrorReply>, bool)> = Vec::new();
+    // the chunks, by key expression of their chunked reply and sequence number
+    let mut chunks: HashMap<(String, usize), Vec<u8>> = HashMap::new();
+    while let Ok(reply) = replies.recv_async().await {
+        let sample = match reply.into_result() {
+            Ok(sample) => sample,
+            Err(err) => {
+                received.push((
+                    Err(ErrorReply {
+                        encoding: err.encoding().clone(),
+                        payload: err.payload().clone(),
+                    }),
+                    false,
+                ));
+                continue;
+            }
+        };
+        let attachment = sample
+            .attachment()
+            .and_then(|a| a.try_to_string().ok().map(|a| a.into_owned()))
+            .unwrap_or_default();
+        if attachment.split(';').any(|item| item.starts_with("seq=")) {
+            let chunk = sample
+                .key_expr()
+                .as_str()
+                .rsplit_once("/chunk/")
+                .and_then(|(key_expr, seq)| Some((key_expr.to_string(), seq.parse().ok()?)));
+            if let Some(chunk) = chunk {
+                chunks.insert(chunk, sample.payload().to_bytes().into_owned());
+                continue;
+            }
+        }
+        let is_manifest = attachment.split(';').any(|item| item == "chunked");
+        received.push((
+            Ok(ReplySample {
+                key_expr: sample.key_expr().clone(),
+                encoding: sample.encoding().clone(),
+                payload: sample.payload().clone(),
+            }),
+            is_manifest,
+        ));
+    }
+
+    received
+        .into_iter()
+        .map(|(result, is_manifest)| match result {
+            Ok(manifest) if is_manifest => reassemble(manifest, &mut chunks),
+            result => result,
+        })
+        .collect()
+}
+
+// Reassemble the payload of a chunked reply from its manifest and its chunks
+fn reassemble(
+    manifest: ReplySample,
+    chunks: &mut HashMap<(String, usize), Vec<u8>>,
+) -> Result<ReplySample, ErrorReply> {
+    let key_expr = manifest.key_expr.to_string();
+    let error = |message: String| {
+        tracing::warn!("{message}");
+        ErrorReply {
+            encoding: Encoding::APPLICATION_JSON,
+            payload: serde_json::json!({ "code": ErrorCode::Internal, "message": message })
+                .to_string()
+                .into(),
+        }
+    };
+    let bytes = manifest.payload.to_bytes();
+    let description: ChunkManifest = serde_json::from_slice(&bytes)
+        .map_err(|e| error(format!("Invalid chunks manifest of {key_expr}: {e}")))?;
+    let mut payload = Vec::new();
+    for seq in 0..description.chunks {
+        let chunk = chunks.remove(&(key_expr.clone(), seq)).ok_or_else(|| {
+            error(format!(
+                "Chunk {seq} of {key_expr} not received ({} chunks)",
+                description.chunks
+            ))
+        })?;
+        payload.extend_from_slice(&chunk);
+    }
+    if payload.len() != description.size {
+        return Err(error(format!(
+            "Reassembled payload of {key_expr} has {} bytes instead of {}",
+            payload.len(),
+            description.size
+        )));
+    }
+    Ok(ReplySample {
+        key_expr: manifest.key_expr,
+        encoding: Encoding::from(description.encoding),
+        payload: payload.into(),
+    })
+}
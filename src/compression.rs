@@ -0,0 +1,96 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Compression of the replies payloads with a zstd dictionary trained over the schemas of the
+// registry. The payloads are small but highly redundant (same field types, comments...), hence
+// a dictionary shared by a fleet gives far better ratios than a per-message compression.
+use std::{path::Path, sync::OnceLock};
+
+use zenoh::{bytes::Encoding, key_expr::keyexpr};
+use zstd::dict::EncoderDictionary;
+
+use crate::registry::Registry;
+
+// The value of the `compress` parameter for the compression with the dictionary
+pub(crate) const ZSTD_DICT: &str = "zstd-dict";
+
+// Maximum size of a trained dictionary (the zstd CLI default)
+const DICTIONARY_MAX_SIZE: usize = 112_640;
+
+// zstd compression level (the zstd default, fast enough for the replies on the fly)
+const COMPRESSION_LEVEL: i32 = 3;
+
+// A dictionary, as shipped to the clients and prepared for the compression
+struct Dictionary {
+    content: Vec<u8>,
+    prepared: EncoderDictionary<'static>,
+}
+
+// The dictionary, set at startup if configured
+static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+
+// Load the dictionary from a file (e.g. trained with `--train-zstd-dictionary`), returning its size
+pub(crate) fn init(path: &Path) -> Result<usize, String> {
+    let content =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let prepared = EncoderDictionary::copy(&content, COMPRESSION_LEVEL);
+    let size = content.len();
+    DICTIONARY
+        .set(Dictionary { content, prepared })
+        .map_err(|_| "zstd dictionary already set".to_string())?;
+    Ok(size)
+}
+
+// Return the content of the dictionary, if configured
+pub(crate) fn dictionary() -> Option<&'static [u8]> {
+    DICTIONARY.get().map(|dict| dict.content.as_slice())
+}
+
+// Compress a payload with the dictionary
+pub(crate) fn compress(payload: &[u8]) -> Result<Vec<u8>, String> {
+    let dict = DICTIONARY
+        .get()
+        .ok_or_else(|| "No zstd dictionary configured".to_string())?;
+    zstd::bulk::Compressor::with_prepared_dictionary(&dict.prepared)
+        .and_then(|mut compressor| compressor.compress(payload))
+        .map_err(|e| format!("Failed to compress payload: {e}"))
+}
+
+// Return the encoding of a compressed payload, with the encoding of the uncompressed payload as
+// schema (e.g. "application/zstd;application/json")
+pub(crate) fn encoding(uncompressed: &Encoding) -> Encoding {
+    Encoding::from("application/zstd").with_schema(uncompressed.to_string())
+}
+
+// Train a dictionary over the definitions and type descriptions of all the types of the
+// registry and write it to a file, returning its size
+pub(crate) fn train_dictionary(registry: &mut Registry, path: &Path) -> Result<usize, String> {
+    let all_types = keyexpr::new("**").expect("valid key expression");
+    registry.load_pending_matching(all_types);
+
+    let mut samples: Vec<Vec<u8>> = Vec::new();
+    for t in registry.iter_types(all_types) {
+        samples.push(registry.get_definition_content(t)?.as_bytes().to_vec());
+        samples.push(serde_json::to_vec(&t.type_description).map_err(|e| {
+            format!(
+                "Failed to serialize type description of {}: {e}",
+                t.full_name
+            )
+        })?);
+    }
+    let dictionary = zstd::dict::from_samples(&samples, DICTIONARY_MAX_SIZE)
+        .map_err(|e| format!("Failed to train zstd dictionary: {e}"))?;
+    std::fs::write(path, &dictionary)
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(dictionary.len())
+}
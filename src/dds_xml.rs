@@ -0,0 +1,218 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// XML type representations of a type and its dependencies for DDS-native tools, with the
+// types named as by the ROS 2 RMWs (e.g. "std_msgs::msg::dds_::String_" for "std_msgs/msg/String")
+use std::fmt::Write;
+
+use crate::{
+    field_type::FieldTypeId,
+    type_description::{Field, FieldType, IndividualTypeDescription, MAX_NESTING_DEPTH},
+    type_info::TypeInfo,
+};
+
+const FASTDDS_XML_NAMESPACE: &str = "http://www.eprosima.com/XMLSchemas/fastRTPS_Profiles";
+const CONNEXT_XML_SCHEMA_LOCATION: &str =
+    "http://community.rti.com/schema/current/rti_dds_topic_types.xsd";
+
+// Generate the eProsima Fast DDS XML types profile (`<types>`) of a type and its dependencies,
+// allowing to create the matching DynamicTypes (see https://fast-dds.docs.eprosima.com/en/latest/fastdds/xml_configuration/dynamic_types.html)
+pub(crate) fn fastdds_types_xml(t: &TypeInfo) -> Result<String, String> {
+    let mut xml = String::new();
+    let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(xml, r#"<types xmlns="{FASTDDS_XML_NAMESPACE}">"#);
+    for d in dependency_order(t)? {
+        let _ = writeln!(xml, "    <type>");
+        let _ = writeln!(
+            xml,
+            r#"        <struct name="{}">"#,
+            dds_type_name(&d.type_name)
+        );
+        for field in &d.fields {
+            xml.push_str("            ");
+            write_member(&mut xml, field, fastdds_type(&field.r#type)?, false);
+        }
+        let _ = writeln!(xml, "        </struct>");
+        let _ = writeln!(xml, "    </type>");
+    }
+    let _ = writeln!(xml, "</types>");
+    Ok(xml)
+}
+
+// Generate the RTI Connext XML type representation of a type and its dependencies, with the types
+// in their modules (see https://community.rti.com/static/documentation/connext-dds/current/doc/manuals/connext_dds_professional/users_manual/users_manual/XML_Type_Representation.htm)
+pub(crate) fn connext_types_xml(t: &TypeInfo) -> Result<String, String> {
+    let mut xml = String::new();
+    let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        xml,
+        r#"<types xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:noNamespaceSchemaLocation="{CONNEXT_XML_SCHEMA_LOCATION}">"#
+    );
+    for d in dependency_order(t)? {
+        // e.g. "std_msgs::msg::dds_::String_" => modules ["std_msgs", "msg", "dds_"] and struct "String_"
+        let dds_name = dds_type_name(&d.type_name);
+        let mut path: Vec<&str> = dds_name.split("::").collect();
+        let struct_name = path.pop().unwrap_or_default();
+        for (depth, module) in path.iter().enumerate() {
+            let _ = writeln!(xml, r#"{}<module name="{module}">"#, indent(depth + 1));
+        }
+        let _ = writeln!(
+            xml,
+            r#"{}<struct name="{struct_name}">"#,
+            indent(path.len() + 1)
+        );
+        for field in &d.fields {
+            xml.push_str(&indent(path.len() + 2));
+            write_member(&mut xml, field, connext_type(&field.r#type)?, true);
+        }
+        let _ = writeln!(xml, "{}</struct>", indent(path.len() + 1));
+        for depth in (0..path.len()).rev() {
+            let _ = writeln!(xml, "{}</module>", indent(depth + 1));
+        }
+    }
+    let _ = writeln!(xml, "</types>");
+    Ok(xml)
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+// Write a <member> element, with its attributes according to its type.
+// `explicit_unbounded_strings` is required by the tools having a default maximum length for strings.
+fn write_member(xml: &mut String, field: &Field, xml_type: &str, explicit_unbounded_strings: bool) {
+    let field_type = &field.r#type;
+    let _ = write!(xml, r#"<member name="{}" type="{xml_type}""#, field.name);
+    if field_type.type_id.base_type() == FieldTypeId::NestedType {
+        let _ = write!(
+            xml,
+            r#" nonBasicTypeName="{}""#,
+            dds_type_name(&field_type.nested_type_name)
+        );
+    }
+    if field_type.type_id.is_bounded_string() {
+        let _ = write!(xml, r#" stringMaxLength="{}""#, field_type.string_capacity);
+    } else if explicit_unbounded_strings && is_unbounded_string(field_type.type_id) {
+        let _ = write!(xml, r#" stringMaxLength="-1""#);
+    }
+    if field_type.type_id.is_array() {
+        let _ = write!(xml, r#" arrayDimensions="{}""#, field_type.capacity);
+    } else if field_type.type_id.is_bounded_sequence() {
+        let _ = write!(xml, r#" sequenceMaxLength="{}""#, field_type.capacity);
+    } else if field_type.type_id.is_unbounded_sequence() {
+        let _ = write!(xml, r#" sequenceMaxLength="-1""#);
+    }
+    let _ = writeln!(xml, "/>");
+}
+
+// The RTI Connext XML type of the elements of a field
+fn connext_type(field_type: &FieldType) -> Result<&'static str, String> {
+    Ok(match field_type.type_id.base_type() {
+        FieldTypeId::Byte => "octet",
+        FieldTypeId::WChar => "wchar",
+        // the other types have the same names than for Fast DDS
+        _ => fastdds_type(field_type)?,
+    })
+}
+
+// The Fast DDS XML type of the elements of a field
+fn fastdds_type(field_type: &FieldType) -> Result<&'static str, String> {
+    Ok(match field_type.type_id.base_type() {
+        FieldTypeId::NestedType => "nonBasic",
+        FieldTypeId::Int8 => "int8",
+        FieldTypeId::UInt8 => "uint8",
+        FieldTypeId::Int16 => "int16",
+        FieldTypeId::UInt16 => "uint16",
+        FieldTypeId::Int32 => "int32",
+        FieldTypeId::UInt32 => "uint32",
+        FieldTypeId::Int64 => "int64",
+        FieldTypeId::UInt64 => "uint64",
+        FieldTypeId::Float => "float32",
+        FieldTypeId::Double => "float64",
+        FieldTypeId::LongDouble => "float128",
+        FieldTypeId::Char => "char8",
+        FieldTypeId::WChar => "char16",
+        FieldTypeId::Boolean => "boolean",
+        FieldTypeId::Byte => "byte",
+        FieldTypeId::String | FieldTypeId::FixedString | FieldTypeId::BoundedString => "string",
+        FieldTypeId::WString | FieldTypeId::FixedWString | FieldTypeId::BoundedWString => "wstring",
+        other => return Err(format!("Field type {other:?} has no DDS equivalent")),
+    })
+}
+
+fn is_unbounded_string(type_id: FieldTypeId) -> bool {
+    matches!(
+        type_id.base_type(),
+        FieldTypeId::String | FieldTypeId::WString
+    )
+}
+
+// Convert a ROS 2 type name to the DDS type name used by the RMWs,
+// e.g. "std_msgs/msg/String" => "std_msgs::msg::dds_::String_"
+fn dds_type_name(type_name: &str) -> String {
+    match type_name.rsplit_once('/') {
+        Some((namespace, name)) => format!("{}::dds_::{name}_", namespace.replace('/', "::")),
+        None => format!("{type_name}_"),
+    }
+}
+
+// Return the type and its dependencies, each dependency being before the types using it
+// (as required to define the types in XML)
+fn dependency_order(t: &TypeInfo) -> Result<Vec<&IndividualTypeDescription>, String> {
+    let description = &t.type_description.type_description_msg;
+    let mut ordered = Vec::new();
+    visit(
+        &description.type_description,
+        &description.referenced_type_descriptions,
+        &mut Vec::new(),
+        &mut ordered,
+    )?;
+    Ok(ordered)
+}
+
+// Depth-first traversal, `stack` being the list of the types being visited, to detect circular references
+fn visit<'d>(
+    d: &'d IndividualTypeDescription,
+    referenced: &'d [IndividualTypeDescription],
+    stack: &mut Vec<&'d str>,
+    ordered: &mut Vec<&'d IndividualTypeDescription>,
+) -> Result<(), String> {
+    if ordered.iter().any(|o| o.type_name == d.type_name) {
+        return Ok(());
+    }
+    if stack.contains(&&*d.type_name) || stack.len() >= MAX_NESTING_DEPTH {
+        return Err(format!(
+            "Circular or too deep reference to type {} (via {})",
+            d.type_name,
+            stack.join(" -> ")
+        ));
+    }
+    stack.push(&d.type_name);
+    for field in &d.fields {
+        let nested_type_name = &field.r#type.nested_type_name;
+        if nested_type_name.is_empty() {
+            continue;
+        }
+        let nested = referenced
+            .iter()
+            .find(|r| r.type_name == *nested_type_name)
+            .ok_or(format!(
+                "Nested type {nested_type_name} of {}.{} not found in referenced type descriptions",
+                d.type_name, field.name
+            ))?;
+        visit(nested, referenced, stack, ordered)?;
+    }
+    stack.pop();
+    ordered.push(d);
+    Ok(())
+}
@@ -0,0 +1,258 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    field_type::FieldTypeId,
+    type_description::{
+        Field, FieldType, IndividualTypeDescription, TypeDescription, MAX_NESTING_DEPTH,
+    },
+};
+
+// Build a JSON instance of the described type, with each field set to its default_value
+// if any, or to a zero/empty value otherwise. Nested types are recursively populated
+// using the referenced type descriptions.
+pub(crate) fn default_instance(description: &TypeDescription) -> Value {
+    individual_default(
+        &description.type_description,
+        &description.referenced_type_descriptions,
+        &mut Vec::new(),
+    )
+}
+
+// `stack` is the list of the types being populated, to detect circular references
+fn individual_default<'d>(
+    t: &'d IndividualTypeDescription,
+    referenced: &'d [IndividualTypeDescription],
+    stack: &mut Vec<&'d str>,
+) -> Value {
+    if stack.contains(&&*t.type_name) || stack.len() >= MAX_NESTING_DEPTH {
+        tracing::warn!(
+            "Circular or too deep reference to type {} (via {})",
+            t.type_name,
+            stack.join(" -> ")
+        );
+        return Value::Null;
+    }
+    stack.push(&t.type_name);
+    let mut object = Map::new();
+    for field in &t.fields {
+        object.insert(
+            field.name.to_string(),
+            field_default(field, referenced, stack),
+        );
+    }
+    stack.pop();
+    Value::Object(object)
+}
+
+fn field_default<'d>(
+    field: &'d Field,
+    referenced: &'d [IndividualTypeDescription],
+    stack: &mut Vec<&'d str>,
+) -> Value {
+    let field_type = &field.r#type;
+
+    match typed_default_value(field) {
+        Ok(Some(value)) => return value,
+        Ok(None) => {}
+        Err(e) => tracing::warn!("{e}"),
+    }
+
+    if field_type.type_id.is_array() {
+        let element = element_default(field_type, referenced, stack);
+        Value::Array(vec![element; field_type.capacity as usize])
+    } else if field_type.type_id.is_collection() {
+        Value::Array(Vec::new())
+    } else {
+        element_default(field_type, referenced, stack)
+    }
+}
+
+// Zero/empty value for a single element of the field type
+fn element_default<'d>(
+    field_type: &'d FieldType,
+    referenced: &'d [IndividualTypeDescription],
+    stack: &mut Vec<&'d str>,
+) -> Value {
+    match field_type.type_id.base_type() {
+        FieldTypeId::NestedType => {
+            match referenced
+                .iter()
+                .find(|t| t.type_name == field_type.nested_type_name)
+            {
+                Some(nested) => individual_default(nested, referenced, stack),
+                None => {
+                    tracing::warn!(
+                        "Nested type {} not found in referenced type descriptions",
+                        field_type.nested_type_name
+                    );
+                    Value::Object(Map::new())
+                }
+            }
+        }
+        FieldTypeId::Float | FieldTypeId::Double | FieldTypeId::LongDouble => {
+            Value::Number(Number::from_f64(0.0).expect("0.0 is a finite number"))
+        }
+        FieldTypeId::Boolean => Value::Bool(false),
+        FieldTypeId::String
+        | FieldTypeId::WString
+        | FieldTypeId::FixedString
+        | FieldTypeId::FixedWString
+        | FieldTypeId::BoundedString
+        | FieldTypeId::BoundedWString => Value::String(String::new()),
+        FieldTypeId::NotSet => Value::Null,
+        _ => Value::Number(0.into()),
+    }
+}
+
+// Return the default_value of a field (if any) as a typed JSON value, parsed according to the
+// field type from its string as found in the type description (e.g. "42", "true", "'hello'" or
+// "[1, 2, 3]")
+pub(crate) fn typed_default_value(field: &Field) -> Result<Option<Value>, String> {
+    let Some(default_value) = field.default_value.as_deref().filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    let type_id = field.r#type.type_id;
+    parse_default_value(type_id, default_value)
+        .map(Some)
+        .ok_or_else(|| {
+            format!(
+                "Failed to parse default value '{default_value}' of field '{}' as {type_id:?}",
+                field.name
+            )
+        })
+}
+
+fn parse_default_value(type_id: FieldTypeId, value: &str) -> Option<Value> {
+    if type_id.is_collection() {
+        // a list, or a tuple as printed by Python
+        let value = value.trim();
+        let inner = value
+            .strip_prefix('[')
+            .and_then(|v| v.strip_suffix(']'))
+            .or_else(|| value.strip_prefix('(').and_then(|v| v.strip_suffix(')')))?
+            .trim();
+        split_elements(inner)?
+            .into_iter()
+            .map(|v| parse_scalar(type_id.base_type(), v))
+            .collect::<Option<Vec<Value>>>()
+            .map(Value::Array)
+    } else {
+        parse_scalar(type_id, value.trim())
+    }
+}
+
+// Split the elements of a list on the commas which aren't in a quoted string (ignoring a trailing
+// comma, as in a Python tuple of 1 element), or return None if a quoted string isn't terminated
+fn split_elements(inner: &str) -> Option<Vec<&str>> {
+    let mut elements = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in inner.char_indices() {
+        match (c, quote) {
+            (_, Some(_)) if escaped => escaped = false,
+            ('\\', Some(_)) => escaped = true,
+            (c, Some(q)) if c == q => quote = None,
+            ('"' | '\'', None) => quote = Some(c),
+            (',', None) => {
+                elements.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if quote.is_some() {
+        return None;
+    }
+    let last = inner[start..].trim();
+    if !last.is_empty() {
+        elements.push(last);
+    }
+    Some(elements)
+}
+
+fn parse_scalar(type_id: FieldTypeId, value: &str) -> Option<Value> {
+    match type_id {
+        FieldTypeId::Float | FieldTypeId::Double | FieldTypeId::LongDouble => value
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        FieldTypeId::Boolean => match value.to_ascii_lowercase().as_str() {
+            "true" | "1" => Some(Value::Bool(true)),
+            "false" | "0" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        FieldTypeId::String
+        | FieldTypeId::WString
+        | FieldTypeId::FixedString
+        | FieldTypeId::FixedWString
+        | FieldTypeId::BoundedString
+        | FieldTypeId::BoundedWString => Some(Value::String(unquote(value))),
+        FieldTypeId::NestedType | FieldTypeId::NotSet => None,
+        _ => parse_integer(value).map(Value::Number),
+    }
+}
+
+// Parse an integer literal, in decimal or with a Python prefix (e.g. "0x1F", "0o17" or "0b101")
+fn parse_integer(value: &str) -> Option<Number> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let lowercase = digits.to_ascii_lowercase();
+    let (radix, digits) = match lowercase.get(..2) {
+        Some("0x") => (16, &lowercase[2..]),
+        Some("0o") => (8, &lowercase[2..]),
+        Some("0b") => (2, &lowercase[2..]),
+        _ => (10, lowercase.as_str()),
+    };
+    let magnitude = u64::from_str_radix(&digits.replace('_', ""), radix).ok()?;
+    if negative {
+        0i64.checked_sub_unsigned(magnitude).map(Number::from)
+    } else {
+        Some(Number::from(magnitude))
+    }
+}
+
+// Remove the surrounding quotes of a string literal, if any, and unescape its content
+fn unquote(value: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+            .filter(|_| value.len() >= 2)
+        {
+            let mut unquoted = String::with_capacity(inner.len());
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                if c != '\\' {
+                    unquoted.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('n') => unquoted.push('\n'),
+                    Some('t') => unquoted.push('\t'),
+                    Some('r') => unquoted.push('\r'),
+                    Some('0') => unquoted.push('\0'),
+                    Some(c) => unquoted.push(c),
+                    None => unquoted.push('\\'),
+                }
+            }
+            return unquoted;
+        }
+    }
+    value.to_string()
+}
@@ -0,0 +1,101 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+// UTF-8 Byte Order Mark, found at the start of some definition files
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// Read the content of a .msg/.srv/.action definition file
+pub(crate) fn read_definition(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Failed to read definition file {}: {}", path.display(), e))?;
+    Ok(decode_definition(&bytes, path))
+}
+
+// Decode a definition file content: skip the UTF-8 BOM if any, fall back to latin-1 if not
+// valid UTF-8, and normalize the CRLF line endings to LF
+fn decode_definition(bytes: &[u8], path: &Path) -> String {
+    let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+    let content = match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(e) => {
+            tracing::warn!(
+                "Definition file {} is not valid UTF-8 ({e}) - decoding it as latin-1",
+                path.display()
+            );
+            // latin-1 code points are the 256 first Unicode code points
+            bytes.iter().map(|b| char::from(*b)).collect()
+        }
+    };
+    if content.contains('\r') {
+        content.replace("\r\n", "\n")
+    } else {
+        content
+    }
+}
+
+// A small LRU cache of definition contents read on demand, for the lazy mode where
+// the definitions are not kept in memory.
+pub(crate) struct DefinitionCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    contents: HashMap<PathBuf, Arc<str>>,
+    // paths ordered from least to most recently used
+    order: VecDeque<PathBuf>,
+}
+
+impl DefinitionCache {
+    // Create a cache of at most `capacity` definitions (0 to disable caching)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    // Return the definition content from the cache, or read it from the file
+    pub fn get_or_read(&self, path: &Path) -> Result<Arc<str>, String> {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if let Some(content) = state.contents.get(path).cloned() {
+            if let Some(pos) = state.order.iter().position(|p| p == path) {
+                let p = state.order.remove(pos).expect("position is valid");
+                state.order.push_back(p);
+            }
+            return Ok(content);
+        }
+
+        let content: Arc<str> = Arc::from(read_definition(path)?);
+        if self.capacity > 0 {
+            if state.contents.len() >= self.capacity {
+                if let Some(evicted) = state.order.pop_front() {
+                    state.contents.remove(&evicted);
+                }
+            }
+            state.contents.insert(path.to_path_buf(), content.clone());
+            state.order.push_back(path.to_path_buf());
+        }
+        Ok(content)
+    }
+}
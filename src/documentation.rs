@@ -0,0 +1,384 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Extraction of the documentation of a type from the comments of its definition, where all the
+// semantic documentation of the ROS interfaces lives:
+//  - the header comment, before the first declaration and followed by a blank line
+//  - for each field or constant, the comment lines just preceding its declaration, its trailing
+//    comment and the following indented comment lines (continuing the trailing comment)
+// The unit of a field is recognized in its documentation, following the common conventions:
+// in brackets (e.g. "# [m/s]") or as words (e.g. "# radians" or "# angle in radians").
+// The constants are grouped by prefix into inferred enums, associated with the field they
+// plausibly enumerate (e.g. the "STATUS_*" constants with a "status" field of the same type).
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{
+    registry::Registry,
+    type_info::{TypeInfo, TypeKind},
+};
+
+// A type with its documentation, in sections (one for a message, the request and response of a
+// service, the goal, result and feedback of an action)
+#[derive(Debug, Serialize)]
+pub(crate) struct DocumentedType<'a> {
+    type_name: &'a str,
+    doc: String,
+    sections: Vec<DocumentedSection>,
+}
+
+#[derive(Debug, Serialize)]
+struct DocumentedSection {
+    type_name: String,
+    fields: Vec<DocumentedMember>,
+    constants: Vec<DocumentedMember>,
+    enums: Vec<InferredEnum>,
+}
+
+// A group of constants with a common prefix, and the field they enumerate, if found
+#[derive(Debug, Serialize)]
+struct InferredEnum {
+    name: String,
+    r#type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<String>,
+    constants: Vec<String>,
+}
+
+// A field (with its default value, if any) or a constant (with its value), as declared in
+// the definition
+#[derive(Debug, Serialize)]
+struct DocumentedMember {
+    name: String,
+    r#type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    doc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unit: Option<String>,
+}
+
+// Suffixes of the types of the sections of the services and actions definitions
+pub(crate) const SRV_SECTIONS: &[&str] = &["_Request", "_Response"];
+pub(crate) const ACTION_SECTIONS: &[&str] = &["_Goal", "_Result", "_Feedback"];
+
+// Units written as words, with their symbols
+const UNIT_WORDS: &[(&str, &str)] = &[
+    ("radian", "rad"),
+    ("degree", "deg"),
+    ("meter", "m"),
+    ("metre", "m"),
+    ("millimeter", "mm"),
+    ("centimeter", "cm"),
+    ("kilometer", "km"),
+    ("second", "s"),
+    ("millisecond", "ms"),
+    ("microsecond", "us"),
+    ("nanosecond", "ns"),
+    ("minute", "min"),
+    ("hour", "h"),
+    ("hertz", "Hz"),
+    ("kilogram", "kg"),
+    ("gram", "g"),
+    ("newton", "N"),
+    ("pascal", "Pa"),
+    ("kelvin", "K"),
+    ("celsius", "degC"),
+    ("volt", "V"),
+    ("ampere", "A"),
+    ("watt", "W"),
+    ("tesla", "T"),
+    ("lux", "lx"),
+    ("percent", "%"),
+];
+
+static BRACKETED_UNIT: OnceLock<Regex> = OnceLock::new();
+static WORDS_UNIT: OnceLock<Regex> = OnceLock::new();
+
+pub(crate) fn documented_type<'t>(
+    registry: &Registry,
+    t: &'t TypeInfo,
+) -> Result<DocumentedType<'t>, String> {
+    let type_name = t.get_type_name();
+    let section_name = |index: usize| match t.kind {
+        TypeKind::MSG => type_name.to_string(),
+        TypeKind::SRV => format!("{type_name}{}", SRV_SECTIONS.get(index).unwrap_or(&"")),
+        TypeKind::ACTION => format!("{type_name}{}", ACTION_SECTIONS.get(index).unwrap_or(&"")),
+    };
+    let new_section = |index: usize| DocumentedSection {
+        type_name: section_name(index),
+        fields: Vec::new(),
+        constants: Vec::new(),
+        enums: Vec::new(),
+    };
+
+    let content = registry.get_definition_content(t)?;
+    let mut doc = String::new();
+    let mut sections = vec![new_section(0)];
+    // the comment lines since the last blank line or declaration
+    let mut comments: Vec<&str> = Vec::new();
+    // the last declared member, while its trailing comment can be continued
+    let mut continued: Option<(bool, usize)> = None;
+    let mut declared = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed == "---" {
+            sections.push(new_section(sections.len()));
+            comments.clear();
+            continued = None;
+            continue;
+        }
+        if trimmed.is_empty() {
+            // a comment block at the top followed by a blank line is the header comment,
+            // other comment blocks followed by a blank line are not attached to a declaration
+            if !declared && doc.is_empty() {
+                doc = comments.join("\n");
+            }
+            comments.clear();
+            continued = None;
+            continue;
+        }
+        let section = sections.last_mut().expect("at least one section");
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            match continued {
+                Some((is_constant, index)) if line.starts_with(char::is_whitespace) => {
+                    let members = if is_constant {
+                        &mut section.constants
+                    } else {
+                        &mut section.fields
+                    };
+                    append_line(&mut members[index].doc, comment.trim());
+                }
+                _ => {
+                    continued = None;
+                    comments.push(comment.trim());
+                }
+            }
+            continue;
+        }
+
+        let (declaration, trailing_comment) = split_comment(trimmed);
+        let Some((mut member, is_constant)) = parse_declaration(declaration) else {
+            comments.clear();
+            continued = None;
+            continue;
+        };
+        declared = true;
+        member.doc = comments.join("\n");
+        comments.clear();
+        let members = if is_constant {
+            &mut section.constants
+        } else {
+            &mut section.fields
+        };
+        continued = match trailing_comment {
+            Some(comment) => {
+                append_line(&mut member.doc, comment);
+                Some((is_constant, members.len()))
+            }
+            None => None,
+        };
+        members.push(member);
+    }
+
+    for section in &mut sections {
+        for field in &mut section.fields {
+            field.unit = extract_unit(&field.doc);
+        }
+        section.enums = infer_enums(&section.fields, &section.constants);
+        // the types of the fields as ROS users read them (e.g. with the full names of the nested
+        // types), once the enums are inferred from the types as declared
+        let description = &t.type_description.type_description_msg;
+        if let Some(section_description) = std::iter::once(&description.type_description)
+            .chain(description.referenced_type_descriptions.iter())
+            .find(|d| *d.type_name == section.type_name)
+        {
+            for field in &mut section.fields {
+                if let Some(f) = section_description
+                    .fields
+                    .iter()
+                    .find(|f| *f.name == field.name)
+                {
+                    field.r#type = f.r#type.pretty();
+                }
+            }
+        }
+    }
+
+    Ok(DocumentedType {
+        type_name,
+        doc,
+        sections,
+    })
+}
+
+// Parse a field or constant declaration (without comment), returning the member and true
+// if it's a constant (e.g. "int32 FOO=1")
+fn parse_declaration(declaration: &str) -> Option<(DocumentedMember, bool)> {
+    let (member_type, rest) = declaration.split_once(char::is_whitespace)?;
+    let rest = rest.trim();
+    // a constant declares a "<NAME>=<value>", with a non-array type
+    if let Some((name, value)) = rest.split_once('=') {
+        let name = name.trim();
+        if !member_type.contains('[') && !name.contains(char::is_whitespace) {
+            return Some((
+                DocumentedMember {
+                    name: name.to_string(),
+                    r#type: member_type.to_string(),
+                    value: Some(value.trim().to_string()),
+                    doc: String::new(),
+                    unit: None,
+                },
+                true,
+            ));
+        }
+    }
+    let (name, default_value) = match rest.split_once(char::is_whitespace) {
+        Some((name, default_value)) => (name, Some(default_value.trim().to_string())),
+        None => (rest, None),
+    };
+    Some((
+        DocumentedMember {
+            name: name.to_string(),
+            r#type: member_type.to_string(),
+            value: default_value,
+            doc: String::new(),
+            unit: None,
+        },
+        false,
+    ))
+}
+
+// Split a declaration line and its trailing comment, ignoring the '#' in quoted strings
+// (e.g. the default value of a string field)
+pub(crate) fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return (line[..i].trim_end(), Some(line[i + 1..].trim())),
+            _ => {}
+        }
+    }
+    (line, None)
+}
+
+// Group the constants into enums: first the constants prefixed with the name of a field of the
+// same type (e.g. "POWER_SUPPLY_STATUS_CHARGING" for a "power_supply_status" field), then the
+// remaining constants sharing their first word (at least 2 of the same type)
+fn infer_enums(fields: &[DocumentedMember], constants: &[DocumentedMember]) -> Vec<InferredEnum> {
+    let mut enums = Vec::new();
+    let mut grouped = vec![false; constants.len()];
+    for field in fields {
+        let prefix = format!("{}_", field.name.to_uppercase());
+        let base_type = field.r#type.split('[').next().unwrap_or_default();
+        let members: Vec<usize> = (0..constants.len())
+            .filter(|i| !grouped[*i])
+            .filter(|i| {
+                constants[*i].r#type == base_type && constants[*i].name.starts_with(&prefix)
+            })
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        for i in &members {
+            grouped[*i] = true;
+        }
+        enums.push(InferredEnum {
+            name: field.name.to_uppercase(),
+            r#type: base_type.to_string(),
+            field: Some(field.name.clone()),
+            constants: members.iter().map(|i| constants[*i].name.clone()).collect(),
+        });
+    }
+
+    let mut groups: Vec<(&str, &str, Vec<usize>)> = Vec::new();
+    for (i, constant) in constants.iter().enumerate().filter(|(i, _)| !grouped[*i]) {
+        let Some((prefix, _)) = constant.name.split_once('_') else {
+            continue;
+        };
+        match groups
+            .iter_mut()
+            .find(|(p, t, _)| *p == prefix && *t == constant.r#type)
+        {
+            Some((_, _, members)) => members.push(i),
+            None => groups.push((prefix, &constant.r#type, vec![i])),
+        }
+    }
+    enums.extend(
+        groups
+            .into_iter()
+            .filter(|(_, _, members)| members.len() >= 2)
+            .map(|(prefix, constant_type, members)| InferredEnum {
+                name: prefix.to_string(),
+                r#type: constant_type.to_string(),
+                field: None,
+                constants: members.iter().map(|i| constants[*i].name.clone()).collect(),
+            }),
+    );
+    enums
+}
+
+// Recognize the unit in the documentation of a field, if any
+fn extract_unit(doc: &str) -> Option<String> {
+    // a unit in brackets, e.g. "[m/s^2]", "[rad]" or "[%]" (but not an index such as "[0]")
+    let bracketed = BRACKETED_UNIT.get_or_init(|| {
+        Regex::new(r"\[\s*([A-Za-z%°µ][A-Za-z0-9%°µ/^*.\-]{0,6})\s*\]").expect("valid regex")
+    });
+    if let Some(unit) = bracketed.captures(doc).and_then(|c| c.get(1)) {
+        return Some(unit.as_str().to_string());
+    }
+    // a unit in words, either as a whole comment line or after "in", e.g. "radians",
+    // "angle in radians" or "speed in meters per second"
+    let words = WORDS_UNIT.get_or_init(|| {
+        let unit = format!(
+            "(?:{})s?",
+            UNIT_WORDS
+                .iter()
+                .map(|(word, _)| *word)
+                .collect::<Vec<_>>()
+                .join("|")
+        );
+        Regex::new(&format!(
+            r"(?im)(?:^\s*|\bin\s+)(?P<unit>{unit})(?:\s+per\s+(?P<per>{unit}))?(?P<squared>\s+squared)?\s*\.?\s*$"
+        ))
+        .expect("valid regex")
+    });
+    let captures = words.captures(doc)?;
+    let symbol = |word: &str| {
+        let word = word.to_lowercase();
+        UNIT_WORDS
+            .iter()
+            .find(|(w, _)| word == *w || word.strip_suffix('s') == Some(*w))
+            .map(|(_, symbol)| *symbol)
+    };
+    let mut unit = symbol(captures.name("unit")?.as_str())?.to_string();
+    if let Some(per) = captures.name("per") {
+        unit.push('/');
+        unit.push_str(symbol(per.as_str())?);
+    }
+    if captures.name("squared").is_some() {
+        unit.push_str("^2");
+    }
+    Some(unit)
+}
+
+fn append_line(doc: &mut String, line: &str) {
+    if !doc.is_empty() {
+        doc.push('\n');
+    }
+    doc.push_str(line);
+}
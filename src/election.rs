@@ -0,0 +1,147 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Election of a leader among redundant registries with identical content (HA setups), so only
+// the leader replies to the wildcard queries while the others stay warm standbys, avoiding
+// duplicate replies. Each ready registry declares a `@ros2_types_election/<zid>` liveliness token
+// (undeclared while paused for maintenance or not ready), and the live registry with the lowest
+// Zenoh ID is the leader. Without any candidate, each registry replies.
+use std::collections::BTreeSet;
+
+use zenoh::{
+    handlers::FifoChannelHandler,
+    key_expr::{keyexpr, KeyExpr},
+    liveliness::LivelinessToken,
+    pubsub::Subscriber,
+    query::Query,
+    sample::{Sample, SampleKind},
+    Session,
+};
+
+use crate::namespaced;
+
+// Key expression prefix of the liveliness tokens of the registries taking part in the election
+const KE_ELECTION: &str = "@ros2_types_election";
+
+// The subscriber to the liveliness changes of the registries taking part in the election
+pub(crate) type ElectionSubscriber = Subscriber<FifoChannelHandler<Sample>>;
+
+pub(crate) struct Election {
+    zid: String,
+    token_ke: KeyExpr<'static>,
+    members: BTreeSet<String>, // Zenoh IDs of the other live registries
+    token: Option<LivelinessToken>, // declared while this registry is a candidate
+}
+
+impl Election {
+    // Take part in the election among the registries serving under this namespace (if any),
+    // returning the subscriber to the liveliness changes to pass to handle_sample(). The registry
+    // is a candidate once declared ready with update_candidacy().
+    pub async fn join(
+        session: &Session,
+        namespace: Option<&keyexpr>,
+    ) -> Result<(Self, ElectionSubscriber), String> {
+        let zid = session.zid().to_string();
+        let token_ke = KeyExpr::try_from(format!("{KE_ELECTION}/{zid}"))
+            .map_err(|e| format!("Invalid election key expression: {e}"))?;
+        let members_ke = KeyExpr::try_from(format!("{KE_ELECTION}/*"))
+            .map_err(|e| format!("Invalid election key expression: {e}"))?;
+        let subscriber = session
+            .liveliness()
+            .declare_subscriber(namespaced(namespace, members_ke))
+            .history(true)
+            .await
+            .map_err(|e| format!("Failed to subscribe to election liveliness tokens: {e}"))?;
+        let election = Election {
+            zid,
+            token_ke: namespaced(namespace, token_ke),
+            members: BTreeSet::new(),
+            token: None,
+        };
+        Ok((election, subscriber))
+    }
+
+    // Declare the liveliness token of this registry when it's ready (i.e. not paused for
+    // maintenance, nor warming up or with dangling references), and undeclare it otherwise, so the
+    // standbys reply in the meantime
+    pub async fn update_candidacy(&mut self, session: &Session, ready: bool) -> Result<(), String> {
+        let was_leader = self.is_leader();
+        match (ready, self.token.is_some()) {
+            (true, false) => {
+                let token = session
+                    .liveliness()
+                    .declare_token(self.token_ke.clone())
+                    .await
+                    .map_err(|e| format!("Failed to declare election liveliness token: {e}"))?;
+                self.token = Some(token);
+            }
+            (false, true) => {
+                tracing::info!("Withdrawn from the election until ready");
+                self.token = None;
+            }
+            _ => return Ok(()),
+        }
+        self.log_transition(was_leader);
+        Ok(())
+    }
+
+    // Update the live registries with a liveliness change
+    pub fn handle_sample(&mut self, sample: Sample) {
+        let was_leader = self.is_leader();
+        let Some(zid) = sample.key_expr().as_str().rsplit('/').next() else {
+            return;
+        };
+        if zid == self.zid {
+            return;
+        }
+        match sample.kind() {
+            SampleKind::Put => {
+                self.members.insert(zid.to_string());
+            }
+            SampleKind::Delete => {
+                self.members.remove(zid);
+            }
+        }
+        self.log_transition(was_leader);
+    }
+
+    // Return true if this registry is the leader
+    pub fn is_leader(&self) -> bool {
+        self.leader() == self.zid
+    }
+
+    // The candidate with the lowest Zenoh ID, or this registry if there is no candidate
+    fn leader(&self) -> &str {
+        match self.members.first() {
+            Some(other) if self.token.is_none() || *other < self.zid => other,
+            _ => &self.zid,
+        }
+    }
+
+    fn log_transition(&self, was_leader: bool) {
+        match (was_leader, self.is_leader()) {
+            (false, true) => tracing::info!(
+                "Elected as leader among {} registries",
+                self.members.len() + 1
+            ),
+            (true, false) => tracing::info!("Standby registry (leader: {})", self.leader()),
+            _ => {}
+        }
+    }
+}
+
+// Return true if this query must be replied, i.e. if there is no election or this registry is
+// the leader, or if the query isn't a wildcard one
+pub(crate) fn should_reply(election: Option<&Election>, query: &Query) -> bool {
+    election.map_or(true, Election::is_leader) || !query.key_expr().is_wild()
+}
@@ -0,0 +1,67 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+// Push signal of the changes of the registry content: each registry declares a
+// `@ros2_types_epoch/<zid>/<epoch>` liveliness token, re-declared with the new epoch when the
+// content changes. The watchers subscribing to `@ros2_types_epoch/**` thus get notified that
+// their cached schemas may be stale, without polling the registry.
+use zenoh::{
+    key_expr::{keyexpr, KeyExpr},
+    liveliness::LivelinessToken,
+    Session,
+};
+
+use crate::namespaced;
+
+// Key expression prefix of the liveliness tokens carrying the epochs of the registries content
+const KE_EPOCH: &str = "@ros2_types_epoch";
+
+pub(crate) struct EpochToken {
+    epoch: u64,
+    _token: LivelinessToken,
+}
+
+impl EpochToken {
+    // Declare the token of the current epoch of the registry content
+    pub async fn declare(
+        session: &Session,
+        namespace: Option<&keyexpr>,
+        epoch: u64,
+    ) -> Result<Self, String> {
+        let token_ke = KeyExpr::try_from(format!("{KE_EPOCH}/{}/{epoch}", session.zid()))
+            .map_err(|e| format!("Invalid epoch key expression: {e}"))?;
+        let token = session
+            .liveliness()
+            .declare_token(namespaced(namespace, token_ke))
+            .await
+            .map_err(|e| format!("Failed to declare epoch liveliness token: {e}"))?;
+        Ok(EpochToken {
+            epoch,
+            _token: token,
+        })
+    }
+
+    // Re-declare the token if the epoch changed. The token of the new epoch is declared before
+    // the previous one is undeclared (when replaced), so the registry never appears as gone.
+    pub async fn update(&mut self, session: &Session, namespace: Option<&keyexpr>, epoch: u64) {
+        if epoch == self.epoch {
+            return;
+        }
+        match EpochToken::declare(session, namespace, epoch).await {
+            Ok(token) => {
+                tracing::debug!("Registry content epoch changed to {epoch}");
+                *self = token;
+            }
+            Err(e) => tracing::warn!("{e}"),
+        }
+    }
+}
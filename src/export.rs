@@ -0,0 +1,92 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::path::Path;
+
+use serde::Serialize;
+use zenoh::key_expr::keyexpr;
+
+use crate::registry::Registry;
+
+// Encoding of the schemas, as expected by Foxglove for ROS 2 message definitions
+const FOXGLOVE_SCHEMA_ENCODING: &str = "ros2msg";
+// Name of the index file listing all the exported schemas
+const INDEX_FILE: &str = "index.json";
+
+// A schema as expected by Foxglove (e.g. in extensions or schema stores)
+#[derive(Serialize)]
+struct FoxgloveSchema<'a> {
+    name: &'a str,
+    encoding: &'a str,
+    data: &'a str,
+}
+
+// An entry of the index file
+#[derive(Serialize)]
+struct IndexEntry<'a> {
+    name: &'a str,
+    encoding: &'a str,
+    path: String,
+}
+
+// Export the schemas of all the types as a directory ready to be bundled into a Foxglove extension:
+// one "<package>/<kind>/<Name>.json" file per type, with the schema name, encoding and data
+// (the MCAP schema), and an index.json file listing them. Return the number of exported schemas.
+pub(crate) fn export_foxglove_schemas(
+    registry: &mut Registry,
+    dir: &Path,
+) -> Result<usize, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create directory {}: {e}", dir.display()))?;
+    let all_types = keyexpr::new("**").expect("valid key expression");
+    registry.load_pending_matching(all_types);
+
+    let mut index = Vec::new();
+    for t in registry.iter_types(all_types) {
+        let name = t.get_type_name();
+        let data = match registry.get_mcap_schema(t) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("Schema of {name} not exported: {e}");
+                continue;
+            }
+        };
+        let path = format!("{name}.json");
+        let file = dir.join(&path);
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+        }
+        let schema = FoxgloveSchema {
+            name,
+            encoding: FOXGLOVE_SCHEMA_ENCODING,
+            data: &data,
+        };
+        let json = serde_json::to_string_pretty(&schema)
+            .map_err(|e| format!("Failed to serialize schema of {name}: {e}"))?;
+        std::fs::write(&file, json)
+            .map_err(|e| format!("Failed to write {}: {e}", file.display()))?;
+        index.push(IndexEntry {
+            name,
+            encoding: FOXGLOVE_SCHEMA_ENCODING,
+            path,
+        });
+    }
+    index.sort_unstable_by(|a, b| a.name.cmp(b.name));
+
+    let index_file = dir.join(INDEX_FILE);
+    let json = serde_json::to_string_pretty(&index)
+        .map_err(|e| format!("Failed to serialize schemas index: {e}"))?;
+    std::fs::write(&index_file, json)
+        .map_err(|e| format!("Failed to write {}: {e}", index_file.display()))?;
+    Ok(index.len())
+}
@@ -139,6 +139,81 @@ pub enum FieldTypeId {
     BoundedWStringUnboundedSequence = 166,
 }
 
+// Offset between the ids of a basic type and of its array/sequence variants
+const COLLECTION_ID_OFFSET: u64 = 48;
+
+impl FieldTypeId {
+    // Return the basic type of the elements, e.g. Int32 for Int32BoundedSequence
+    pub fn base_type(&self) -> FieldTypeId {
+        FieldTypeId::from_repr(*self as u64 % COLLECTION_ID_OFFSET).unwrap_or(FieldTypeId::NotSet)
+    }
+
+    // True for fixed size arrays (e.g. "int32[3]")
+    pub fn is_array(&self) -> bool {
+        (FieldTypeId::NestedTypeArray as u64..=FieldTypeId::BoundedWStringArray as u64)
+            .contains(&(*self as u64))
+    }
+
+    // True for bounded sequences (e.g. "int32[<=3]")
+    pub fn is_bounded_sequence(&self) -> bool {
+        (FieldTypeId::NestedTypeBoundedSequence as u64
+            ..=FieldTypeId::BoundedWStringBoundedSequence as u64)
+            .contains(&(*self as u64))
+    }
+
+    // True for unbounded sequences (e.g. "int32[]")
+    pub fn is_unbounded_sequence(&self) -> bool {
+        (FieldTypeId::NestedTypeUnboundedSequence as u64
+            ..=FieldTypeId::BoundedWStringUnboundedSequence as u64)
+            .contains(&(*self as u64))
+    }
+
+    // True for arrays and sequences
+    pub fn is_collection(&self) -> bool {
+        self.is_array() || self.is_bounded_sequence() || self.is_unbounded_sequence()
+    }
+
+    // Return the name of the basic type of the elements as written in the .msg definitions
+    // (e.g. "float64" for Double or DoubleArray), or None for a nested type
+    pub fn msg_type_name(&self) -> Option<&'static str> {
+        match self.base_type() {
+            FieldTypeId::Int8 => Some("int8"),
+            FieldTypeId::UInt8 => Some("uint8"),
+            FieldTypeId::Int16 => Some("int16"),
+            FieldTypeId::UInt16 => Some("uint16"),
+            FieldTypeId::Int32 => Some("int32"),
+            FieldTypeId::UInt32 => Some("uint32"),
+            FieldTypeId::Int64 => Some("int64"),
+            FieldTypeId::UInt64 => Some("uint64"),
+            FieldTypeId::Float => Some("float32"),
+            FieldTypeId::Double => Some("float64"),
+            FieldTypeId::LongDouble => Some("long double"),
+            FieldTypeId::Char => Some("char"),
+            FieldTypeId::WChar => Some("wchar"),
+            FieldTypeId::Boolean => Some("bool"),
+            FieldTypeId::Byte => Some("byte"),
+            FieldTypeId::String | FieldTypeId::FixedString | FieldTypeId::BoundedString => {
+                Some("string")
+            }
+            FieldTypeId::WString | FieldTypeId::FixedWString | FieldTypeId::BoundedWString => {
+                Some("wstring")
+            }
+            _ => None,
+        }
+    }
+
+    // True for the strings with a maximum size (e.g. "string<=10" or "wstring<=10[5]")
+    pub fn is_bounded_string(&self) -> bool {
+        matches!(
+            self.base_type(),
+            FieldTypeId::FixedString
+                | FieldTypeId::FixedWString
+                | FieldTypeId::BoundedString
+                | FieldTypeId::BoundedWString
+        )
+    }
+}
+
 struct FieldTypeIdVisitor;
 
 impl<'de> Visitor<'de> for FieldTypeIdVisitor {
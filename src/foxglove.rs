@@ -0,0 +1,354 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// A server implementing the schema-serving side of the Foxglove WebSocket protocol
+// (https://github.com/foxglove/ws-protocol/blob/main/docs/spec.md): the ROS 2 topics published
+// via rmw_zenoh are discovered from their liveliness tokens, and advertised as channels with
+// their MCAP schema resolved from the registry. Optionally, the topics data is proxied from Zenoh.
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_tungstenite::tungstenite::{
+    handshake::server::{Request, Response},
+    http::HeaderValue,
+    Message,
+};
+use zenoh::{
+    key_expr::format::keformat, pubsub::Subscriber, query::ReplyKeyExpr, sample::SampleKind,
+    Session,
+};
+
+use crate::{chunking, keformat_ros2_types, rate_limit, type_name};
+
+// WebSocket sub-protocol of the Foxglove WebSocket protocol
+const FOXGLOVE_SUBPROTOCOL: &str = "foxglove.websocket.v1";
+// Key expression of the rmw_zenoh liveliness tokens
+const KE_RMW_ZENOH_LIVELINESS: &str = "@ros2_lv/**";
+// Entity kind of the publishers in rmw_zenoh liveliness tokens
+const RMW_ZENOH_PUBLISHER: &str = "MP";
+// Opcode of the binary "Message Data" frames
+const MESSAGE_DATA_OPCODE: u8 = 0x01;
+
+// A ROS 2 topic, as discovered from a rmw_zenoh publisher liveliness token
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Topic {
+    domain_id: String,
+    name: String,      // e.g. "/chatter"
+    type_name: String, // e.g. "std_msgs/msg/String"
+    dds_type: String,  // e.g. "std_msgs::msg::dds_::String_"
+    type_hash: String,
+}
+
+impl Topic {
+    // Parse a rmw_zenoh liveliness token of a publisher, with format:
+    // @ros2_lv/<domain>/<zid>/<nid>/<eid>/MP/<enclave>/<namespace>/<node>/<topic>/<type>/<hash>/<qos>
+    // where the '/' in names are replaced with '%'
+    fn from_liveliness_token(ke: &str) -> Option<Self> {
+        let chunks: Vec<&str> = ke.split('/').collect();
+        if chunks.len() < 13 || chunks[5] != RMW_ZENOH_PUBLISHER {
+            return None;
+        }
+        let dds_type = chunks[10].to_string();
+        Some(Self {
+            domain_id: chunks[1].to_string(),
+            name: chunks[9].replace('%', "/"),
+            type_name: ros2_type_name(&dds_type)?,
+            dds_type,
+            type_hash: chunks[11].to_string(),
+        })
+    }
+
+    // The key expression of the topic data published by rmw_zenoh
+    fn data_key_expr(&self) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            self.domain_id,
+            self.name.trim_start_matches('/'),
+            self.dds_type,
+            self.type_hash
+        )
+    }
+}
+
+// Convert a DDS type name to a ROS 2 type name, e.g. "std_msgs::msg::dds_::String_" => "std_msgs/msg/String"
+fn ros2_type_name(dds_type: &str) -> Option<String> {
+    let name = dds_type.replace("::dds_::", "::");
+    let name = name.strip_suffix('_')?;
+    Some(name.replace("::", "/"))
+}
+
+// Run the Foxglove WebSocket server, until the listener fails
+pub(crate) async fn run_server(
+    session: Session,
+    addr: SocketAddr,
+    proxy_data: bool,
+) -> Result<(), String> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind Foxglove WebSocket server on {addr}: {e}"))?;
+    tracing::info!("Foxglove WebSocket server listening on ws://{addr}");
+    loop {
+        let (stream, peer) = listener
+            .accept()
+            .await
+            .map_err(|e| format!("Foxglove WebSocket server failed to accept connection: {e}"))?;
+        let session = session.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(session, stream, proxy_data).await {
+                tracing::warn!("Foxglove WebSocket connection from {peer} closed: {e}");
+            } else {
+                tracing::debug!("Foxglove WebSocket connection from {peer} closed");
+            }
+        });
+    }
+}
+
+// A channel advertised to a client
+struct Channel {
+    topic: Topic,
+}
+
+#[tracing::instrument(skip_all)]
+async fn handle_connection(
+    session: Session,
+    stream: TcpStream,
+    proxy_data: bool,
+) -> Result<(), String> {
+    let ws =
+        tokio_tungstenite::accept_hdr_async(stream, |request: &Request, mut response: Response| {
+            let accepts_foxglove = request
+                .headers()
+                .get_all("Sec-WebSocket-Protocol")
+                .iter()
+                .filter_map(|h| h.to_str().ok())
+                .any(|h| h.split(',').any(|p| p.trim() == FOXGLOVE_SUBPROTOCOL));
+            if accepts_foxglove {
+                response.headers_mut().insert(
+                    "Sec-WebSocket-Protocol",
+                    HeaderValue::from_static(FOXGLOVE_SUBPROTOCOL),
+                );
+            }
+            Ok(response)
+        })
+        .await
+        .map_err(|e| format!("WebSocket handshake failed: {e}"))?;
+    let (mut ws_tx, mut ws_rx) = ws.split();
+
+    send_json(
+        &mut ws_tx,
+        json!({
+            "op": "serverInfo",
+            "name": "ros2-types-registry",
+            "capabilities": [],
+            "supportedEncodings": ["cdr"],
+            "metadata": {},
+        }),
+    )
+    .await?;
+
+    // discover the topics from the rmw_zenoh liveliness tokens (including the existing ones)
+    let liveliness_subscriber = session
+        .liveliness()
+        .declare_subscriber(KE_RMW_ZENOH_LIVELINESS)
+        .history(true)
+        .await
+        .map_err(|e| format!("Failed to subscribe to rmw_zenoh liveliness tokens: {e}"))?;
+
+    let mut channels: HashMap<u32, Channel> = HashMap::new();
+    // number of publishers per topic, to unadvertise a topic when it has no more publisher
+    let mut publishers: HashMap<Topic, (u32, usize)> = HashMap::new();
+    let mut next_channel_id = 0u32;
+    // when proxying data, the Zenoh subscribers per subscription id,
+    // forwarding the received payloads via this mpsc channel
+    let mut data_subscribers: HashMap<u32, Subscriber<()>> = HashMap::new();
+    let (data_tx, mut data_rx) = mpsc::unbounded_channel::<(u32, u64, Vec<u8>)>();
+
+    loop {
+        tokio::select! {
+            sample = liveliness_subscriber.recv_async() => {
+                let Ok(sample) = sample else {
+                    return Err("Liveliness subscriber closed".into());
+                };
+                let Some(topic) = Topic::from_liveliness_token(sample.key_expr().as_str()) else {
+                    continue;
+                };
+                match sample.kind() {
+                    SampleKind::Put => {
+                        if let Some((_, count)) = publishers.get_mut(&topic) {
+                            *count += 1;
+                            continue;
+                        }
+                        let schema = match get_mcap_schema(&session, &topic).await {
+                            Ok(schema) => schema,
+                            Err(e) => {
+                                tracing::warn!("Can't advertise topic {}: {e}", topic.name);
+                                continue;
+                            }
+                        };
+                        let id = next_channel_id;
+                        next_channel_id += 1;
+                        tracing::debug!("Advertise topic {} ({}) as channel {id}", topic.name, topic.type_name);
+                        send_json(&mut ws_tx, json!({
+                            "op": "advertise",
+                            "channels": [{
+                                "id": id,
+                                "topic": topic.name,
+                                "encoding": "cdr",
+                                "schemaName": topic.type_name,
+                                "schema": schema,
+                                "schemaEncoding": "ros2msg",
+                            }],
+                        })).await?;
+                        publishers.insert(topic.clone(), (id, 1));
+                        channels.insert(id, Channel { topic });
+                    }
+                    SampleKind::Delete => {
+                        let Some((id, count)) = publishers.get_mut(&topic) else {
+                            continue;
+                        };
+                        *count -= 1;
+                        if *count == 0 {
+                            let id = *id;
+                            tracing::debug!("Unadvertise topic {} (channel {id})", topic.name);
+                            publishers.remove(&topic);
+                            channels.remove(&id);
+                            send_json(&mut ws_tx, json!({"op": "unadvertise", "channelIds": [id]})).await?;
+                        }
+                    }
+                }
+            },
+
+            message = ws_rx.next() => {
+                let message = match message {
+                    Some(Ok(message)) => message,
+                    Some(Err(e)) => return Err(format!("WebSocket error: {e}")),
+                    None => return Ok(()),
+                };
+                let Message::Text(text) = message else {
+                    if message.is_close() {
+                        return Ok(());
+                    }
+                    continue;
+                };
+                let Ok(request) = serde_json::from_str::<Value>(&text) else {
+                    tracing::warn!("Invalid JSON message from Foxglove client: {text}");
+                    continue;
+                };
+                match request["op"].as_str() {
+                    Some("subscribe") if proxy_data => {
+                        for subscription in request["subscriptions"].as_array().into_iter().flatten() {
+                            let (Some(sub_id), Some(channel_id)) = (
+                                subscription["id"].as_u64().and_then(|id| u32::try_from(id).ok()),
+                                subscription["channelId"].as_u64().and_then(|id| u32::try_from(id).ok()),
+                            ) else {
+                                continue;
+                            };
+                            let Some(channel) = channels.get(&channel_id) else {
+                                continue;
+                            };
+                            let data_tx = data_tx.clone();
+                            match session
+                                .declare_subscriber(channel.topic.data_key_expr())
+                                .callback(move |sample| {
+                                    let timestamp_ns = sample
+                                        .timestamp()
+                                        .map(|t| t.get_time().to_system_time())
+                                        .unwrap_or_else(SystemTime::now)
+                                        .duration_since(UNIX_EPOCH)
+                                        .map_or(0, |d| d.as_nanos() as u64);
+                                    let _ = data_tx.send((sub_id, timestamp_ns, sample.payload().to_bytes().into_owned()));
+                                })
+                                .await
+                            {
+                                Ok(subscriber) => {
+                                    data_subscribers.insert(sub_id, subscriber);
+                                }
+                                Err(e) => tracing::warn!("Failed to subscribe to topic {}: {e}", channel.topic.name),
+                            }
+                        }
+                    }
+                    Some("unsubscribe") => {
+                        for sub_id in request["subscriptionIds"].as_array().into_iter().flatten() {
+                            if let Some(sub_id) = sub_id.as_u64().and_then(|id| u32::try_from(id).ok()) {
+                                data_subscribers.remove(&sub_id);
+                            }
+                        }
+                    }
+                    op => tracing::debug!("Ignoring Foxglove client operation {op:?}"),
+                }
+            },
+
+            Some((sub_id, timestamp_ns, payload)) = data_rx.recv() => {
+                let mut frame = Vec::with_capacity(1 + 4 + 8 + payload.len());
+                frame.push(MESSAGE_DATA_OPCODE);
+                frame.extend_from_slice(&sub_id.to_le_bytes());
+                frame.extend_from_slice(&timestamp_ns.to_le_bytes());
+                frame.extend_from_slice(&payload);
+                ws_tx
+                    .send(Message::Binary(frame))
+                    .await
+                    .map_err(|e| format!("Failed to send message data: {e}"))?;
+            },
+        }
+    }
+}
+
+// Get the MCAP schema of a topic type from the registry, via its own queryable
+async fn get_mcap_schema(session: &Session, topic: &Topic) -> Result<String, String> {
+    let ke = keformat!(
+        keformat_ros2_types::formatter(),
+        type_name = type_name::escape(&topic.type_name)
+    )
+    .map_err(|e| format!("Invalid type name {}: {e}", topic.type_name))?;
+    let replies = session
+        .get(format!("{ke}?format=Mcap;hash={}", topic.type_hash))
+        .accept_replies(ReplyKeyExpr::Any)
+        .attachment(rate_limit::local_attachment())
+        .await
+        .map_err(|e| format!("Failed to query type {}: {e}", topic.type_name))?;
+    let Some(reply) = chunking::recv_replies(replies).await.into_iter().next() else {
+        return Err(format!("Type {} not found in registry", topic.type_name));
+    };
+    match reply {
+        Ok(sample) => sample
+            .payload()
+            .try_to_string()
+            .map(|s| s.into_owned())
+            .map_err(|e| format!("Invalid schema for type {}: {e}", topic.type_name)),
+        Err(e) => Err(format!(
+            "Error getting schema for type {}: {}",
+            topic.type_name,
+            e.payload().try_to_string().unwrap_or_default()
+        )),
+    }
+}
+
+async fn send_json<S>(ws_tx: &mut S, value: Value) -> Result<(), String>
+where
+    S: SinkExt<Message> + Unpin,
+    S::Error: std::fmt::Display,
+{
+    ws_tx
+        .send(Message::Text(value.to_string()))
+        .await
+        .map_err(|e| format!("Failed to send message: {e}"))
+}
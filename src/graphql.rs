@@ -0,0 +1,257 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// A GraphQL schema for the exploration of the types graph, served by the HTTP server on /graphql.
+// The types are resolved lazily via the registry's own Queryables, so that a request only fetches
+// the slice of the graph it selects, e.g.:
+//   { type(name: "geometry_msgs/msg/Pose") { hash fields { name nestedType { hash } } } }
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Error, Object, Result, Schema};
+use serde_json::Value;
+use zenoh::{key_expr::format::keformat, query::ReplyKeyExpr, Session};
+
+use crate::{
+    chunking::{self, ReplySample},
+    keformat_ros2_types, rate_limit,
+    type_description::{self, IndividualTypeDescription},
+    type_name,
+};
+
+pub(crate) type RegistrySchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+// Build the GraphQL schema, resolving the types via the Zenoh session
+pub(crate) fn schema(session: Session) -> RegistrySchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(session)
+        // the depth of the nested types is bounded, as for the other traversals
+        .limit_depth(type_description::MAX_NESTING_DEPTH)
+        .finish()
+}
+
+pub(crate) struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    // A type by its name (e.g. "std_msgs/msg/String")
+    async fn r#type(&self, ctx: &Context<'_>, name: String) -> Result<Option<Type>> {
+        Type::fetch(ctx.data::<Session>()?, &name).await
+    }
+
+    // The types matching a key expression (all types by default)
+    async fn types(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = "**")] key_expr: String,
+    ) -> Result<Vec<Type>> {
+        let ke = keformat!(keformat_ros2_types::formatter(), type_name = &key_expr)
+            .map_err(|e| Error::new(format!("Invalid key expression '{key_expr}': {e}")))?;
+        let samples = query(ctx.data::<Session>()?, format!("{ke}?format=Summary")).await?;
+        samples.iter().map(Type::from_sample).collect()
+    }
+
+    // A type by its type hash (e.g. "RIHS01_...")
+    async fn type_by_hash(&self, ctx: &Context<'_>, hash: String) -> Result<Option<Type>> {
+        let ke = keformat!(keformat_ros2_types::formatter(), type_name = "**")
+            .map_err(|e| Error::new(e.to_string()))?;
+        let samples = query(
+            ctx.data::<Session>()?,
+            format!("{ke}?format=Summary;hash={hash}"),
+        )
+        .await?;
+        samples.first().map(Type::from_sample).transpose()
+    }
+}
+
+// A type, from its Summary
+pub(crate) struct Type {
+    summary: Value,
+}
+
+impl Type {
+    async fn fetch(session: &Session, name: &str) -> Result<Option<Type>> {
+        let samples = query(session, type_selector(name, "Summary")?).await?;
+        samples.first().map(Type::from_sample).transpose()
+    }
+
+    fn from_sample(sample: &ReplySample) -> Result<Type> {
+        serde_json::from_slice(&sample.payload().to_bytes())
+            .map(|summary| Type { summary })
+            .map_err(|e| Error::new(format!("Invalid type summary: {e}")))
+    }
+
+    fn summary_str(&self, key: &str) -> &str {
+        self.summary[key].as_str().unwrap_or_default()
+    }
+
+    async fn description(&self, session: &Session) -> Result<IndividualTypeDescription> {
+        let name = self.summary_str("type_name");
+        let samples = query(session, type_selector(name, "TypeDescription")?).await?;
+        let sample = samples
+            .first()
+            .ok_or_else(|| Error::new(format!("Type '{name}' not found")))?;
+        serde_json::from_slice(&sample.payload().to_bytes())
+            .map_err(|e| Error::new(format!("Invalid type description of '{name}': {e}")))
+    }
+}
+
+#[Object]
+impl Type {
+    async fn name(&self) -> &str {
+        self.summary_str("type_name")
+    }
+
+    // "msg", "srv" or "action"
+    async fn kind(&self) -> &str {
+        self.summary_str("kind")
+    }
+
+    async fn package(&self) -> &str {
+        self.summary_str("package")
+    }
+
+    async fn hash(&self) -> &str {
+        self.summary_str("type_hash")
+    }
+
+    async fn schema_version(&self) -> &str {
+        self.summary_str("schema_version")
+    }
+
+    // The original .msg/.srv/.action definition
+    async fn definition(&self, ctx: &Context<'_>) -> Result<String> {
+        let name = self.summary_str("type_name");
+        let samples = query(ctx.data::<Session>()?, type_selector(name, "Definition")?).await?;
+        let sample = samples
+            .first()
+            .ok_or_else(|| Error::new(format!("Type '{name}' not found")))?;
+        Ok(String::from_utf8_lossy(&sample.payload().to_bytes()).into_owned())
+    }
+
+    async fn fields(&self, ctx: &Context<'_>) -> Result<Vec<Field>> {
+        let description = self.description(ctx.data::<Session>()?).await?;
+        Ok(description.fields.into_iter().map(Field::from).collect())
+    }
+
+    // The direct dependencies (i.e. the nested types of the fields) found in the registry
+    async fn dependencies(&self, ctx: &Context<'_>) -> Result<Vec<Type>> {
+        let session = ctx.data::<Session>()?;
+        let description = self.description(session).await?;
+        let mut names: Vec<&str> = description
+            .fields
+            .iter()
+            .map(|f| &*f.r#type.nested_type_name)
+            .filter(|n| !n.is_empty())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        let mut dependencies = Vec::with_capacity(names.len());
+        for name in names {
+            if let Some(t) = Type::fetch(session, name).await? {
+                dependencies.push(t);
+            }
+        }
+        Ok(dependencies)
+    }
+}
+
+pub(crate) struct Field {
+    name: String,
+    type_id: String,
+    capacity: u32,
+    string_capacity: u32,
+    nested_type_name: Option<String>,
+    default_value: Option<String>,
+}
+
+impl From<type_description::Field> for Field {
+    fn from(field: type_description::Field) -> Self {
+        Field {
+            name: field.name.to_string(),
+            type_id: format!("{:?}", field.r#type.type_id),
+            capacity: field.r#type.capacity,
+            string_capacity: field.r#type.string_capacity,
+            nested_type_name: Some(field.r#type.nested_type_name.to_string())
+                .filter(|n| !n.is_empty()),
+            default_value: field.default_value.filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+#[Object]
+impl Field {
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    // The FieldTypeId name (e.g. "Int32", "NestedTypeUnboundedSequence")
+    async fn type_id(&self) -> &str {
+        &self.type_id
+    }
+
+    async fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    async fn string_capacity(&self) -> u32 {
+        self.string_capacity
+    }
+
+    async fn nested_type_name(&self) -> Option<&str> {
+        self.nested_type_name.as_deref()
+    }
+
+    async fn default_value(&self) -> Option<&str> {
+        self.default_value.as_deref()
+    }
+
+    // The nested type, if any and found in the registry
+    async fn nested_type(&self, ctx: &Context<'_>) -> Result<Option<Type>> {
+        match &self.nested_type_name {
+            Some(name) => Type::fetch(ctx.data::<Session>()?, name).await,
+            None => Ok(None),
+        }
+    }
+}
+
+fn type_selector(name: &str, format: &str) -> Result<String> {
+    let ke = keformat!(
+        keformat_ros2_types::formatter(),
+        type_name = type_name::escape(name)
+    )
+    .map_err(|e| Error::new(format!("Invalid type name '{name}': {e}")))?;
+    Ok(format!("{ke}?format={format}"))
+}
+
+// Query the registry, returning all the replies samples or the message of the first error reply
+async fn query(session: &Session, selector: String) -> Result<Vec<ReplySample>> {
+    let replies = session
+        .get(&selector)
+        .accept_replies(ReplyKeyExpr::Any)
+        .attachment(rate_limit::local_attachment())
+        .await
+        .map_err(|e| Error::new(format!("Failed to query '{selector}': {e}")))?;
+    let mut samples = Vec::new();
+    for reply in chunking::recv_replies(replies).await {
+        match reply {
+            Ok(sample) => samples.push(sample),
+            Err(err) => {
+                let payload = err.payload().to_bytes();
+                let message = serde_json::from_slice::<Value>(&payload)
+                    .ok()
+                    .and_then(|v| v["message"].as_str().map(String::from))
+                    .unwrap_or_else(|| String::from_utf8_lossy(&payload).into_owned());
+                return Err(Error::new(message));
+            }
+        }
+    }
+    Ok(samples)
+}
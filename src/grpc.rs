@@ -0,0 +1,186 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// A gRPC server exposing the registry (see proto/registry.proto) for the backend services
+// that can't embed a Zenoh client. As for the HTTP server, the requests are forwarded to the
+// registry's own Queryables, and their error replies converted to gRPC status codes.
+use std::net::SocketAddr;
+
+use serde_json::Value;
+use tonic::{Request, Response, Status};
+use zenoh::{key_expr::format::keformat, query::ReplyKeyExpr, Session};
+
+use crate::{
+    chunking::{self, ReplySample},
+    keformat_ros2_types, rate_limit, type_name,
+};
+
+mod proto {
+    tonic::include_proto!("ros2_types_registry");
+}
+
+use proto::{
+    registry_server::{Registry, RegistryServer},
+    GetMcapSchemaRequest, GetTypeRequest, ListTypesReply, ListTypesRequest, ResolveHashRequest,
+    TypeReply,
+};
+
+// Run the gRPC server, until it fails
+pub(crate) async fn run_server(session: Session, addr: SocketAddr) -> Result<(), String> {
+    tracing::info!("gRPC server listening on {addr}");
+    tonic::transport::Server::builder()
+        .add_service(RegistryServer::new(RegistryService { session }))
+        .serve(addr)
+        .await
+        .map_err(|e| format!("gRPC server failed on {addr}: {e}"))
+}
+
+struct RegistryService {
+    session: Session,
+}
+
+#[tonic::async_trait]
+impl Registry for RegistryService {
+    async fn get_type(
+        &self,
+        request: Request<GetTypeRequest>,
+    ) -> Result<Response<TypeReply>, Status> {
+        let request = request.into_inner();
+        let mut parameters = Vec::new();
+        if !request.format.is_empty() {
+            parameters.push(format!("format={}", request.format));
+        }
+        self.get_one_type(&request.type_name, &parameters).await
+    }
+
+    async fn list_types(
+        &self,
+        request: Request<ListTypesRequest>,
+    ) -> Result<Response<ListTypesReply>, Status> {
+        let request = request.into_inner();
+        let key_expr = match request.key_expr.as_str() {
+            "" => "**",
+            key_expr => key_expr,
+        };
+        let ke = keformat!(keformat_ros2_types::formatter(), type_name = key_expr)
+            .map_err(|e| Status::invalid_argument(format!("Invalid key expression: {e}")))?;
+        let samples = query(&self.session, format!("{ke}?format=Hash")).await?;
+        let mut type_names: Vec<String> = samples.iter().filter_map(reply_type_name).collect();
+        type_names.sort_unstable();
+        Ok(Response::new(ListTypesReply { type_names }))
+    }
+
+    async fn resolve_hash(
+        &self,
+        request: Request<ResolveHashRequest>,
+    ) -> Result<Response<TypeReply>, Status> {
+        let request = request.into_inner();
+        let mut parameters = vec![format!("hash={}", request.type_hash)];
+        if !request.format.is_empty() {
+            parameters.push(format!("format={}", request.format));
+        }
+        let ke = keformat!(keformat_ros2_types::formatter(), type_name = "**")
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let samples = query(&self.session, format!("{ke}?{}", parameters.join(";"))).await?;
+        match samples.first() {
+            Some(sample) => Ok(Response::new(type_reply(sample))),
+            None => Err(Status::not_found(format!(
+                "No type with hash '{}'",
+                request.type_hash
+            ))),
+        }
+    }
+
+    async fn get_mcap_schema(
+        &self,
+        request: Request<GetMcapSchemaRequest>,
+    ) -> Result<Response<TypeReply>, Status> {
+        let request = request.into_inner();
+        let parameters = [
+            "format=Mcap".to_string(),
+            format!("strict={}", request.strict),
+        ];
+        self.get_one_type(&request.type_name, &parameters).await
+    }
+}
+
+impl RegistryService {
+    async fn get_one_type(
+        &self,
+        name: &str,
+        parameters: &[String],
+    ) -> Result<Response<TypeReply>, Status> {
+        let ke = keformat!(
+            keformat_ros2_types::formatter(),
+            type_name = type_name::escape(name)
+        )
+        .map_err(|e| Status::invalid_argument(format!("Invalid type name '{name}': {e}")))?;
+        let samples = query(&self.session, format!("{ke}?{}", parameters.join(";"))).await?;
+        match samples.first() {
+            Some(sample) => Ok(Response::new(type_reply(sample))),
+            None => Err(Status::not_found(format!("Type '{name}' not found"))),
+        }
+    }
+}
+
+// Query the registry, returning all the replies samples or the status of the first error reply
+async fn query(session: &Session, selector: String) -> Result<Vec<ReplySample>, Status> {
+    let replies = session
+        .get(&selector)
+        .accept_replies(ReplyKeyExpr::Any)
+        .attachment(rate_limit::local_attachment())
+        .await
+        .map_err(|e| Status::internal(format!("Failed to query '{selector}': {e}")))?;
+    let mut samples = Vec::new();
+    for reply in chunking::recv_replies(replies).await {
+        match reply {
+            Ok(sample) => samples.push(sample),
+            Err(err) => return Err(error_status(&err.payload().to_bytes())),
+        }
+    }
+    Ok(samples)
+}
+
+// The gRPC status of an error reply, according to its error code
+fn error_status(payload: &[u8]) -> Status {
+    let error = serde_json::from_slice::<Value>(payload).unwrap_or(Value::Null);
+    let message = error["message"]
+        .as_str()
+        .map(String::from)
+        .unwrap_or_else(|| String::from_utf8_lossy(payload).into_owned());
+    match error["code"].as_str() {
+        Some("invalid_parameter") | Some("invalid_payload") | Some("invalid_key_expr") => {
+            Status::invalid_argument(message)
+        }
+        Some("not_allowed") => Status::permission_denied(message),
+        Some("unknown_operation") | Some("not_found") => Status::not_found(message),
+        Some("rendering_failed") => Status::failed_precondition(message),
+        _ => Status::internal(message),
+    }
+}
+
+// The original type name of a reply, from its key expression
+fn reply_type_name(sample: &ReplySample) -> Option<String> {
+    keformat_ros2_types::parse(sample.key_expr())
+        .ok()?
+        .type_name()
+        .map(|name| type_name::unescape(name.as_str()).into_owned())
+}
+
+fn type_reply(sample: &ReplySample) -> TypeReply {
+    TypeReply {
+        type_name: reply_type_name(sample).unwrap_or_default(),
+        encoding: sample.encoding().to_string(),
+        payload: sample.payload().to_bytes().into_owned(),
+    }
+}
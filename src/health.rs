@@ -0,0 +1,80 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use serde::Serialize;
+use zenoh::{bytes::Encoding, query::Query};
+
+use crate::{
+    access_log::QueryStats, query_reply_ke, registry::Registry, send_reply, send_reply_err,
+    ErrorCode,
+};
+
+// Key expression on which the health status is replied and published
+pub(crate) const KE_HEALTH: &str = "@ros2_health";
+
+// Health status of the registry
+#[derive(Debug, Serialize)]
+pub(crate) struct HealthStatus {
+    pub status: &'static str, // "paused" for maintenance, "warming_up" while some indexed types are not parsed yet, "unresolved" with dangling references (see --strict-dependencies), "ready" otherwise
+    pub loaded: usize,        // number of types loaded
+    pub pending: usize,       // number of types indexed but not parsed yet
+    // number of references to types not found in the registry
+    pub dangling_references: usize,
+}
+
+impl HealthStatus {
+    pub fn of(registry: &Registry) -> Self {
+        let pending = registry.get_pending_count();
+        Self {
+            status: if registry.get_maintenance().is_some() {
+                "paused"
+            } else if pending > 0 {
+                "warming_up"
+            } else if !registry.is_resolved() {
+                "unresolved"
+            } else {
+                "ready"
+            },
+            loaded: registry.get_size(),
+            pending,
+            dangling_references: registry.get_dangling_references().len(),
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.status == "ready"
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Failed to serialize health status: {e}"))
+    }
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+pub(crate) async fn handle_ros2_health_query(query: Query, registry: &Registry<'_>) -> QueryStats {
+    tracing::debug!("Received query: {}", query.key_expr());
+    let mut stats = QueryStats::default();
+    match HealthStatus::of(registry).to_json() {
+        Ok(json) => {
+            stats.matched = 1;
+            stats.bytes_sent += send_reply(
+                &query,
+                query_reply_ke(&query, registry.get_namespace()),
+                json,
+                Encoding::APPLICATION_JSON,
+            )
+            .await;
+        }
+        Err(e) => send_reply_err(&query, ErrorCode::SerializationFailed, e).await,
+    }
+    stats
+}
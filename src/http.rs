@@ -0,0 +1,235 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// A HTTP server with dedicated routes for the non-Zenoh tools (curl, browsers, CI scripts):
+//   GET /types/{pkg}/{kind}/{name}  => the type, with the same parameters than the types Queryable
+//   GET /packages                   => all the packages with their types, as a JSON object
+//   GET /packages/{pkg}             => the types of a package, as a JSON array
+//   GET /health                     => the health status
+//   POST /graphql                   => the GraphQL API (with the "graphql" feature)
+// The requests are forwarded to the registry's own Queryables, and their replies converted
+// to HTTP responses with the corresponding content types and status codes.
+use std::{collections::HashMap, net::SocketAddr};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use serde_json::{Map, Value};
+use zenoh::{key_expr::format::keformat, query::ReplyKeyExpr, Session};
+
+use crate::{
+    chunking::{self, ReplySample},
+    health, keformat_ros2_packages, keformat_ros2_types, rate_limit, type_name, ErrorCode,
+};
+
+// Run the HTTP server, until the listener fails
+pub(crate) async fn run_server(session: Session, addr: SocketAddr) -> Result<(), String> {
+    let app = Router::new()
+        .route("/types/:pkg/:kind/:name", get(get_type))
+        .route("/packages", get(get_packages))
+        .route("/packages/:pkg", get(get_package))
+        .route("/health", get(get_health));
+    #[cfg(feature = "graphql")]
+    let app = app.route_service(
+        "/graphql",
+        async_graphql_axum::GraphQL::new(crate::graphql::schema(session.clone())),
+    );
+    let app = app.with_state(session);
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind HTTP server on {addr}: {e}"))?;
+    tracing::info!("HTTP server listening on http://{addr}");
+    axum::serve(listener, app)
+        .await
+        .map_err(|e| format!("HTTP server failed: {e}"))
+}
+
+async fn get_type(
+    State(session): State<Session>,
+    Path((pkg, kind, name)): Path<(String, String, String)>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let type_name = format!("{pkg}/{kind}/{name}");
+    let ke = match keformat!(
+        keformat_ros2_types::formatter(),
+        type_name = type_name::escape(&type_name)
+    ) {
+        Ok(ke) => ke,
+        Err(e) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidParameter,
+                format!("Invalid type name '{type_name}': {e}"),
+            )
+        }
+    };
+    // the HTTP query parameters are passed as the Selector parameters
+    let parameters = params
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join(";");
+    match query(&session, format!("{ke}?{parameters}")).await {
+        Ok(samples) => match samples.into_iter().next() {
+            Some(sample) => sample_response(StatusCode::OK, &sample),
+            None => error_response(
+                StatusCode::NOT_FOUND,
+                ErrorCode::NotFound,
+                format!("Type '{type_name}' not found"),
+            ),
+        },
+        Err(response) => response,
+    }
+}
+
+async fn get_packages(State(session): State<Session>) -> Response {
+    let ke = match keformat!(keformat_ros2_packages::formatter(), package = "*") {
+        Ok(ke) => ke,
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::Internal,
+                e.to_string(),
+            )
+        }
+    };
+    let samples = match query(&session, ke.to_string()).await {
+        Ok(samples) => samples,
+        Err(response) => return response,
+    };
+    let mut packages = Map::new();
+    for sample in samples {
+        let Ok(package) = keformat_ros2_packages::parse(sample.key_expr()) else {
+            continue;
+        };
+        let types =
+            serde_json::from_slice::<Value>(&sample.payload().to_bytes()).unwrap_or(Value::Null);
+        packages.insert(package.package().to_string(), types);
+    }
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/json")],
+        Value::Object(packages).to_string(),
+    )
+        .into_response()
+}
+
+async fn get_package(State(session): State<Session>, Path(pkg): Path<String>) -> Response {
+    let ke = match keformat!(keformat_ros2_packages::formatter(), package = &pkg) {
+        Ok(ke) => ke,
+        Err(e) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidParameter,
+                format!("Invalid package name '{pkg}': {e}"),
+            )
+        }
+    };
+    match query(&session, ke.to_string()).await {
+        Ok(samples) => match samples.into_iter().next() {
+            Some(sample) => sample_response(StatusCode::OK, &sample),
+            None => error_response(
+                StatusCode::NOT_FOUND,
+                ErrorCode::NotFound,
+                format!("Package '{pkg}' not found"),
+            ),
+        },
+        Err(response) => response,
+    }
+}
+
+async fn get_health(State(session): State<Session>) -> Response {
+    match query(&session, health::KE_HEALTH.to_string()).await {
+        Ok(samples) => match samples.into_iter().next() {
+            Some(sample) => sample_response(StatusCode::OK, &sample),
+            None => error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::Internal,
+                "No health status".to_string(),
+            ),
+        },
+        Err(response) => response,
+    }
+}
+
+// Query the registry, returning all the replies samples or the response of the first error reply
+async fn query(session: &Session, selector: String) -> Result<Vec<ReplySample>, Response> {
+    let replies = session
+        .get(&selector)
+        .accept_replies(ReplyKeyExpr::Any)
+        .attachment(rate_limit::local_attachment())
+        .await
+        .map_err(|e| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::Internal,
+                format!("Failed to query '{selector}': {e}"),
+            )
+        })?;
+    let mut samples = Vec::new();
+    for reply in chunking::recv_replies(replies).await {
+        match reply {
+            Ok(sample) => samples.push(sample),
+            Err(err) => {
+                let payload = err.payload().to_bytes();
+                return Err((
+                    error_status(&payload),
+                    [(header::CONTENT_TYPE, err.encoding().to_string())],
+                    payload.into_owned(),
+                )
+                    .into_response());
+            }
+        }
+    }
+    Ok(samples)
+}
+
+// The HTTP status of an error reply, according to its error code
+fn error_status(payload: &[u8]) -> StatusCode {
+    let code = serde_json::from_slice::<Value>(payload)
+        .ok()
+        .and_then(|v| v["code"].as_str().map(String::from));
+    match code.as_deref() {
+        Some("invalid_parameter") | Some("invalid_payload") | Some("invalid_key_expr") => {
+            StatusCode::BAD_REQUEST
+        }
+        Some("not_allowed") => StatusCode::FORBIDDEN,
+        Some("maintenance") => StatusCode::SERVICE_UNAVAILABLE,
+        Some("unknown_operation") | Some("not_found") => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn sample_response(status: StatusCode, sample: &ReplySample) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, sample.encoding().to_string())],
+        sample.payload().to_bytes().into_owned(),
+    )
+        .into_response()
+}
+
+// An error response, with the same JSON format than the Queryables error replies
+fn error_response(status: StatusCode, code: ErrorCode, message: String) -> Response {
+    tracing::debug!("HTTP error {status}: {message}");
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        serde_json::json!({ "code": code, "message": message }).to_string(),
+    )
+        .into_response()
+}
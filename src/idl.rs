@@ -0,0 +1,170 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// A self-contained IDL document of a type and its dependencies, for the IDL compilers which can't
+// be given include paths. The IDL file of each type (its definition, or the .idl file generated by
+// rosidl_adapter next to its .msg/.srv/.action file) is added after those of its dependencies,
+// without its '#include' directives, and the consecutive types of a same module (e.g.
+// "std_msgs::msg") are merged in a single module declaration.
+use std::{collections::HashSet, sync::OnceLock};
+
+use regex::Regex;
+
+use crate::{
+    definition_cache,
+    registry::{self, Registry},
+    type_description::MAX_NESTING_DEPTH,
+    type_info::TypeInfo,
+};
+
+// The 2 nested modules (package and kind) of an IDL file generated by rosidl
+static MODULES: OnceLock<Regex> = OnceLock::new();
+
+pub(crate) fn expanded_idl(registry: &Registry, t: &TypeInfo) -> Result<String, String> {
+    let mut types = Vec::new();
+    collect_types(registry, t, &mut HashSet::new(), &mut types, 0)?;
+
+    let mut text = format!(
+        "// Self-contained IDL of {} with its dependencies\n",
+        t.get_type_name()
+    );
+    // the module currently open, if any
+    let mut open_module: Option<(String, String)> = None;
+    for t in types {
+        let content = idl_content(registry, t)?;
+        match split_modules(&content) {
+            Some((package, kind, body)) => {
+                let module = (package.to_string(), kind.to_string());
+                if open_module.as_ref() != Some(&module) {
+                    if open_module.is_some() {
+                        text.push_str("  };\n};\n");
+                    }
+                    text.push_str(&format!("\nmodule {package} {{\n  module {kind} {{\n"));
+                    open_module = Some(module);
+                }
+                text.push_str(body.trim_matches('\n'));
+                text.push('\n');
+            }
+            // not structured as a rosidl IDL file: added as is
+            None => {
+                if open_module.take().is_some() {
+                    text.push_str("  };\n};\n");
+                }
+                text.push('\n');
+                text.push_str(content.trim());
+                text.push('\n');
+            }
+        }
+    }
+    if open_module.is_some() {
+        text.push_str("  };\n};\n");
+    }
+    Ok(text)
+}
+
+// Collect a type and its dependencies, each dependency before the types using it
+fn collect_types<'r>(
+    registry: &'r Registry,
+    t: &'r TypeInfo,
+    visited: &mut HashSet<&'r str>,
+    types: &mut Vec<&'r TypeInfo>,
+    depth: usize,
+) -> Result<(), String> {
+    if depth >= MAX_NESTING_DEPTH {
+        return Err(format!("Too deep dependencies for type {}", t.full_name));
+    }
+    if !visited.insert(t.full_name.as_str()) {
+        return Ok(());
+    }
+    for dep in registry::direct_dependencies(t) {
+        let dep_info = registry
+            .get_type(dep)
+            .ok_or_else(|| format!("Dependency {dep} of {} not found in registry", t.full_name))?;
+        collect_types(registry, dep_info, visited, types, depth + 1)?;
+    }
+    types.push(t);
+    Ok(())
+}
+
+// The IDL content of a type, without its '#include' directives
+fn idl_content(registry: &Registry, t: &TypeInfo) -> Result<String, String> {
+    let content = match t.definition_path.extension() {
+        Some(extension) if extension == "idl" => registry.get_definition_content(t)?.to_string(),
+        _ => definition_cache::read_definition(&t.definition_path.with_extension("idl"))?,
+    };
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("#include"))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+// Split an IDL file generated by rosidl in its package and kind modules names and the body of
+// the kind module (e.g. "std_msgs", "msg" and the struct declarations)
+fn split_modules(content: &str) -> Option<(&str, &str, &str)> {
+    let modules = MODULES.get_or_init(|| {
+        Regex::new(r"module\s+(\w+)\s*\{\s*module\s+(\w+)\s*\{").expect("valid regex")
+    });
+    let captures = modules.captures(content)?;
+    let start = captures.get(0)?.end();
+    let end = start + closing_brace(&content[start..])?;
+    // the rest must only close the package module
+    let rest = content[end + 1..].trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+    if rest.trim() != "}" && rest.trim() != "};" {
+        return None;
+    }
+    Some((
+        captures.get(1)?.as_str(),
+        captures.get(2)?.as_str(),
+        &content[start..end],
+    ))
+}
+
+// Return the position of the brace closing a block (whose opening brace precedes the text),
+// ignoring the braces in the string literals and the comments
+fn closing_brace(text: &str) -> Option<usize> {
+    let mut depth = 1;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                // skip the string literal, with its escaped characters
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek().is_some_and(|(_, c)| *c == '/') => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
@@ -0,0 +1,340 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Textual outputs reproducing the `ros2 interface` commands, for the scripts written against the
+// ROS CLI. Available under the @ros2_interface/** key space:
+//   @ros2_interface/list[?msgs][;srvs][;actions]  => `ros2 interface list [-m] [-s] [-a]`
+//   @ros2_interface/packages                      => `ros2 interface packages`
+//   @ros2_interface/package/<pkg>                 => `ros2 interface package <pkg>`
+//   @ros2_interface/show/<type_name>              => `ros2 interface show <type_name>`
+use zenoh::{
+    bytes::Encoding,
+    key_expr::{format::keformat, keyexpr, KeyExpr},
+    query::Query,
+};
+
+use crate::{
+    access_log::QueryStats,
+    close_names, content_hash, keformat_ros2_interface,
+    registry::Registry,
+    send_not_served, send_reply_err, send_reply_err_details, send_tagged_reply,
+    type_description::MAX_NESTING_DEPTH,
+    type_info::{TypeInfo, TypeKind},
+    type_name, ErrorCode, ErrorDetails,
+};
+
+// Commands available in the @ros2_interface/** key space
+const INTERFACE_COMMANDS: &[&str] = &["list", "packages", "package/<pkg>", "show/<type_name>"];
+
+// Indentation of the nested types definitions in `ros2 interface show`
+const NESTED_INDENT: char = '\t';
+
+// The primitive types of the .msg/.srv/.action definitions
+const PRIMITIVE_TYPES: &[&str] = &[
+    "bool", "byte", "char", "float32", "float64", "int8", "uint8", "int16", "uint16", "int32",
+    "uint32", "int64", "uint64", "string", "wstring",
+];
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+pub(crate) async fn handle_ros2_interface_query(
+    query: Query,
+    registry: &mut Registry<'_>,
+) -> QueryStats {
+    tracing::debug!("Received query: {}", query.key_expr());
+    let mut stats = QueryStats::default();
+    let ke = match keformat_ros2_interface::parse(strip_namespace(query.key_expr())) {
+        Ok(ke) => ke,
+        Err(_) => {
+            tracing::error!(
+                "Received a query on '{}' but it doesn't match the '@ros2_interface/**' queryable!",
+                query.key_expr()
+            );
+            return stats;
+        }
+    };
+    let Some(command) = ke.command() else {
+        return stats;
+    };
+
+    match command.as_str() {
+        "list" => {
+            registry.load_pending_matching(keyexpr::new("**").expect("valid key expression"));
+            let parameters = query.parameters();
+            let only: Vec<TypeKind> = [
+                ("msgs", TypeKind::MSG),
+                ("srvs", TypeKind::SRV),
+                ("actions", TypeKind::ACTION),
+            ]
+            .into_iter()
+            .filter(|(param, _)| parameters.contains_key(*param))
+            .map(|(_, kind)| kind)
+            .collect();
+            stats.matched += 1;
+            stats.bytes_sent += send_text_reply(
+                &query,
+                query_reply_ke(&query, registry.get_namespace()),
+                interface_list(registry, &only),
+                registry,
+            )
+            .await;
+        }
+        "packages" => {
+            registry.load_pending_matching(keyexpr::new("**").expect("valid key expression"));
+            let mut packages: Vec<&str> =
+                registry.get_packages().map(|(p, _)| p.as_str()).collect();
+            packages.sort_unstable();
+            stats.matched += 1;
+            stats.bytes_sent += send_text_reply(
+                &query,
+                query_reply_ke(&query, registry.get_namespace()),
+                lines(packages),
+                registry,
+            )
+            .await;
+        }
+        c => match (c.strip_prefix("package/"), c.strip_prefix("show/")) {
+            (Some(package), _) => {
+                let Ok(types_ke) = keyexpr::new(package)
+                    .map(|p| p / keyexpr::new("**").expect("valid key expression"))
+                else {
+                    return stats;
+                };
+                registry.load_pending_matching(&types_ke);
+                let Some((_, type_names)) =
+                    registry.get_packages().find(|(p, _)| p.as_str() == package)
+                else {
+                    let packages: Vec<String> =
+                        registry.get_packages().map(|(p, _)| p.clone()).collect();
+                    send_reply_err_details(
+                        &query,
+                        ErrorCode::InvalidParameter,
+                        format!("Unknown package '{package}'"),
+                        Some(ErrorDetails {
+                            value: Some(package.to_string()),
+                            candidates: close_names(package, &packages),
+                            ..Default::default()
+                        }),
+                    )
+                    .await;
+                    return stats;
+                };
+                let mut type_names: Vec<String> = type_names
+                    .iter()
+                    .map(|n| type_name::unescape(n.as_str()).into_owned())
+                    .collect();
+                type_names.sort_unstable();
+                stats.matched += 1;
+                stats.bytes_sent += send_text_reply(
+                    &query,
+                    query_reply_ke(&query, registry.get_namespace()),
+                    lines(type_names),
+                    registry,
+                )
+                .await;
+            }
+            (_, Some(types)) => {
+                let Ok(types_ke) = keyexpr::new(types) else {
+                    return stats;
+                };
+                registry.load_pending_matching(types_ke);
+                for t in registry.iter_types(types_ke) {
+                    stats.matched += 1;
+                    if !registry.is_served(t) {
+                        send_not_served(&query, t).await;
+                        continue;
+                    }
+                    let reply_ke = match keformat!(
+                        keformat_ros2_interface::formatter(),
+                        command = format!("show/{}", t.full_name)
+                    ) {
+                        Ok(reply_ke) => namespaced(registry.get_namespace(), reply_ke),
+                        Err(e) => {
+                            send_reply_err(
+                                &query,
+                                ErrorCode::InvalidKeyExpr,
+                                format!(
+                                    "Failed to build the reply key expression for type '{}': {e}",
+                                    t.full_name
+                                ),
+                            )
+                            .await;
+                            continue;
+                        }
+                    };
+                    match interface_show(registry, t) {
+                        Ok(text) => {
+                            stats.bytes_sent +=
+                                send_text_reply(&query, reply_ke, text, registry).await
+                        }
+                        Err(e) => send_reply_err(&query, ErrorCode::RenderingFailed, e).await,
+                    }
+                }
+            }
+            _ => {
+                send_reply_err_details(
+                    &query,
+                    ErrorCode::UnknownOperation,
+                    format!("Unknown command '{command}' - available: {INTERFACE_COMMANDS:?}"),
+                    Some(ErrorDetails::invalid_value(
+                        "command",
+                        command.as_str(),
+                        INTERFACE_COMMANDS,
+                    )),
+                )
+                .await
+            }
+        },
+    }
+    stats
+}
+
+// Send a textual reply, with its content hash and the cache-control metadata in attachment
+async fn send_text_reply(
+    query: &Query,
+    key_expr: impl Into<KeyExpr<'static>>,
+    text: String,
+    registry: &Registry<'_>,
+) -> usize {
+    let etag = content_hash(text.as_bytes());
+    send_tagged_reply(
+        query,
+        key_expr,
+        text,
+        Encoding::TEXT_PLAIN,
+        &etag,
+        &registry.get_cache_control(),
+        &[],
+    )
+    .await
+}
+
+// Reproduce `ros2 interface list`, with only the sections of some kinds of types if specified
+pub(crate) fn interface_list(registry: &Registry, only: &[TypeKind]) -> String {
+    let all_types = keyexpr::new("**").expect("valid key expression");
+    let mut text = String::new();
+    for (kind, title) in [
+        (TypeKind::MSG, "Messages:"),
+        (TypeKind::SRV, "Services:"),
+        (TypeKind::ACTION, "Actions:"),
+    ] {
+        if !only.is_empty() && !only.contains(&kind) {
+            continue;
+        }
+        let mut type_names: Vec<&str> = registry
+            .iter_types(all_types)
+            .filter(|t| t.kind == kind)
+            .map(|t| t.get_type_name())
+            .collect();
+        type_names.sort_unstable();
+        text.push_str(title);
+        text.push('\n');
+        for name in type_names {
+            text.push_str("    ");
+            text.push_str(name);
+            text.push('\n');
+        }
+    }
+    text
+}
+
+// Reproduce `ros2 interface show`: the original definition, where each field of a nested type is
+// followed by the definition of this type (without comments), indented with a tab per nesting level
+pub(crate) fn interface_show(registry: &Registry, t: &TypeInfo) -> Result<String, String> {
+    let mut text = String::new();
+    show_definition(registry, t, 0, &mut Vec::new(), &mut text)?;
+    Ok(text)
+}
+
+// `stack` is the list of the types being shown, to detect circular references
+fn show_definition(
+    registry: &Registry,
+    t: &TypeInfo,
+    depth: usize,
+    stack: &mut Vec<String>,
+    text: &mut String,
+) -> Result<(), String> {
+    if stack.iter().any(|name| name == t.full_name.as_str()) || stack.len() >= MAX_NESTING_DEPTH {
+        return Err(format!(
+            "Circular or too deep reference to type {} (via {})",
+            t.full_name,
+            stack.join(" -> ")
+        ));
+    }
+    stack.push(t.full_name.to_string());
+    let content = registry.get_definition_content(t)?;
+    for line in content.lines() {
+        let line = if depth == 0 {
+            line.trim_end()
+        } else {
+            // the nested definitions are shown without comments nor blank lines
+            match line.split_once('#') {
+                Some((l, _)) if !is_string_field(l) => l.trim_end(),
+                _ => line.trim_end(),
+            }
+        };
+        if depth > 0 && line.trim().is_empty() {
+            continue;
+        }
+        for _ in 0..depth {
+            text.push(NESTED_INDENT);
+        }
+        text.push_str(line);
+        text.push('\n');
+        if let Some(nested_name) = nested_type_name(line, &t.package_name) {
+            match registry.get_type(&nested_name) {
+                Some(nested) => show_definition(registry, nested, depth + 1, stack, text)?,
+                None => tracing::warn!(
+                    "Nested type {nested_name} of {} not found in registry",
+                    t.full_name
+                ),
+            }
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+// True if the line declares a string field or constant (whose value may contain a '#')
+fn is_string_field(line: &str) -> bool {
+    let field_type = line.trim_start();
+    field_type.starts_with("string") || field_type.starts_with("wstring")
+}
+
+// Return the full name of the nested type of a field declaration line, if any
+// (e.g. "geometry_msgs/msg/Point" for "Point[<=3] points" in a geometry_msgs definition)
+fn nested_type_name(line: &str, package: &str) -> Option<String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with("---") {
+        return None;
+    }
+    let (field_type, _) = line.split_once(char::is_whitespace)?;
+    let base_type = field_type.split(['[', '<']).next()?;
+    if PRIMITIVE_TYPES.contains(&base_type) {
+        return None;
+    }
+    match base_type.split('/').collect::<Vec<_>>()[..] {
+        [name] => Some(format!("{package}/msg/{name}")),
+        [pkg, name] => Some(format!("{pkg}/msg/{name}")),
+        [pkg, kind, name] => Some(format!("{pkg}/{kind}/{name}")),
+        _ => None,
+    }
+}
+
+fn lines<S: AsRef<str>>(items: impl IntoIterator<Item = S>) -> String {
+    let mut text = String::new();
+    for item in items {
+        text.push_str(item.as_ref());
+        text.push('\n');
+    }
+    text
+}
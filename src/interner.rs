@@ -0,0 +1,53 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use serde::{Deserialize, Deserializer};
+
+// Global set of interned strings. The same type names, field names and definitions appear
+// many times across the type descriptions (e.g. in referenced_type_descriptions, or in
+// overlays), so they are shared as Arc<str> instead of being duplicated.
+static INTERNER: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+
+// Return the shared instance of the empty string (e.g. for missing names)
+pub(crate) fn empty() -> Arc<str> {
+    intern("")
+}
+
+// Return the shared instance of a string
+pub(crate) fn intern(s: &str) -> Arc<str> {
+    let mut interner = match INTERNER.get_or_init(Default::default).lock() {
+        Ok(interner) => interner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    match interner.get(s) {
+        Some(interned) => interned.clone(),
+        None => {
+            let interned: Arc<str> = Arc::from(s);
+            interner.insert(interned.clone());
+            interned
+        }
+    }
+}
+
+// Deserialize a string as an interned Arc<str> (to be used with #[serde(deserialize_with)])
+pub(crate) fn deserialize_interned<'de, D>(deserializer: D) -> Result<Arc<str>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Ok(intern(&s))
+}
@@ -0,0 +1,113 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// A language-agnostic wire descriptor of a type, equivalent to the MessageMembers structures of
+// rosidl's introspection type support, to drive generic (de)serializers without code generation.
+// The types are in a table (the described type first, then its dependencies), each member
+// referring to its nested type by its index in this table. As in rosidl (since Iron), the type ids
+// are the FieldType ids of the element types, the bounded and fixed strings being strings with
+// an upper bound. The language specific information (e.g. the members offsets) is not included.
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    default_instance::typed_default_value, field_type::FieldTypeId,
+    type_description::IndividualTypeDescription, type_info::TypeInfo,
+};
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Descriptor<'a> {
+    types: Vec<MessageMembers<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct MessageMembers<'a> {
+    message_namespace: String, // e.g. "std_msgs__msg" for "std_msgs/msg/Header"
+    message_name: &'a str,     // e.g. "Header"
+    members: Vec<MessageMember<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct MessageMember<'a> {
+    name: &'a str,
+    type_id: u8,
+    string_upper_bound: u32,
+    nested_type: Option<usize>, // index in the types table, for a nested type
+    is_array: bool,             // true for the arrays and the sequences
+    array_size: u32,            // the size of an array, or the upper bound of a bounded sequence
+    is_upper_bound: bool,       // true for the bounded sequences
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_value: Option<Value>,
+}
+
+// Build the descriptor of a type and of its dependencies
+pub(crate) fn descriptor(t: &TypeInfo) -> Result<Descriptor<'_>, String> {
+    let description = &t.type_description.type_description_msg;
+    let types: Vec<&IndividualTypeDescription> = std::iter::once(&description.type_description)
+        .chain(description.referenced_type_descriptions.iter())
+        .collect();
+    let type_indexes: HashMap<&str, usize> = types
+        .iter()
+        .enumerate()
+        .map(|(i, d)| (&*d.type_name, i))
+        .collect();
+
+    let mut message_members = Vec::with_capacity(types.len());
+    for d in types {
+        let (namespace, name) = d.type_name.rsplit_once('/').unwrap_or(("", &*d.type_name));
+        let mut members = Vec::with_capacity(d.fields.len());
+        for field in &d.fields {
+            let field_type = &field.r#type;
+            let type_id = field_type.type_id;
+            let nested_type = match &*field_type.nested_type_name {
+                "" => None,
+                nested => Some(*type_indexes.get(nested).ok_or(format!(
+                    "Nested type {nested} of {}.{} not found in referenced type descriptions",
+                    d.type_name, field.name
+                ))?),
+            };
+            members.push(MessageMember {
+                name: &field.name,
+                type_id: wire_type_id(type_id.base_type()),
+                string_upper_bound: field_type.string_capacity,
+                nested_type,
+                is_array: type_id.is_collection(),
+                array_size: field_type.capacity,
+                is_upper_bound: type_id.is_bounded_sequence(),
+                default_value: typed_default_value(field).unwrap_or_else(|e| {
+                    tracing::warn!("{e}");
+                    field.default_value.clone().map(Value::String)
+                }),
+            });
+        }
+        message_members.push(MessageMembers {
+            message_namespace: namespace.replace('/', "__"),
+            message_name: name,
+            members,
+        });
+    }
+    Ok(Descriptor {
+        types: message_members,
+    })
+}
+
+// The introspection type id of an element type
+fn wire_type_id(base_type: FieldTypeId) -> u8 {
+    match base_type {
+        FieldTypeId::FixedString | FieldTypeId::BoundedString => FieldTypeId::String as u8,
+        FieldTypeId::FixedWString | FieldTypeId::BoundedWString => FieldTypeId::WString as u8,
+        base_type => base_type as u8,
+    }
+}
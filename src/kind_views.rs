@@ -0,0 +1,102 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Kind-partitioned views of the types: '@ros2_msgs/**', '@ros2_srvs/**' and '@ros2_actions/**',
+// where the types of a kind are named "<package>/<name>" (e.g. '@ros2_msgs/std_msgs/String' for
+// 'std_msgs/msg/String'), letting the clients wildcard within a kind.
+use zenoh::key_expr::{keyexpr, OwnedKeyExpr};
+
+use crate::type_info::{TypeInfo, TypeKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KindView {
+    Msgs,
+    Srvs,
+    Actions,
+}
+
+impl KindView {
+    pub(crate) const ALL: [KindView; 3] = [KindView::Msgs, KindView::Srvs, KindView::Actions];
+
+    // The root of the view key space
+    pub(crate) fn prefix(&self) -> &'static keyexpr {
+        let prefix = match self {
+            KindView::Msgs => "@ros2_msgs",
+            KindView::Srvs => "@ros2_srvs",
+            KindView::Actions => "@ros2_actions",
+        };
+        keyexpr::new(prefix).expect("valid key expression")
+    }
+
+    fn kind(&self) -> TypeKind {
+        match self {
+            KindView::Msgs => TypeKind::MSG,
+            KindView::Srvs => TypeKind::SRV,
+            KindView::Actions => TypeKind::ACTION,
+        }
+    }
+
+    // Key expression of the Queryable on the view (e.g. '@ros2_msgs/**')
+    pub(crate) fn queryable_ke(&self) -> OwnedKeyExpr {
+        self.prefix() / keyexpr::new("**").expect("valid key expression")
+    }
+
+    // Parse a queried key expression (without namespace), returning the selection of the types
+    // in the view (e.g. "std_msgs/*" for '@ros2_msgs/std_msgs/*')
+    pub(crate) fn parse<'a>(&self, ke: &'a keyexpr) -> Option<&'a keyexpr> {
+        let selection = ke
+            .as_str()
+            .strip_prefix(self.prefix().as_str())?
+            .strip_prefix('/')?;
+        keyexpr::new(selection).ok()
+    }
+
+    // Key expression of the types of the registry possibly included in a selection of the view,
+    // to be refined with includes()
+    pub(crate) fn types_ke(&self, selection: &keyexpr) -> OwnedKeyExpr {
+        let kind = self.kind().as_ref().to_lowercase();
+        // a "<package>/<name>" selection maps to "<package>/<kind>/<name>", with '**' reduced to
+        // '*' as the views names have exactly 2 chunks
+        let chunks: Vec<&str> = selection
+            .as_str()
+            .split('/')
+            .map(|c| if c == "**" { "*" } else { c })
+            .collect();
+        let ke = match chunks.as_slice() {
+            [package, name] => format!("{package}/{kind}/{name}"),
+            _ => format!("*/{kind}/*"),
+        };
+        OwnedKeyExpr::autocanonize(ke).unwrap_or_else(|_| {
+            OwnedKeyExpr::autocanonize(format!("*/{kind}/*")).expect("valid key expression")
+        })
+    }
+
+    // Check if a type is included in a selection of the view
+    pub(crate) fn includes(&self, selection: &keyexpr, t: &TypeInfo) -> bool {
+        t.kind == self.kind()
+            && keyexpr::new(&view_name(t)).is_ok_and(|name| selection.includes(name))
+    }
+
+    // Key expression of a type in the view (e.g. '@ros2_msgs/std_msgs/String')
+    pub(crate) fn type_ke(&self, t: &TypeInfo) -> Result<OwnedKeyExpr, String> {
+        keyexpr::new(&view_name(t))
+            .map(|name| self.prefix() / name)
+            .map_err(|e| e.to_string())
+    }
+}
+
+// The name of a type in its view, e.g. "std_msgs/String" for "std_msgs/msg/String"
+// (escaped as a valid key expression, as the full name)
+fn view_name(t: &TypeInfo) -> String {
+    format!("{}/{}", t.package_name, t.short_name)
+}
@@ -0,0 +1,2293 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::{borrow::Cow, path::PathBuf, str::FromStr};
+
+use access_log::{AccessLog, QueryStats};
+use anyhow::{anyhow, bail};
+use futures::{
+    channel::mpsc::{unbounded, UnboundedReceiver},
+    select_biased, FutureExt, StreamExt,
+};
+use serde::Serialize;
+use stats::QueryRecord;
+use strum::{AsRefStr, EnumString, VariantNames};
+use zenoh::{
+    self,
+    bytes::{Encoding, ZBytes},
+    handlers::FifoChannelHandler,
+    internal::{plugins::PluginsManager, runtime::RuntimeBuilder},
+    key_expr::{
+        format::{kedefine, keformat},
+        keyexpr, KeyExpr, OwnedKeyExpr,
+    },
+    liveliness::LivelinessToken,
+    pubsub::Subscriber,
+    query::{Query, Queryable},
+    sample::{Sample, SampleKind},
+};
+
+mod access_log;
+mod admin;
+mod admin_space;
+mod alias;
+mod args;
+mod auth;
+mod binary_schema;
+mod bridge_schemas;
+mod canonical_json;
+mod chunking;
+mod compression;
+mod dds_xml;
+mod default_instance;
+mod definition_cache;
+mod documentation;
+mod election;
+mod epoch_token;
+mod export;
+mod field_type;
+#[cfg(feature = "foxglove")]
+mod foxglove;
+#[cfg(feature = "graphql")]
+mod graphql;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod health;
+#[cfg(feature = "http")]
+mod http;
+mod idl;
+mod interface;
+mod interner;
+mod introspection;
+mod kind_views;
+mod manifest;
+mod mcap_check;
+mod mirror;
+mod package_xml;
+mod plugin;
+mod prefetch;
+mod rate_limit;
+mod registry;
+mod reply;
+mod resolve;
+mod ros1;
+mod rosbridge;
+mod self_test;
+#[cfg(windows)]
+mod service;
+mod signing;
+mod snapshot;
+mod source_lines;
+mod stats;
+mod subjects;
+mod tarball;
+mod telemetry;
+mod type_description;
+mod type_filter;
+mod type_info;
+mod type_name;
+mod validation;
+mod virtual_types;
+
+// Key expression for the Liveliness Token assessing this types registry is up and running
+const KE_LIVELINESS_TOKEN: &str = "@ros2_types";
+
+kedefine!(
+    // Key expression pattern for the Queryable on types
+    pub(crate) keformat_ros2_types: "@ros2_types/${type_name:**}",
+    // Key expression pattern for the Queryable on environment variables
+    pub(crate) keformat_ros2_env: "@ros2_env/${env_var:*}",
+    // Key expression pattern for the Queryable validating JSON message instances against types
+    pub(crate) keformat_ros2_validate: "@ros2_validate/${type_name:**}",
+    // Key expression pattern for the Queryable reproducing the `ros2 interface` commands outputs
+    pub(crate) keformat_ros2_interface: "@ros2_interface/${command:**}",
+    // Key expression pattern for the Queryable on packages
+    pub(crate) keformat_ros2_packages: "@ros2_packages/${package:*}",
+    // Key expression pattern for the Queryable on packages manifests
+    pub(crate) keformat_ros2_package_manifest: "@ros2_packages/${package:*}/manifest",
+    // Key expression pattern for the Queryable resolving the field type tokens of definitions
+    pub(crate) keformat_ros2_resolve: "@ros2_resolve/${token:**}",
+    // Key expression pattern for the Queryable on the subjects (Kafka Schema Registry semantics)
+    pub(crate) keformat_ros2_subjects: "@ros2_subjects/${command:**}",
+    // Key expression pattern for the Queryable on administration operations
+    pub(crate) keformat_ros2_admin: "@ros2_admin/${operation:**}",
+);
+
+// Interval between the garbage collection runs of the types history
+const HISTORY_GC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Number of types parsed at each step of the background warm-up
+const WARM_UP_BATCH_SIZE: usize = 64;
+// Definitions cache size when lazy definitions are implied by a memory budget
+const DEFAULT_DEFINITIONS_CACHE_SIZE: usize = 128;
+
+// Number of replies sent for a query before yielding to other tasks
+const REPLIES_BETWEEN_YIELDS: usize = 64;
+
+// List of environment variables that can be queried via the @ros2_env/* queryable
+// If the queried variable is not in this list, an error is returned.
+const ALLOWED_ENV_VARS: &[&str] = &[
+    "ROS_DOMAIN_ID",
+    "RMW_IMPLEMENTATION",
+    "ROS_VERSION",
+    "ROS_PYTHON_VERSION",
+    "ROS_DISTRO",
+    "AMENT_PREFIX_PATH",
+];
+
+#[derive(Debug, Default, Clone, Copy, AsRefStr, EnumString, PartialEq, Eq, Hash, VariantNames)]
+#[strum(ascii_case_insensitive)]
+pub(crate) enum ReplyFormat {
+    #[default]
+    TypeDescription, // the type description in JSON
+    FullTypeDescription, // the full type description with dependencies in JSON
+    Definition,          // the original .msg/.srv/.action definition
+    Mcap,                // the type description for a MCAP schema
+    Hash,                // the type hash string
+    Path,                // the path to the original .msg/.srv/.action file
+    DefaultInstance,     // a JSON instance of the type populated with default values
+    Ros1Definition,      // the definition converted to ROS 1 (messages only)
+    Ros1Md5sum,          // the ROS 1 md5sum of the converted definition (messages only)
+    Summary,             // a JSON summary of the type information (hash, schema version...)
+    Rosbridge,           // the type details as rosapi's message_details (for roslib clients)
+    Binary,              // the type description in a compact binary encoding (for microcontrollers)
+    InterfaceShow,       // the output of `ros2 interface show` for the type
+    FastDdsXml,          // the Fast DDS XML types profile of the type and its dependencies
+    ConnextXml,          // the RTI Connext XML type representation of the type and its dependencies
+    McapSchema,          // the MCAP Schema record fields (name, encoding, base64 data) in JSON
+    Snapshot,            // the snapshot entry of the type (e.g. for a mirror registry)
+    Tar,                 // a gzip'd tar archive of the definitions (one for all the matching types)
+    History,             // the versions of the type (hashes and validity epochs) in JSON
+    Deps,                // the transitive dependencies (names, hashes and kinds) in JSON
+    Documented,          // the fields and constants with their documentation comments in JSON
+    SourceLines,         // the definition file and line of each field (recursively) in JSON
+    Introspection,       // the wire descriptor of the type, as rosidl's introspection type support
+    ExpandedIdl,         // a self-contained IDL document of the type and its dependencies
+    Pretty,              // the fields of the type and its dependencies with pretty-printed types
+}
+
+pub use plugin::Ros2TypesRegistryPlugin;
+
+// The field types of the type descriptions, with their pretty-printer (e.g. "string<=10[5]")
+pub use field_type::FieldTypeId;
+pub use type_description::FieldType;
+
+// The registry API used by the benchmarks (not a stable API)
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::{registry::Registry, type_info::TypeInfo};
+}
+
+// Return the "share" directories of the paths listed in AMENT_PREFIX_PATH
+fn get_ament_share_paths() -> Result<Vec<PathBuf>, String> {
+    match std::env::var("AMENT_PREFIX_PATH") {
+        Err(_) => Err("AMENT_PREFIX_PATH environment variable is not defined. Is your ROS environment setup ?".into()),
+        Ok(s) if s.is_empty() => Err("AMENT_PREFIX_PATH environment variable is empty. Is your ROS environment correctly setup ?".into()),
+        // paths are separated by ':' on Unix and ';' on Windows (where ':' follows drive letters)
+        Ok(ament_prefix_path) => Ok(std::env::split_paths(&ament_prefix_path)
+            .map(|path| {
+                let mut path = args::drive_root(path);
+                path.push("share");
+                path
+            })
+            .collect()),
+    }
+}
+
+// Run the standalone registry, with its own Zenoh runtime configured by the command line arguments
+pub async fn run_standalone() -> anyhow::Result<()> {
+    // parse command line arguments
+    let args = args::parse_args();
+
+    // initiate logging
+    let log_filter_handle = telemetry::init_logging(&args)?;
+
+    // Install or uninstall the Windows service and exit, or run under the Service Control Manager,
+    // if requested
+    #[cfg(windows)]
+    {
+        if args.install_service {
+            // the service runs with the same arguments, under the SCM
+            let arguments = std::env::args_os()
+                .skip(1)
+                .filter(|arg| arg != "--install-service")
+                .chain(std::iter::once("--service".into()))
+                .collect();
+            service::install(&args.service_name, arguments).map_err(|e| anyhow!(e))?;
+            tracing::info!("Service '{}' installed", args.service_name);
+            return Ok(());
+        }
+        if args.uninstall_service {
+            service::uninstall(&args.service_name).map_err(|e| anyhow!(e))?;
+            tracing::info!("Service '{}' uninstalled", args.service_name);
+            return Ok(());
+        }
+        if args.service {
+            let runtime = tokio::runtime::Handle::current();
+            let name = args.service_name.clone();
+            return tokio::task::block_in_place(|| {
+                service::run(&name, runtime, args, log_filter_handle)
+            })
+            .map_err(|e| anyhow!(e));
+        }
+    }
+
+    start(args, log_filter_handle).await
+}
+
+// Start the standalone registry, with its own Zenoh runtime
+pub(crate) async fn start(
+    args: args::Args,
+    log_filter_handle: telemetry::LogFilterHandle,
+) -> anyhow::Result<()> {
+    let config = zenoh::Config::from(&args);
+
+    // In client-only mode, a plain Session connected to an existing router
+    if args.client_only {
+        let session = zenoh::open(config)
+            .await
+            .map_err(|err| anyhow!("failed to open Zenoh session: {err}"))?;
+        tracing::info!("Zenoh session opened in client-only mode");
+        spawn_admin_space(&session, "client");
+        return run(args, session, Some(log_filter_handle)).await;
+    }
+
+    // the mode of the runtime, in the admin space key expressions
+    let whatami = config
+        .get_json("mode")
+        .ok()
+        .and_then(|mode| serde_json::from_str::<String>(&mode).ok())
+        .unwrap_or_else(|| "peer".into());
+
+    // Plugin manager with REST plugin
+    let mut plugins_manager = PluginsManager::static_plugins_only();
+    if let Ok(http_port) = config.get_json("plugins/rest/http_port") {
+        tracing::info!("REST plugin available on HTTP port {http_port}");
+        plugins_manager.declare_static_plugin::<zenoh_plugin_rest::RestPlugin, &str>("rest", true);
+    }
+
+    // Create a Zenoh Runtime with the PluginManager and a Session.
+    let mut runtime = RuntimeBuilder::new(config)
+        .plugins_manager(plugins_manager)
+        .build()
+        .await
+        .map_err(|err| anyhow!("failed to build Zenoh runtime: {err}"))?;
+    runtime
+        .start()
+        .await
+        .map_err(|err| anyhow!("failed to start Zenoh runtime: {err}"))?;
+    let session = zenoh::session::init(runtime.into())
+        .await
+        .map_err(|err| anyhow!("failed to create Zenoh session: {err}"))?;
+    spawn_admin_space(&session, &whatami);
+
+    run(args, session, Some(log_filter_handle)).await
+}
+
+// Reply the registry state on the admin space of the standalone registry
+fn spawn_admin_space(session: &zenoh::Session, whatami: &str) {
+    let session = session.clone();
+    let whatami = whatami.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = admin_space::serve(session, &whatami).await {
+            tracing::warn!("{e}");
+        }
+    });
+}
+
+// Load the types and serve them on a Zenoh session (either of the standalone registry, or of the
+// zenohd hosting the registry as a plugin, which then manages the logging)
+pub(crate) async fn run(
+    args: args::Args,
+    session: zenoh::Session,
+    log_filter_handle: Option<telemetry::LogFilterHandle>,
+) -> anyhow::Result<()> {
+    // load the key signing the replies, if configured
+    if let Some(path) = &args.signing_key {
+        let public_key = signing::init(path).map_err(|e| anyhow!(e))?;
+        tracing::info!("Replies signed with the ed25519 key {public_key} (public key, base64)");
+    }
+
+    // load the zstd dictionary for the compressed replies, if configured
+    if let Some(path) = &args.zstd_dictionary {
+        let size = compression::init(path).map_err(|e| anyhow!(e))?;
+        tracing::info!("Loaded zstd dictionary {} ({size} bytes)", path.display());
+    }
+
+    // chunk the oversized replies, if configured
+    if let Some(max_reply_size) = args.max_reply_size {
+        chunking::init(max_reply_size).map_err(|e| anyhow!(e))?;
+    }
+
+    // publish the version and configuration in the admin space
+    admin_space::init(&args);
+
+    // open the access log, if configured
+    let access_log = match &args.access_log {
+        Some(path) => Some(
+            AccessLog::open(path)
+                .map_err(|err| anyhow!("failed to open access log {}: {err}", path.display()))?,
+        ),
+        None => None,
+    };
+
+    // Create the Registry of the types of AMENT_PREFIX_PATH (mandatory without tenants), or of the
+    // imported snapshot, or of the snapshot embedded at build time, and a Registry per tenant,
+    // each served under its namespace
+    let mut registries = Vec::new();
+    let snapshot = match &args.import_snapshot {
+        Some(path) => Some(snapshot::read_snapshot(path).map_err(|e| anyhow!(e))?),
+        None => snapshot::embedded_snapshot().map_err(|e| anyhow!(e))?,
+    };
+    if args.mirror.is_some() {
+        // the mirror registry is filled by the synchronizations with the upstream one (its types
+        // can't be evicted, as not re-parsable from disk)
+        if args.memory_budget.is_some() {
+            return Err(anyhow!("--memory-budget can't be used with --mirror"));
+        }
+        registries.push(new_registry(&args, None));
+    } else if let Some(entries) = snapshot {
+        if args.memory_budget.is_some() {
+            return Err(anyhow!(
+                "--memory-budget can't be used with the types of a snapshot"
+            ));
+        }
+        let mut registry = new_registry(&args, None);
+        let count = registry.load_types_from_snapshot(entries);
+        tracing::info!("{count} types loaded from snapshot");
+        log_registry_size(&registry);
+        registries.push(registry);
+    } else {
+        match get_ament_share_paths() {
+            Ok(paths) => registries.push(load_registry(&args, None, &paths)),
+            Err(e) if args.tenant.is_empty() => return Err(anyhow!(e)),
+            Err(e) => tracing::warn!("{e} - only the tenants types are served"),
+        }
+    }
+    for tenant in &args.tenant {
+        let namespace = OwnedKeyExpr::new(tenant.name.as_str())
+            .map_err(|err| anyhow!("invalid tenant name '{}': {err}", tenant.name))?;
+        registries.push(load_registry(&args, Some(namespace), &tenant.roots));
+    }
+
+    // Add the virtual types defined in a file to each Registry, if requested
+    if let Some(path) = &args.virtual_types {
+        let virtual_types = virtual_types::read_virtual_types(path).map_err(|e| anyhow!(e))?;
+        for registry in &mut registries {
+            let count = virtual_types::load_virtual_types(registry, &virtual_types, path);
+            tracing::info!("{count} virtual types loaded from {}", path.display());
+        }
+    }
+
+    // Run the self-test of the types of each registry, print the reports and exit, if requested
+    if args.self_test {
+        let reports: Vec<self_test::SelfTestReport> =
+            registries.iter_mut().map(self_test::self_test).collect();
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        let types: usize = reports.iter().map(|r| r.types).sum();
+        let failed: usize = reports.iter().map(|r| r.failed).sum();
+        if failed > 0 {
+            bail!("The self-test of {failed} of the {types} types failed");
+        }
+        tracing::info!("The self-test of the {types} types succeeded");
+        return Ok(());
+    }
+
+    // Export the schemas bundle, the snapshot or the ros1_bridge mapping rules, train the zstd
+    // dictionary, or validate or repair an MCAP file, and exit, if requested
+    if args.export_foxglove_schemas.is_some()
+        || args.export_snapshot.is_some()
+        || args.export_ros1_bridge_mapping.is_some()
+        || args.train_zstd_dictionary.is_some()
+        || args.validate_mcap.is_some()
+        || args.repair_mcap.is_some()
+    {
+        let registry = registries
+            .iter_mut()
+            .find(|r| r.get_namespace().is_none())
+            .ok_or_else(|| anyhow!("no types to export without AMENT_PREFIX_PATH"))?;
+        if let Some(dir) = &args.export_foxglove_schemas {
+            let count = export::export_foxglove_schemas(registry, dir).map_err(|e| anyhow!(e))?;
+            tracing::info!("Exported {count} schemas to {}", dir.display());
+        }
+        if let Some(path) = &args.export_snapshot {
+            let count = snapshot::export_snapshot(registry, path).map_err(|e| anyhow!(e))?;
+            tracing::info!("Exported {count} types to snapshot {}", path.display());
+        }
+        if let Some(path) = &args.export_ros1_bridge_mapping {
+            let count = ros1::export_ros1_bridge_mapping(registry, path).map_err(|e| anyhow!(e))?;
+            tracing::info!(
+                "Exported {count} ros1_bridge mapping rules to {}",
+                path.display()
+            );
+        }
+        if let Some(path) = &args.train_zstd_dictionary {
+            let size = compression::train_dictionary(registry, path).map_err(|e| anyhow!(e))?;
+            tracing::info!("Trained zstd dictionary {} ({size} bytes)", path.display());
+        }
+        if let Some(path) = &args.repair_mcap {
+            let output = args
+                .repair_mcap_output
+                .clone()
+                .unwrap_or_else(|| path.with_extension("repaired.mcap"));
+            let repaired =
+                mcap_check::repair_mcap(registry, path, &output).map_err(|e| anyhow!(e))?;
+            tracing::info!(
+                "Repaired {} schemas of {} into {}: {repaired:?}",
+                repaired.len(),
+                path.display(),
+                output.display()
+            );
+        }
+        if let Some(path) = &args.validate_mcap {
+            let report = mcap_check::check_mcap(registry, path).map_err(|e| anyhow!(e))?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if report.mismatches > 0 {
+                bail!(
+                    "{} of the {} schemas of {} don't match the registry",
+                    report.mismatches,
+                    report.schemas,
+                    path.display()
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    // Announce the schemas of the topics routed by zenoh-bridge-ros2dds
+    if args.bridge_schemas {
+        let session = session.clone();
+        tokio::spawn(async move {
+            if let Err(e) = bridge_schemas::run(session).await {
+                tracing::error!("{e}");
+            }
+        });
+    }
+
+    // Start the HTTP server, forwarding the requests to the Queryables
+    #[cfg(feature = "http")]
+    if let Some(addr) = args.http_listen {
+        let session = session.clone();
+        tokio::spawn(async move {
+            if let Err(e) = http::run_server(session, addr).await {
+                tracing::error!("{e}");
+            }
+        });
+    }
+
+    // Start the gRPC server, forwarding the requests to the Queryables
+    #[cfg(feature = "grpc")]
+    if let Some(addr) = args.grpc_listen {
+        let session = session.clone();
+        tokio::spawn(async move {
+            if let Err(e) = grpc::run_server(session, addr).await {
+                tracing::error!("{e}");
+            }
+        });
+    }
+
+    // Start the Foxglove WebSocket server, resolving the schemas via the types Queryable
+    #[cfg(feature = "foxglove")]
+    if let Some(addr) = args.foxglove_listen {
+        let session = session.clone();
+        let proxy_data = args.foxglove_proxy_data;
+        tokio::spawn(async move {
+            if let Err(e) = foxglove::run_server(session, addr, proxy_data).await {
+                tracing::error!("{e}");
+            }
+        });
+    }
+
+    // Synchronize the mirror registry with the upstream one
+    let mut mirror_updates = None;
+    if let Some(upstream) = &args.mirror {
+        let upstream = match upstream.as_str() {
+            "" => None,
+            ns => Some(
+                OwnedKeyExpr::new(ns)
+                    .map_err(|err| anyhow!("invalid mirror namespace '{ns}': {err}"))?,
+            ),
+        };
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        mirror_updates = Some(rx);
+        let session = session.clone();
+        let token = args.mirror_token.clone();
+        let interval = std::time::Duration::from_secs(args.mirror_interval);
+        tokio::spawn(async move {
+            if let Err(e) = mirror::run(session, upstream, token, interval, tx).await {
+                tracing::error!("{e}");
+            }
+        });
+    }
+
+    // Serve all the registries, until a failure
+    futures::future::try_join_all(registries.into_iter().map(|registry| {
+        let mirror_updates = match registry.get_namespace() {
+            None => mirror_updates.take(),
+            Some(_) => None,
+        };
+        serve_registry(
+            &session,
+            registry,
+            &args,
+            access_log.as_ref(),
+            log_filter_handle.as_ref(),
+            mirror_updates,
+        )
+    }))
+    .await?;
+    Ok(())
+}
+
+// Create a Registry configured by the arguments, and load the types found in those directories
+fn load_registry<'a>(
+    args: &args::Args,
+    namespace: Option<OwnedKeyExpr>,
+    dirs: &[PathBuf],
+) -> registry::Registry<'a> {
+    let mut registry = new_registry(args, namespace);
+    for path in dirs {
+        registry.load_types_from_dir(path);
+    }
+    log_registry_size(&registry);
+    registry
+}
+
+// Rescan the directories of a Registry (e.g. after the deployment of new interface packages),
+// replacing its content with the types found. The mirror registry and the types of a snapshot
+// can't be rescanned.
+pub(crate) fn rescan_registry(
+    args: &args::Args,
+    registry: &mut registry::Registry,
+) -> Result<registry::ContentChanges, String> {
+    let namespace = registry.get_namespace().map(keyexpr::to_owned);
+    let dirs = match &namespace {
+        None if args.mirror.is_some() => {
+            return Err("The mirror registry can't be rescanned".into())
+        }
+        None if args.import_snapshot.is_some() || cfg!(feature = "embedded") => {
+            return Err("The types of a snapshot can't be rescanned".into())
+        }
+        None => get_ament_share_paths()?,
+        Some(ns) => args
+            .tenant
+            .iter()
+            .find(|tenant| tenant.name == ns.as_str())
+            .map(|tenant| tenant.roots.clone())
+            .ok_or_else(|| format!("No directories for tenant '{ns}'"))?,
+    };
+    let mut rescanned = load_registry(args, namespace, &dirs);
+    if let Some(path) = &args.virtual_types {
+        let virtual_types = virtual_types::read_virtual_types(path)?;
+        virtual_types::load_virtual_types(&mut rescanned, &virtual_types, path);
+    }
+    let changes = registry.replace_content(rescanned);
+    check_references(registry);
+    tracing::info!(
+        "Registry rescanned: {} types added, {} changed and {} removed",
+        changes.added,
+        changes.changed,
+        changes.removed
+    );
+    Ok(changes)
+}
+
+// Create an empty Registry configured by the arguments
+fn new_registry<'a>(args: &args::Args, namespace: Option<OwnedKeyExpr>) -> registry::Registry<'a> {
+    let mut registry = registry::Registry::new()
+        .with_ignore_patterns(args.ignore.clone())
+        .with_walk_options(registry::WalkOptions {
+            follow_links: !args.no_follow_links,
+            max_depth: args.max_depth,
+            same_file_system: args.same_file_system,
+        })
+        .with_mcap_dependency_order(args.mcap_dependency_order)
+        .with_cache_max_age(args.cache_max_age)
+        .with_aliases(args.alias.iter().cloned())
+        .with_denied_types(args.deny.iter().cloned())
+        .with_history_retention(registry::HistoryRetention {
+            max_versions: args.history_max_versions,
+            max_age: args.history_max_age,
+            max_memory: args.history_max_memory.map(|mb| mb * 1024 * 1024),
+        });
+    match (args.lazy_definitions, args.memory_budget) {
+        (Some(cache_size), _) => registry = registry.with_lazy_definitions(cache_size),
+        (None, Some(_)) => {
+            registry = registry.with_lazy_definitions(DEFAULT_DEFINITIONS_CACHE_SIZE)
+        }
+        (None, None) => {}
+    }
+    if let Some(memory_budget) = args.memory_budget {
+        registry = registry.with_memory_budget(memory_budget * 1024 * 1024);
+    }
+    if args.warm_up {
+        registry = registry.with_warm_up();
+    }
+    if args.strict_json {
+        registry = registry.with_strict_json();
+    }
+    if args.strict_dependencies {
+        registry = registry.with_strict_dependencies();
+    }
+    if let Some(namespace) = namespace {
+        registry = registry.with_namespace(namespace);
+    }
+    registry
+}
+
+fn log_registry_size(registry: &registry::Registry) {
+    let tenant = registry
+        .get_namespace()
+        .map(|ns| format!(" of tenant '{ns}'"))
+        .unwrap_or_default();
+    if registry.get_pending_count() > 0 {
+        tracing::info!(
+            "Total types indexed in registry{tenant}: {} - parsing them in background",
+            registry.get_pending_count()
+        );
+    } else {
+        tracing::info!("Total types in registry{tenant}: {}", registry.get_size());
+    }
+}
+
+// Declare the Queryables on a Registry (under its namespace, if any) and reply to their queries
+async fn serve_registry(
+    session: &zenoh::Session,
+    mut registry: registry::Registry<'_>,
+    args: &args::Args,
+    access_log: Option<&AccessLog>,
+    log_filter_handle: Option<&telemetry::LogFilterHandle>,
+    mirror_updates: Option<UnboundedReceiver<mirror::MirrorUpdate>>,
+) -> anyhow::Result<()> {
+    // without mirroring, the updates stream is terminated (and thus ignored by select_biased!)
+    let mut mirror_updates = mirror_updates.unwrap_or_else(|| unbounded().1);
+    let namespace = registry.get_namespace().map(keyexpr::to_owned);
+    let namespace = namespace.as_deref();
+
+    // Declare Queryable for types
+    let ros2_types_queryable_ke = keformat!(keformat_ros2_types::formatter(), type_name = "**")
+        .map_err(|err| {
+            anyhow!(
+                "Internal error that shouldn't happen, formating ros2_types_queryable_ke: {err}"
+            )
+        })?;
+    let ros2_types_queryable_ke = namespaced(namespace, ros2_types_queryable_ke);
+    tracing::debug!("Declaring Queryable on '{ros2_types_queryable_ke}'");
+    let ros2_types_queryable = session
+        .declare_queryable(ros2_types_queryable_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for types: {err}"))?;
+
+    // Declare Queryables for the kind-partitioned views of the types
+    let mut ros2_kind_views_queryables = Vec::new();
+    for view in kind_views::KindView::ALL {
+        let view_queryable_ke = namespaced(namespace, view.queryable_ke());
+        tracing::debug!("Declaring Queryable on '{view_queryable_ke}'");
+        let queryable = session
+            .declare_queryable(view_queryable_ke)
+            .await
+            .map_err(|err| anyhow!("failed to declare queryable for {view:?} view: {err}"))?;
+        ros2_kind_views_queryables.push((view, queryable));
+    }
+
+    // Declare Queryable for environment variables
+    let ros2_env_queryable_ke =
+        keformat!(keformat_ros2_env::formatter(), env_var = "*").map_err(|err| {
+            anyhow!("Internal error that shouldn't happen, formating ros2_env_queryable_ke: {err}")
+        })?;
+    let ros2_env_queryable_ke = namespaced(namespace, ros2_env_queryable_ke);
+    tracing::debug!("Declaring Queryable on '{ros2_env_queryable_ke}'");
+    let ros2_env_queryable = session
+        .declare_queryable(ros2_env_queryable_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for environment variables: {err}"))?;
+
+    // Declare Queryable for validation of JSON message instances
+    let ros2_validate_queryable_ke =
+        keformat!(keformat_ros2_validate::formatter(), type_name = "**").map_err(|err| {
+            anyhow!(
+                "Internal error that shouldn't happen, formating ros2_validate_queryable_ke: {err}"
+            )
+        })?;
+    let ros2_validate_queryable_ke = namespaced(namespace, ros2_validate_queryable_ke);
+    tracing::debug!("Declaring Queryable on '{ros2_validate_queryable_ke}'");
+    let ros2_validate_queryable = session
+        .declare_queryable(ros2_validate_queryable_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for validation: {err}"))?;
+
+    // Declare Queryable for packages
+    let ros2_packages_queryable_ke = keformat!(keformat_ros2_packages::formatter(), package = "*")
+        .map_err(|err| {
+            anyhow!(
+                "Internal error that shouldn't happen, formating ros2_packages_queryable_ke: {err}"
+            )
+        })?;
+    let ros2_packages_queryable_ke = namespaced(namespace, ros2_packages_queryable_ke);
+    tracing::debug!("Declaring Queryable on '{ros2_packages_queryable_ke}'");
+    let ros2_packages_queryable = session
+        .declare_queryable(ros2_packages_queryable_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for packages: {err}"))?;
+
+    // Declare Queryable for packages manifests
+    let ros2_package_manifest_queryable_ke =
+        keformat!(keformat_ros2_package_manifest::formatter(), package = "*").map_err(|err| {
+            anyhow!(
+                "Internal error that shouldn't happen, formating ros2_package_manifest_queryable_ke: {err}"
+            )
+        })?;
+    let ros2_package_manifest_queryable_ke =
+        namespaced(namespace, ros2_package_manifest_queryable_ke);
+    tracing::debug!("Declaring Queryable on '{ros2_package_manifest_queryable_ke}'");
+    let ros2_package_manifest_queryable = session
+        .declare_queryable(ros2_package_manifest_queryable_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for packages manifests: {err}"))?;
+
+    // Declare Queryable for the `ros2 interface` commands outputs
+    let ros2_interface_queryable_ke =
+        keformat!(keformat_ros2_interface::formatter(), command = "**").map_err(|err| {
+            anyhow!(
+                "Internal error that shouldn't happen, formating ros2_interface_queryable_ke: {err}"
+            )
+        })?;
+    let ros2_interface_queryable_ke = namespaced(namespace, ros2_interface_queryable_ke);
+    tracing::debug!("Declaring Queryable on '{ros2_interface_queryable_ke}'");
+    let ros2_interface_queryable = session
+        .declare_queryable(ros2_interface_queryable_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for interface commands: {err}"))?;
+
+    // Declare Queryable for the resolution of field type tokens
+    let ros2_resolve_queryable_ke = keformat!(keformat_ros2_resolve::formatter(), token = "**")
+        .map_err(|err| {
+            anyhow!(
+                "Internal error that shouldn't happen, formating ros2_resolve_queryable_ke: {err}"
+            )
+        })?;
+    let ros2_resolve_queryable_ke = namespaced(namespace, ros2_resolve_queryable_ke);
+    tracing::debug!("Declaring Queryable on '{ros2_resolve_queryable_ke}'");
+    let ros2_resolve_queryable = session
+        .declare_queryable(ros2_resolve_queryable_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for types resolution: {err}"))?;
+
+    // Declare Queryable for the subjects
+    let ros2_subjects_queryable_ke = keformat!(keformat_ros2_subjects::formatter(), command = "**")
+        .map_err(|err| {
+            anyhow!(
+                "Internal error that shouldn't happen, formating ros2_subjects_queryable_ke: {err}"
+            )
+        })?;
+    let ros2_subjects_queryable_ke = namespaced(namespace, ros2_subjects_queryable_ke);
+    tracing::debug!("Declaring Queryable on '{ros2_subjects_queryable_ke}'");
+    let ros2_subjects_queryable = session
+        .declare_queryable(ros2_subjects_queryable_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for subjects: {err}"))?;
+
+    // Declare Queryable for administration operations
+    let ros2_admin_queryable_ke = keformat!(keformat_ros2_admin::formatter(), operation = "**")
+        .map_err(|err| {
+            anyhow!(
+                "Internal error that shouldn't happen, formating ros2_admin_queryable_ke: {err}"
+            )
+        })?;
+    let ros2_admin_queryable_ke = namespaced(namespace, ros2_admin_queryable_ke);
+    tracing::debug!("Declaring Queryable on '{ros2_admin_queryable_ke}'");
+    let ros2_admin_queryable = session
+        .declare_queryable(ros2_admin_queryable_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for administration: {err}"))?;
+
+    // Declare Queryable for the inventory manifest
+    let manifest_ke = namespaced(
+        namespace,
+        keyexpr::new(manifest::KE_MANIFEST).expect("valid key expression"),
+    );
+    tracing::debug!("Declaring Queryable on '{manifest_ke}'");
+    let ros2_manifest_queryable = session
+        .declare_queryable(manifest_ke)
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for the manifest: {err}"))?;
+
+    // Declare Queryable and Publisher for the health status
+    let health_ke = namespaced(
+        namespace,
+        keyexpr::new(health::KE_HEALTH).expect("valid key expression"),
+    );
+    tracing::debug!("Declaring Queryable and Publisher on '{health_ke}'");
+    let ros2_health_queryable = session
+        .declare_queryable(health_ke.clone())
+        .await
+        .map_err(|err| anyhow!("failed to declare queryable for health status: {err}"))?;
+    let health_publisher = session
+        .declare_publisher(health_ke)
+        .encoding(Encoding::APPLICATION_JSON)
+        .await
+        .map_err(|err| anyhow!("failed to declare publisher for health status: {err}"))?;
+
+    // Declare the Liveliness Token, unless some types reference types not found in the registry
+    // with --strict-dependencies (then declared once those are resolved, e.g. after a rescan)
+    check_references(&mut registry);
+    if !registry.is_resolved() {
+        tracing::warn!("Not declared ready until the dangling references are resolved");
+    }
+    let mut liveliness_token = None;
+    update_liveliness_token(session, namespace, &registry, &mut liveliness_token)
+        .await
+        .map_err(|e| anyhow!(e))?;
+
+    // Declare the Liveliness Token of the epoch of the content, re-declared on each change
+    let mut epoch_token =
+        epoch_token::EpochToken::declare(session, namespace, registry.get_cache_control().epoch)
+            .await
+            .map_err(|e| anyhow!(e))?;
+
+    // Take part in the election among the redundant registries, if configured
+    let (mut election, election_subscriber) = if args.election {
+        let (mut election, subscriber) = election::Election::join(session, namespace)
+            .await
+            .map_err(|e| anyhow!(e))?;
+        election
+            .update_candidacy(session, health::HealthStatus::of(&registry).is_ready())
+            .await
+            .map_err(|e| anyhow!(e))?;
+        (Some(election), Some(subscriber))
+    } else {
+        (None, None)
+    };
+
+    let mut statistics = stats::Statistics::new(args.slow_query_threshold);
+    let mut rate_limiter = rate_limit::RateLimiter::new(
+        args.rate_limit,
+        args.rate_limit_burst,
+        session.zid().to_string(),
+        args.admin_token.clone(),
+    );
+    let mut history_gc_interval = tokio::time::interval(HISTORY_GC_INTERVAL);
+    let mut admin_space_interval = tokio::time::interval(admin_space::REFRESH_INTERVAL);
+
+    // Prefetch the types in use on the ROS graph, if configured
+    let prefetch_subscriber = if args.prefetch {
+        Some(prefetch::subscribe(session).await.map_err(|e| anyhow!(e))?)
+    } else {
+        None
+    };
+    let render_options = reply::RenderOptions {
+        strict_mcap: args.strict_mcap,
+        ..Default::default()
+    };
+
+    // Rescan the directories on SIGHUP (or on the ParamChange control of the Windows service),
+    // unless hosted by zenohd (managing the logging and the signals of its process)
+    let mut hangup_signal = match log_filter_handle {
+        Some(_) => hangup_signal(),
+        None => None,
+    };
+
+    tracing::info!("Ready! Listening for queries...");
+    loop {
+        // While some types are not parsed yet, a warm-up step is run when no query is pending
+        let warming_up = registry.get_pending_count() > 0;
+        let mut warm_up_tick = Box::pin(
+            async move {
+                if warming_up {
+                    tokio::task::yield_now().await
+                } else {
+                    futures::future::pending::<()>().await
+                }
+            }
+            .fuse(),
+        );
+
+        // Wait a query (with priority over the warm-up)
+        select_biased!(
+            query = ros2_types_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if election::should_reply(election.as_ref(), &q) && rate_limiter.admit(&q).await && admin::admit_serving(&registry, &q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = handle_ros2_types_query(q, None, &mut registry, &mut statistics, &render_options).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_types_queryable was closed");
+                }
+                registry.enforce_memory_budget();
+            },
+            query = next_kind_view_query(&ros2_kind_views_queryables).fuse() => {
+                if let Some((view, q)) = query {
+                    if election::should_reply(election.as_ref(), &q) && rate_limiter.admit(&q).await && admin::admit_serving(&registry, &q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = handle_ros2_types_query(q, Some(view), &mut registry, &mut statistics, &render_options).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but a kind view queryable was closed");
+                }
+                registry.enforce_memory_budget();
+            },
+            query = ros2_env_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if rate_limiter.admit(&q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = handle_ros2_env_query(q, namespace, args.env_token.as_deref()).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_env_queryable was closed");
+                }
+            },
+            query = ros2_validate_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if election::should_reply(election.as_ref(), &q) && rate_limiter.admit(&q).await && admin::admit_serving(&registry, &q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = handle_ros2_validate_query(q, &mut registry).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_validate_queryable was closed");
+                }
+                registry.enforce_memory_budget();
+            },
+            query = ros2_packages_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if election::should_reply(election.as_ref(), &q) && rate_limiter.admit(&q).await && admin::admit_serving(&registry, &q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = handle_ros2_packages_query(q, &mut registry).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_packages_queryable was closed");
+                }
+            },
+            query = ros2_package_manifest_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if election::should_reply(election.as_ref(), &q) && rate_limiter.admit(&q).await && admin::admit_serving(&registry, &q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = handle_ros2_package_manifest_query(q, &registry).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_package_manifest_queryable was closed");
+                }
+            },
+            query = ros2_interface_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if election::should_reply(election.as_ref(), &q) && rate_limiter.admit(&q).await && admin::admit_serving(&registry, &q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = interface::handle_ros2_interface_query(q, &mut registry).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_interface_queryable was closed");
+                }
+                registry.enforce_memory_budget();
+            },
+            query = ros2_resolve_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if election::should_reply(election.as_ref(), &q) && rate_limiter.admit(&q).await && admin::admit_serving(&registry, &q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = resolve::handle_ros2_resolve_query(q, &mut registry).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_resolve_queryable was closed");
+                }
+                registry.enforce_memory_budget();
+            },
+            query = ros2_subjects_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if election::should_reply(election.as_ref(), &q) && rate_limiter.admit(&q).await && admin::admit_serving(&registry, &q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = subjects::handle_ros2_subjects_query(q, &mut registry, args.subject_compatibility).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_subjects_queryable was closed");
+                }
+                registry.enforce_memory_budget();
+            },
+            query = ros2_admin_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if rate_limiter.admit(&q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = admin::handle_ros2_admin_query(q, &mut registry, &mut statistics, log_filter_handle, args).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_admin_queryable was closed");
+                }
+                registry.enforce_memory_budget();
+            },
+            query = ros2_manifest_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if rate_limiter.admit(&q).await && admin::admit_serving(&registry, &q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = manifest::handle_ros2_manifest_query(q, &mut registry).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_manifest_queryable was closed");
+                }
+                registry.enforce_memory_budget();
+            },
+            query = ros2_health_queryable.recv_async() => {
+                if let Ok(q) = query {
+                    if rate_limiter.admit(&q).await {
+                        let record = QueryRecord::start(&q, access_log);
+                        let stats = health::handle_ros2_health_query(q, &registry).await;
+                        record.finish(stats, &mut statistics, access_log);
+                    }
+                } else {
+                    tracing::error!("Query recceived but ros2_health_queryable was closed");
+                }
+            },
+            update = mirror_updates.select_next_some() => {
+                for type_name in update
+                    .removed
+                    .iter()
+                    .chain(update.added.iter().map(|entry| &entry.type_name))
+                {
+                    registry.remove_type(type_name);
+                }
+                registry.load_types_from_snapshot(update.added);
+                registry.collect_history_garbage();
+                check_references(&mut registry);
+            },
+            sample = next_sample(election_subscriber.as_ref()).fuse() => {
+                if let Some(election) = election.as_mut() {
+                    election.handle_sample(sample);
+                }
+            },
+            sample = next_sample(prefetch_subscriber.as_ref()).fuse() => {
+                if sample.kind() == SampleKind::Put {
+                    if let Some(type_name) = prefetch::parse_token(sample.key_expr().as_str()) {
+                        registry.prefetch(&type_name);
+                    }
+                }
+            },
+            _ = history_gc_interval.tick().fuse() => {
+                registry.collect_history_garbage();
+            },
+            _ = admin_space_interval.tick().fuse() => {
+                admin_space::update(&registry, &statistics);
+            },
+            _ = next_hangup(hangup_signal.as_mut()).fuse() => {
+                tracing::info!("Reload requested - rescanning the registry");
+                if let Err(e) = rescan_registry(args, &mut registry) {
+                    tracing::warn!("{e}");
+                }
+                registry.collect_history_garbage();
+                registry.enforce_memory_budget();
+            },
+            _ = warm_up_tick => {
+                registry.warm_up_step(WARM_UP_BATCH_SIZE);
+                registry.enforce_memory_budget();
+                let status = health::HealthStatus::of(&registry);
+                if status.pending == 0 {
+                    tracing::info!("Warm-up completed - total types in registry: {}", status.loaded);
+                    check_references(&mut registry);
+                }
+                match status.to_json() {
+                    Ok(json) => health_publisher.put(json).await.unwrap_or_else(|e| {
+                        tracing::warn!("Error publishing health status: {e}")
+                    }),
+                    Err(e) => tracing::warn!("{e}"),
+                }
+            },
+        );
+
+        // Declare or undeclare the Liveliness Token, if the references were resolved or broken
+        if let Err(e) =
+            update_liveliness_token(session, namespace, &registry, &mut liveliness_token).await
+        {
+            tracing::warn!("{e}");
+        }
+
+        // Withdraw from the election while paused or not ready, for the standbys to reply
+        if let Some(election) = election.as_mut() {
+            if let Err(e) = election
+                .update_candidacy(session, health::HealthStatus::of(&registry).is_ready())
+                .await
+            {
+                tracing::warn!("{e}");
+            }
+        }
+
+        // Signal the content changes (e.g. a mirror synchronization) to the watchers
+        epoch_token
+            .update(session, namespace, registry.get_cache_control().epoch)
+            .await;
+    }
+}
+
+// Check the references of the types of a Registry to other types, warning about the dangling ones
+fn check_references(registry: &mut registry::Registry) {
+    let count = registry.check_references();
+    if count > 0 {
+        tracing::warn!(
+            "{count} references to types not found in the registry (see \
+            @ros2_admin/diagnostics/dangling_references)"
+        );
+    }
+}
+
+// Declare the Liveliness Token of a Registry when it's ready, and undeclare it otherwise
+async fn update_liveliness_token(
+    session: &zenoh::Session,
+    namespace: Option<&keyexpr>,
+    registry: &registry::Registry<'_>,
+    token: &mut Option<LivelinessToken>,
+) -> Result<(), String> {
+    match (registry.is_resolved(), token.is_some()) {
+        (true, false) => {
+            let liveliness_token = session
+                .liveliness()
+                .declare_token(namespaced(
+                    namespace,
+                    keyexpr::new(KE_LIVELINESS_TOKEN).expect("valid key expression"),
+                ))
+                .await
+                .map_err(|err| format!("Failed to create Liveliness Token: {err}"))?;
+            *token = Some(liveliness_token);
+        }
+        (false, true) => {
+            tracing::warn!("Liveliness Token undeclared: dangling references to resolve");
+            *token = None;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Wait for the next sample of an optional subscriber (never returning if None or closed)
+async fn next_sample(subscriber: Option<&Subscriber<FifoChannelHandler<Sample>>>) -> Sample {
+    match subscriber {
+        Some(subscriber) => match subscriber.recv_async().await {
+            Ok(sample) => sample,
+            Err(_) => futures::future::pending().await,
+        },
+        None => futures::future::pending().await,
+    }
+}
+
+// The stream of the SIGHUP signals on Unix, or of the ParamChange controls of the Service Control
+// Manager on Windows
+#[cfg(unix)]
+type HangupSignal = tokio::signal::unix::Signal;
+#[cfg(windows)]
+type HangupSignal = UnboundedReceiver<()>;
+#[cfg(not(any(unix, windows)))]
+type HangupSignal = ();
+
+// Listen to the SIGHUP signals (or the ParamChange controls), if supported
+fn hangup_signal() -> Option<HangupSignal> {
+    #[cfg(unix)]
+    {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .map_err(|e| tracing::warn!("Failed to listen to SIGHUP: {e}"))
+            .ok()
+    }
+    #[cfg(windows)]
+    {
+        Some(service::reload_requests())
+    }
+    #[cfg(not(any(unix, windows)))]
+    None
+}
+
+// Wait for the next SIGHUP signal (or ParamChange control), never returning if not listened
+#[cfg_attr(not(any(unix, windows)), allow(unused_variables))]
+async fn next_hangup(signal: Option<&mut HangupSignal>) {
+    #[cfg(unix)]
+    if let Some(signal) = signal {
+        if signal.recv().await.is_some() {
+            return;
+        }
+    }
+    #[cfg(windows)]
+    if let Some(signal) = signal {
+        if signal.next().await.is_some() {
+            return;
+        }
+    }
+    futures::future::pending().await
+}
+
+// Wait for the next query on one of the kind-partitioned views of the types
+async fn next_kind_view_query(
+    queryables: &[(kind_views::KindView, Queryable<FifoChannelHandler<Query>>)],
+) -> Option<(kind_views::KindView, Query)> {
+    let (query, index, _) =
+        futures::future::select_all(queryables.iter().map(|(_, q)| Box::pin(q.recv_async()))).await;
+    query.ok().map(|q| (queryables[index].0, q))
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+async fn handle_ros2_types_query(
+    query: Query,
+    view: Option<kind_views::KindView>,
+    registry: &mut registry::Registry<'_>,
+    statistics: &mut stats::Statistics,
+    render_options: &reply::RenderOptions,
+) -> QueryStats {
+    let mut stats = QueryStats::default();
+    tracing::debug!("Received query: {}", query.key_expr());
+    // the selection of the types, either on '@ros2_types/**' or on a kind-partitioned view
+    // (e.g. '@ros2_msgs/**'), converted to the key expression of the types in the registry
+    let (type_name, selection) = match view {
+        None => match keformat_ros2_types::parse(strip_namespace(query.key_expr())) {
+            Ok(ke) => (ke.type_name().map(ToOwned::to_owned), None),
+            Err(_) => {
+                tracing::error!(
+                    "Received a query on '{}' but it doesn't match the '@ros2_types/**' queryable!",
+                    query.key_expr()
+                );
+                return stats;
+            }
+        },
+        Some(view) => match view.parse(strip_namespace(query.key_expr())) {
+            Some(selection) => (Some(view.types_ke(selection)), Some(selection)),
+            None => {
+                tracing::error!(
+                    "Received a query on '{}' but it doesn't match the '{}' queryable!",
+                    query.key_expr(),
+                    view.queryable_ke()
+                );
+                return stats;
+            }
+        },
+    };
+
+    // a query on an alias (see --alias) is served with the target types, replied under the alias
+    let alias = match (view, type_name.as_deref()) {
+        (None, Some(type_name)) => registry.get_alias(type_name),
+        _ => None,
+    };
+    let (alias, type_name) = match alias {
+        Some((alias, target)) => (Some(alias), Some(target)),
+        None => (None, type_name),
+    };
+
+    // in warm-up or bounded-memory mode, parse the matching types which are not parsed yet
+    // (or were evicted)
+    match (type_name.as_deref(), query.parameters().get("hash")) {
+        // the hash of a type still pending is unknown until it's parsed
+        (Some(type_name), Some(hash)) => {
+            if !registry.load_pending_by_hash(hash) {
+                registry.load_pending_matching(type_name);
+            }
+        }
+        (Some(type_name), None) => registry.load_pending_matching(type_name),
+        (None, _) => {}
+    }
+    let registry = &*registry;
+    let cache_control = registry.get_cache_control();
+
+    let format = match query.parameters().get("format") {
+        Some(f) => match ReplyFormat::from_str(f) {
+            Ok(fmt) => fmt,
+            Err(_) => {
+                send_reply_err_details(
+                    &query,
+                    ErrorCode::InvalidParameter,
+                    format!(
+                        "Unknown format '{f}' - accepted values are: {:?}",
+                        ReplyFormat::VARIANTS
+                    ),
+                    Some(ErrorDetails::invalid_value(
+                        "format",
+                        f,
+                        ReplyFormat::VARIANTS,
+                    )),
+                )
+                .await;
+                return stats;
+            }
+        },
+        None => ReplyFormat::default(),
+    };
+    let renderer = format.renderer();
+
+    // the 'strict' parameter (without value or set to true/false) overrides the configured mode
+    let mut render_options = *render_options;
+    // the 'json' parameter (without value) adds the JSON type descriptions to the tar archives
+    render_options.tar_json = query.parameters().contains_key("json");
+    // the 'include_type_sources' parameter (without value or set to true) adds the sources of the
+    // type and its dependencies to the full type descriptions
+    render_options.type_sources = query
+        .parameters()
+        .get("include_type_sources")
+        .is_some_and(|v| v.is_empty() || v == "true");
+    // the 'ros_msg' parameter (without value or set to true) renders the type descriptions with
+    // the exact shape of the type_description_interfaces messages
+    render_options.ros_msg = query
+        .parameters()
+        .get("ros_msg")
+        .is_some_and(|v| v.is_empty() || v == "true");
+    // the 'canonical' parameter (without value or set to true) renders the JSON formats as
+    // canonical JSON (RFC 8785), to be hashed or compared byte-for-byte
+    render_options.canonical = query
+        .parameters()
+        .get("canonical")
+        .is_some_and(|v| v.is_empty() || v == "true");
+    // the 'typed_defaults' parameter (without value or set to true) renders the default values of
+    // the fields in the type descriptions as typed JSON values (numbers, booleans, arrays...)
+    render_options.typed_defaults = query
+        .parameters()
+        .get("typed_defaults")
+        .is_some_and(|v| v.is_empty() || v == "true");
+    if let Some(strict) = query.parameters().get("strict") {
+        match strict {
+            "" => render_options.strict_mcap = true,
+            s => match s.parse::<bool>() {
+                Ok(strict) => render_options.strict_mcap = strict,
+                Err(_) => {
+                    send_reply_err_details(
+                        &query,
+                        ErrorCode::InvalidParameter,
+                        format!("Invalid 'strict' parameter '{s}' - expected true or false"),
+                        Some(ErrorDetails::invalid_value("strict", s, ["true", "false"])),
+                    )
+                    .await;
+                    return stats;
+                }
+            },
+        }
+    }
+
+    // the 'compress' parameter requests the payloads compressed with the zstd dictionary
+    let compression = match query.parameters().get("compress") {
+        None => None,
+        Some(compression::ZSTD_DICT) if compression::dictionary().is_some() => {
+            Some(compression::ZSTD_DICT)
+        }
+        Some(c) => {
+            let allowed_values = compression::dictionary().map(|_| compression::ZSTD_DICT);
+            send_reply_err_details(
+                &query,
+                ErrorCode::InvalidParameter,
+                format!(
+                    "Unsupported 'compress' parameter '{c}' - accepted values are: [\"{}\"] (with a dictionary configured)",
+                    compression::ZSTD_DICT
+                ),
+                Some(ErrorDetails::invalid_value("compress", c, allowed_values)),
+            )
+            .await;
+            return stats;
+        }
+    };
+
+    // an advanced filter of the types can be sent as a JSON document in the query payload
+    let filter = match query.payload().map(|p| p.try_to_string()) {
+        Some(Ok(s)) if s.trim().is_empty() => None,
+        Some(Ok(s)) => match type_filter::TypeFilter::parse(&s) {
+            Ok(filter) => Some(filter),
+            Err(e) => {
+                send_reply_err(&query, ErrorCode::InvalidPayload, e).await;
+                return stats;
+            }
+        },
+        Some(Err(e)) => {
+            send_reply_err(
+                &query,
+                ErrorCode::InvalidPayload,
+                format!("Query payload is not a valid UTF-8 string: {e}"),
+            )
+            .await;
+            return stats;
+        }
+        None => None,
+    };
+
+    // the 'since' parameter restricts the reply to the types added or changed after this epoch
+    // (as replied in the attachments), for an incremental refresh of the clients caches
+    let since = match query.parameters().get("since").map(str::parse::<u64>) {
+        Some(Ok(since)) => Some(since),
+        Some(Err(e)) => {
+            send_reply_err_details(
+                &query,
+                ErrorCode::InvalidParameter,
+                format!("Invalid 'since' parameter: {e}"),
+                Some(ErrorDetails {
+                    parameter: Some("since"),
+                    value: query.parameters().get("since").map(ToOwned::to_owned),
+                    ..Default::default()
+                }),
+            )
+            .await;
+            return stats;
+        }
+        None => None,
+    };
+
+    // the 'at' parameter requests the versions of the types which were current at this epoch
+    let at = match query.parameters().get("at").map(str::parse::<u64>) {
+        Some(Ok(at)) => Some(at),
+        Some(Err(e)) => {
+            send_reply_err_details(
+                &query,
+                ErrorCode::InvalidParameter,
+                format!("Invalid 'at' parameter: {e}"),
+                Some(ErrorDetails {
+                    parameter: Some("at"),
+                    value: query.parameters().get("at").map(ToOwned::to_owned),
+                    ..Default::default()
+                }),
+            )
+            .await;
+            return stats;
+        }
+        None => None,
+    };
+
+    // the 'expect_hash' parameter makes the registry reply an error instead of the content of
+    // a type with another hash, for the clients to verify and fetch a type atomically
+    let expect_hash = query.parameters().get("expect_hash");
+
+    // the 'all_versions' parameter (without value or set to true) adds the overlaid versions of
+    // the types (see Registry::iter_overlaid) to the replies, except to the tar archives
+    let all_versions = format != ReplyFormat::Tar
+        && query
+            .parameters()
+            .get("all_versions")
+            .is_some_and(|v| v.is_empty() || v == "true");
+
+    // the content hashes already known by the client (several ones separated by '|' for wildcard
+    // queries), for which a "not modified" reply is sent instead of the content
+    let if_none_match: Vec<&str> = query
+        .parameters()
+        .get("if_none_match")
+        .map(|etags| etags.split('|').collect())
+        .unwrap_or_default();
+
+    if let Some(type_name) = type_name.as_deref() {
+        // if a 'hash' parameter is set, look for the type with this hash
+        // otherwise, iterate lazily over the matching types, to not build a huge list
+        // for wildcard queries such as '@ros2_types/**'
+        // with an 'at' parameter, look for the versions which were current at this epoch
+        let types: Box<dyn Iterator<Item = &type_info::TypeInfo> + Send + '_> =
+            match (at, query.parameters().get("hash")) {
+                (Some(at), hash) => Box::new(
+                    registry
+                        .get_types_at(type_name, at)
+                        .into_iter()
+                        .filter(move |t| hash.map_or(true, |hash| t.type_hash == hash)),
+                ),
+                (None, Some(hash)) => Box::new(
+                    registry
+                        .get_type_by_hash(hash)
+                        .filter(|t| type_name.includes(&t.full_name))
+                        .into_iter(),
+                ),
+                (None, None) if all_versions => Box::new(
+                    registry
+                        .iter_types(type_name)
+                        .chain(registry.iter_overlaid(type_name)),
+                ),
+                (None, None) => Box::new(registry.iter_types(type_name)),
+            };
+        let types = types
+            .filter(|t| since.map_or(true, |since| registry.get_type_epoch(t) > since))
+            .filter(|t| filter.as_ref().map_or(true, |f| f.matches(t)))
+            .filter(|t| match (view, selection) {
+                (Some(view), Some(selection)) => view.includes(selection, t),
+                _ => true,
+            });
+
+        // a single tar archive for all the types matching a wildcard query
+        if format == ReplyFormat::Tar && type_name.is_wild() {
+            // the types not served are still counted, but not archived
+            let (types, denied): (Vec<&type_info::TypeInfo>, Vec<_>) =
+                types.partition(|t| registry.is_served(t));
+            if let Some(t) = types
+                .iter()
+                .find(|t| expect_hash.is_some_and(|hash| t.type_hash != hash))
+            {
+                send_hash_mismatch(&query, t, expect_hash.unwrap_or_default()).await;
+                return stats;
+            }
+            for t in types.iter().chain(&denied) {
+                statistics.record_type_query(t.get_type_name(), format);
+            }
+            stats.matched = types.len() + denied.len();
+            for t in denied {
+                send_not_served(&query, t).await;
+            }
+            match tarball::build_tarball(registry, &types, render_options.tar_json) {
+                Ok(payload) => {
+                    let etag = content_hash(&payload);
+                    let reply_ke = query_reply_ke(&query, registry.get_namespace());
+                    stats.bytes_sent += if if_none_match.contains(&etag.as_str()) {
+                        send_not_modified(&query, reply_ke, &etag, &cache_control).await
+                    } else {
+                        send_tagged_reply(
+                            &query,
+                            reply_ke,
+                            payload,
+                            renderer.encoding(),
+                            &etag,
+                            &cache_control,
+                            &[],
+                        )
+                        .await
+                    }
+                }
+                Err(e) => send_reply_err(&query, ErrorCode::RenderingFailed, e).await,
+            }
+            return stats;
+        }
+
+        for type_info in types {
+            stats.matched += 1;
+            // regularly yield to not monopolize the runtime while replying to a huge match
+            if stats.matched % REPLIES_BETWEEN_YIELDS == 0 {
+                tokio::task::yield_now().await;
+            }
+            let reply_ke = match view.map_or_else(
+                || {
+                    let reply_name = match &alias {
+                        Some(alias) => alias.alias_name(&type_info.full_name),
+                        None => type_info.full_name.clone(),
+                    };
+                    keformat!(keformat_ros2_types::formatter(), type_name = &reply_name)
+                        .map_err(|e| e.to_string())
+                },
+                |view| view.type_ke(type_info),
+            ) {
+                Ok(reply_ke) => namespaced(registry.get_namespace(), reply_ke),
+                Err(e) => {
+                    send_reply_err(
+                        &query,
+                        ErrorCode::InvalidKeyExpr,
+                        format!(
+                            "Failed to build the reply key expression for type '{}': {e}",
+                            type_info.full_name
+                        ),
+                    )
+                    .await;
+                    continue;
+                }
+            };
+            // the types not served are still counted in the statistics
+            if !registry.is_served(type_info) {
+                statistics.record_type_query(type_info.get_type_name(), format);
+                send_not_served(&query, type_info).await;
+                continue;
+            }
+            if let Some(hash) = expect_hash.filter(|hash| type_info.type_hash != *hash) {
+                send_hash_mismatch(&query, type_info, hash).await;
+                continue;
+            }
+            statistics.record_type_query(type_info.get_type_name(), format);
+            let rendered = renderer
+                .render(registry, type_info, &render_options)
+                .and_then(|payload| {
+                    if render_options.canonical && renderer.encoding() == Encoding::APPLICATION_JSON
+                    {
+                        payload.canonicalize()
+                    } else {
+                        Ok(payload)
+                    }
+                });
+            match rendered {
+                Ok(payload) => {
+                    // the content hash is the one of the uncompressed payload
+                    let etag = content_hash(&payload);
+                    if if_none_match.contains(&etag.as_str()) {
+                        stats.bytes_sent +=
+                            send_not_modified(&query, reply_ke, &etag, &cache_control).await;
+                        continue;
+                    }
+                    let (payload, encoding) = match compression {
+                        Some(_) => match compression::compress(&payload) {
+                            Ok(compressed) => (
+                                reply::Payload::Binary(compressed),
+                                compression::encoding(&renderer.encoding()),
+                            ),
+                            Err(e) => {
+                                send_reply_err(&query, ErrorCode::Internal, e).await;
+                                continue;
+                            }
+                        },
+                        None => (payload, renderer.encoding()),
+                    };
+                    let mut metadata = Vec::with_capacity(4);
+                    if let Some(compression) = compression {
+                        metadata.push(("compression", compression));
+                    }
+                    if let Some(version) = &type_info.package_version {
+                        metadata.push(("package_version", version.as_str()));
+                    }
+                    if alias.is_some() {
+                        metadata.push(("alias_of", type_info.get_type_name()));
+                    }
+                    if all_versions && registry.is_overlaid(type_info) {
+                        metadata.push(("overlaid", "true"));
+                    }
+                    stats.bytes_sent += send_tagged_reply(
+                        &query,
+                        reply_ke,
+                        &*payload,
+                        encoding,
+                        &etag,
+                        &cache_control,
+                        &metadata,
+                    )
+                    .await;
+                }
+                Err(e) => send_reply_err(&query, ErrorCode::RenderingFailed, e).await,
+            }
+        }
+    }
+    stats
+}
+
+// Send an error reply for a type which is loaded but not served
+pub(crate) async fn send_not_served(query: &Query, type_info: &type_info::TypeInfo) {
+    send_reply_err_details(
+        query,
+        ErrorCode::NotServed,
+        format!("Type '{}' is not served", type_info.get_type_name()),
+        Some(ErrorDetails {
+            type_name: Some(type_info.get_type_name().to_string()),
+            ..Default::default()
+        }),
+    )
+    .await
+}
+
+// Send an error reply for a type whose hash differs from the one expected by the query
+async fn send_hash_mismatch(query: &Query, type_info: &type_info::TypeInfo, expected: &str) {
+    send_reply_err_details(
+        query,
+        ErrorCode::HashMismatch,
+        format!(
+            "Type '{}' has hash '{}' but '{expected}' was expected",
+            type_info.get_type_name(),
+            type_info.type_hash
+        ),
+        Some(ErrorDetails {
+            type_name: Some(type_info.get_type_name().to_string()),
+            type_hash: Some(type_info.type_hash.to_string()),
+            expected_hash: Some(expected.to_string()),
+            ..Default::default()
+        }),
+    )
+    .await
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+async fn handle_ros2_env_query(
+    query: Query,
+    namespace: Option<&keyexpr>,
+    token: Option<&str>,
+) -> QueryStats {
+    let mut stats = QueryStats::default();
+    tracing::debug!("Received query: {}", query.key_expr());
+    if !auth::is_authorized(&query, token) {
+        send_reply_err(
+            &query,
+            ErrorCode::Unauthorized,
+            "A valid token is required to query '@ros2_env/*'",
+        )
+        .await;
+        return stats;
+    }
+    let ke = match keformat_ros2_env::parse(strip_namespace(query.key_expr())) {
+        Ok(ke) => ke,
+        Err(_) => {
+            tracing::error!(
+                "Received a query on '{}' but it doesn't match the '@ros2_env/*' queryable!",
+                query.key_expr()
+            );
+            return stats;
+        }
+    };
+
+    if ALLOWED_ENV_VARS.contains(&ke.env_var().as_str()) {
+        if let Some(value) = std::env::var_os(ke.env_var().as_str()) {
+            stats.matched = 1;
+            stats.bytes_sent += send_reply(
+                &query,
+                query_reply_ke(&query, namespace),
+                value.to_string_lossy(),
+                Encoding::TEXT_PLAIN,
+            )
+            .await;
+        }
+    } else {
+        send_reply_err_details(
+            &query,
+            ErrorCode::NotAllowed,
+            format!(
+                "Environment variable '{}' cannot be queried. Allowed variables are: {:?}",
+                ke.env_var(),
+                ALLOWED_ENV_VARS
+            ),
+            Some(ErrorDetails {
+                value: Some(ke.env_var().to_string()),
+                allowed_values: ALLOWED_ENV_VARS.iter().map(|v| v.to_string()).collect(),
+                ..Default::default()
+            }),
+        )
+        .await;
+    }
+    stats
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+async fn handle_ros2_packages_query(
+    query: Query,
+    registry: &mut registry::Registry<'_>,
+) -> QueryStats {
+    tracing::debug!("Received query: {}", query.key_expr());
+    let mut stats = QueryStats::default();
+    let ke = match keformat_ros2_packages::parse(strip_namespace(query.key_expr())) {
+        Ok(ke) => ke,
+        Err(_) => {
+            tracing::error!(
+                "Received a query on '{}' but it doesn't match the '@ros2_packages/*' queryable!",
+                query.key_expr()
+            );
+            return stats;
+        }
+    };
+
+    // the 'rdeps' parameter (without value or set to true/false) requests the where-used report
+    // of the packages: the other packages with types referencing their types
+    let rdeps = match query.parameters().get("rdeps") {
+        None => false,
+        Some("") => true,
+        Some(s) => match s.parse::<bool>() {
+            Ok(rdeps) => rdeps,
+            Err(_) => {
+                send_reply_err_details(
+                    &query,
+                    ErrorCode::InvalidParameter,
+                    format!("Invalid 'rdeps' parameter '{s}' - expected true or false"),
+                    Some(ErrorDetails::invalid_value("rdeps", s, ["true", "false"])),
+                )
+                .await;
+                return stats;
+            }
+        },
+    };
+    // the references are computed on all the types, which have to be parsed
+    if rdeps {
+        registry.load_pending_matching(keyexpr::new("**").expect("valid key expression"));
+    }
+    let registry = &*registry;
+
+    let cache_control = registry.get_cache_control();
+    for (package, type_names) in registry.get_packages() {
+        let Ok(package_ke) = keyexpr::new(package.as_str()) else {
+            continue;
+        };
+        if !ke.package().intersects(package_ke) {
+            continue;
+        }
+        stats.matched += 1;
+        let reply_ke = match keformat!(keformat_ros2_packages::formatter(), package = package_ke) {
+            Ok(reply_ke) => namespaced(registry.get_namespace(), reply_ke),
+            Err(e) => {
+                send_reply_err(
+                    &query,
+                    ErrorCode::InvalidKeyExpr,
+                    format!(
+                        "Failed to build the reply key expression for package '{}': {e}",
+                        package
+                    ),
+                )
+                .await;
+                continue;
+            }
+        };
+        let response = if rdeps {
+            serde_json::to_string(&registry.get_package_dependents(package))
+        } else {
+            let type_names: Vec<Cow<str>> = type_names
+                .iter()
+                .map(|n| type_name::unescape(n.as_str()))
+                .collect();
+            serde_json::to_string(&type_names)
+        };
+        match response {
+            Ok(response) => {
+                let etag = content_hash(response.as_bytes());
+                stats.bytes_sent += send_tagged_reply(
+                    &query,
+                    reply_ke,
+                    response,
+                    Encoding::APPLICATION_JSON,
+                    &etag,
+                    &cache_control,
+                    &[],
+                )
+                .await
+            }
+            Err(e) => {
+                send_reply_err(
+                    &query,
+                    ErrorCode::SerializationFailed,
+                    format!("Failed to serialize reply for package '{package}': {e}"),
+                )
+                .await
+            }
+        }
+    }
+    stats
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+async fn handle_ros2_package_manifest_query(
+    query: Query,
+    registry: &registry::Registry<'_>,
+) -> QueryStats {
+    tracing::debug!("Received query: {}", query.key_expr());
+    let mut stats = QueryStats::default();
+    let ke = match keformat_ros2_package_manifest::parse(strip_namespace(query.key_expr())) {
+        Ok(ke) => ke,
+        Err(_) => {
+            tracing::error!(
+                "Received a query on '{}' but it doesn't match the '@ros2_packages/*/manifest' queryable!",
+                query.key_expr()
+            );
+            return stats;
+        }
+    };
+
+    let cache_control = registry.get_cache_control();
+    // the interface dependencies are the dependencies on packages defining types
+    let is_interface_package = |package: &str| {
+        registry
+            .get_packages()
+            .any(|(p, _)| type_name::unescape(p) == package)
+    };
+    for (package, _) in registry.get_packages() {
+        let Ok(package_ke) = keyexpr::new(package.as_str()) else {
+            continue;
+        };
+        if !ke.package().intersects(package_ke) {
+            continue;
+        }
+        let Some(manifest) = registry.get_package_manifest(package) else {
+            continue;
+        };
+        stats.matched += 1;
+        let reply_ke = match keformat!(
+            keformat_ros2_package_manifest::formatter(),
+            package = package_ke
+        ) {
+            Ok(reply_ke) => namespaced(registry.get_namespace(), reply_ke),
+            Err(e) => {
+                send_reply_err(
+                    &query,
+                    ErrorCode::InvalidKeyExpr,
+                    format!(
+                        "Failed to build the reply key expression for package '{package}': {e}"
+                    ),
+                )
+                .await;
+                continue;
+            }
+        };
+        match serde_json::to_string(&manifest.to_reply(is_interface_package)) {
+            Ok(response) => {
+                let etag = content_hash(response.as_bytes());
+                stats.bytes_sent += send_tagged_reply(
+                    &query,
+                    reply_ke,
+                    response,
+                    Encoding::APPLICATION_JSON,
+                    &etag,
+                    &cache_control,
+                    &[],
+                )
+                .await
+            }
+            Err(e) => {
+                send_reply_err(
+                    &query,
+                    ErrorCode::SerializationFailed,
+                    format!("Failed to serialize manifest of package '{package}': {e}"),
+                )
+                .await
+            }
+        }
+    }
+    stats
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+async fn handle_ros2_validate_query(
+    query: Query,
+    registry: &mut registry::Registry<'_>,
+) -> QueryStats {
+    let mut stats = QueryStats::default();
+    tracing::debug!("Received query: {}", query.key_expr());
+    let ke = match keformat_ros2_validate::parse(strip_namespace(query.key_expr())) {
+        Ok(ke) => ke,
+        Err(_) => {
+            tracing::error!(
+                "Received a query on '{}' but it doesn't match the '@ros2_validate/**' queryable!",
+                query.key_expr()
+            );
+            return stats;
+        }
+    };
+
+    // in warm-up or bounded-memory mode, parse the matching types which are not parsed yet
+    // (or were evicted)
+    if let Some(type_name) = ke.type_name() {
+        registry.load_pending_matching(type_name);
+    }
+    let registry = &*registry;
+
+    // Parse the JSON message instance from the query payload
+    let instance = match query.payload().map(|p| p.try_to_string()) {
+        Some(Ok(s)) => serde_json::from_str::<serde_json::Value>(&s)
+            .map_err(|e| format!("Invalid JSON message in query payload: {e}")),
+        Some(Err(e)) => Err(format!("Query payload is not a valid UTF-8 string: {e}")),
+        None => Err("Expected a JSON message to validate in query payload".to_string()),
+    };
+    let instance = match instance {
+        Ok(instance) => instance,
+        Err(msg) => {
+            send_reply_err(&query, ErrorCode::InvalidPayload, msg).await;
+            return stats;
+        }
+    };
+
+    if let Some(type_name) = ke.type_name() {
+        for type_info in registry.iter_types(type_name) {
+            stats.matched += 1;
+            if stats.matched % REPLIES_BETWEEN_YIELDS == 0 {
+                tokio::task::yield_now().await;
+            }
+            if !registry.is_served(type_info) {
+                send_not_served(&query, type_info).await;
+                continue;
+            }
+            let reply_ke = match keformat!(
+                keformat_ros2_validate::formatter(),
+                type_name = &type_info.full_name
+            ) {
+                Ok(reply_ke) => namespaced(registry.get_namespace(), reply_ke),
+                Err(e) => {
+                    send_reply_err(
+                        &query,
+                        ErrorCode::InvalidKeyExpr,
+                        format!(
+                            "Failed to build the reply key expression for type '{}': {e}",
+                            type_info.full_name
+                        ),
+                    )
+                    .await;
+                    continue;
+                }
+            };
+            let report =
+                validation::validate(&instance, &type_info.type_description.type_description_msg);
+            match serde_json::to_string(&report) {
+                Ok(response) => {
+                    stats.bytes_sent +=
+                        send_reply(&query, reply_ke, response, Encoding::APPLICATION_JSON).await
+                }
+                Err(e) => {
+                    send_reply_err(
+                        &query,
+                        ErrorCode::SerializationFailed,
+                        format!(
+                            "Failed to serialize validation report for type '{}': {e}",
+                            type_info.full_name
+                        ),
+                    )
+                    .await
+                }
+            }
+        }
+    }
+    stats
+}
+
+// Return a key expression in the namespace of a tenant, if any
+pub(crate) fn namespaced(
+    namespace: Option<&keyexpr>,
+    ke: impl Into<KeyExpr<'static>>,
+) -> KeyExpr<'static> {
+    let ke = ke.into();
+    match namespace {
+        Some(namespace) => (namespace / &*ke).into(),
+        None => ke,
+    }
+}
+
+// Return a queried key expression without its tenant namespace, if any, i.e. without the chunks
+// before the first one starting with '@' (e.g. "robot_a/@ros2_types/**" => "@ros2_types/**")
+pub(crate) fn strip_namespace(ke: &keyexpr) -> &keyexpr {
+    if ke.as_str().starts_with('@') {
+        return ke;
+    }
+    match ke.as_str().find("/@") {
+        Some(i) => keyexpr::new(&ke.as_str()[i + 1..]).unwrap_or(ke),
+        None => ke,
+    }
+}
+
+// Return the key expression of the reply to a query on a single resource: the queried one,
+// in the namespace of the tenant, if any
+pub(crate) fn query_reply_ke(query: &Query, namespace: Option<&keyexpr>) -> KeyExpr<'static> {
+    match namespace {
+        Some(namespace) => (namespace / strip_namespace(query.key_expr())).into(),
+        None => query.key_expr().clone(),
+    }
+}
+
+// Category of a server-side failure, sent in error replies to let clients handle them
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(not(feature = "http"), allow(dead_code))]
+pub(crate) enum ErrorCode {
+    InvalidParameter,    // a query parameter has an invalid value
+    InvalidPayload,      // the query payload is missing or invalid
+    InvalidKeyExpr,      // a reply key expression can't be built
+    NotAllowed,          // the queried resource can't be accessed
+    Unauthorized,        // the query doesn't provide the token required for the key space
+    RateLimited,         // the client sent too many queries and must retry later
+    HashMismatch,        // the type hash differs from the one expected by the query
+    NotServed,           // the queried type is loaded but not served (see --deny)
+    Maintenance,         // the registry is paused for maintenance and must be queried later
+    UnknownOperation,    // the queried operation doesn't exist
+    NotFound,            // the queried resource doesn't exist (HTTP routes and subjects)
+    RenderingFailed,     // a type can't be rendered in the requested format
+    SerializationFailed, // a reply can't be serialized
+    Internal,            // any other server-side failure
+}
+
+// Machine-readable details of an error reply, for the clients to handle it without parsing its
+// message (only the relevant ones are set)
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct ErrorDetails {
+    // the faulty query parameter, and its value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    // the values accepted for this parameter, or the available operations or resources
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_values: Vec<String>,
+    // the accepted names close to an unknown one (e.g. for a misspelled format)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+    // the type concerned by the error, with its type hash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_hash: Option<String>,
+    // the delay after which a rate limited client can retry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+}
+
+impl ErrorDetails {
+    // The details of an invalid parameter value, with the accepted values and the ones close to
+    // the invalid value
+    pub(crate) fn invalid_value<S: AsRef<str>>(
+        parameter: &'static str,
+        value: &str,
+        allowed_values: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let allowed_values: Vec<String> = allowed_values
+            .into_iter()
+            .map(|v| v.as_ref().to_string())
+            .collect();
+        ErrorDetails {
+            parameter: Some(parameter),
+            value: Some(value.to_string()),
+            candidates: close_names(value, &allowed_values),
+            allowed_values,
+            ..Default::default()
+        }
+    }
+}
+
+// Return the names close to an unknown one: equal ignoring the case, containing it or within 2
+// edits of it
+pub(crate) fn close_names(unknown: &str, names: &[String]) -> Vec<String> {
+    let unknown = unknown.to_lowercase();
+    if unknown.is_empty() {
+        return Vec::new();
+    }
+    names
+        .iter()
+        .filter(|name| {
+            let name = name.to_lowercase();
+            name.contains(&unknown) || edit_distance(&name, &unknown) <= 2
+        })
+        .cloned()
+        .collect()
+}
+
+// The Levenshtein distance between 2 strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// An error reply, sent as a JSON object
+#[derive(Debug, Serialize)]
+struct ErrorReply<'a> {
+    code: ErrorCode,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<&'a ErrorDetails>,
+}
+
+// Send an error reply to the query, as a JSON object with an error code and a message
+pub(crate) async fn send_reply_err(query: &Query, code: ErrorCode, message: impl AsRef<str>) {
+    send_reply_err_details(query, code, message, None).await
+}
+
+// Send an error reply to the query, as a JSON object with an error code, a message and the
+// details of the error, if any
+pub(crate) async fn send_reply_err_details(
+    query: &Query,
+    code: ErrorCode,
+    message: impl AsRef<str>,
+    details: Option<ErrorDetails>,
+) {
+    let message = message.as_ref();
+    tracing::debug!("Error reply for {}: {message}", query.key_expr());
+    let error = ErrorReply {
+        code,
+        message,
+        details: details.as_ref(),
+    };
+    // the bare message as fallback, if the error can't be serialized
+    let (payload, encoding) = match serde_json::to_string(&error) {
+        Ok(json) => (json, Encoding::APPLICATION_JSON),
+        Err(_) => (message.to_string(), Encoding::TEXT_PLAIN),
+    };
+    query
+        .reply_err(payload)
+        .encoding(encoding)
+        .await
+        .unwrap_or_else(|e| tracing::warn!("Error sending reply for {}: {e}", query.key_expr()));
+}
+
+// Send a reply to the query, returning the number of payload bytes sent (0 on failure)
+pub(crate) async fn send_reply(
+    query: &Query,
+    key_expr: impl Into<KeyExpr<'static>>,
+    payload: impl Into<ZBytes>,
+    encoding: Encoding,
+) -> usize {
+    let payload: ZBytes = payload.into();
+    let len = payload.len();
+    let signature = signing::sign(&payload.to_bytes());
+    match query
+        .reply(key_expr.into(), payload)
+        .encoding(encoding)
+        .attachment(signature.map(|signature| format!("signature={signature}")))
+        .await
+    {
+        Ok(()) => len,
+        Err(e) => {
+            tracing::warn!("Error sending reply for {}: {e}", query.key_expr());
+            0
+        }
+    }
+}
+
+// Return the content hash of a reply payload, used as its entity tag for conditional queries
+pub(crate) fn content_hash(payload: &[u8]) -> String {
+    format!("{:x}", md5::compute(payload))
+}
+
+// Send a reply with its content hash and cache-control metadata in attachment,
+// returning the number of payload bytes sent (0 on failure)
+pub(crate) async fn send_tagged_reply(
+    query: &Query,
+    key_expr: impl Into<KeyExpr<'static>>,
+    payload: impl Into<ZBytes>,
+    encoding: Encoding,
+    etag: &str,
+    cache_control: &reply::CacheControl,
+    metadata: &[(&str, &str)],
+) -> usize {
+    let payload: ZBytes = payload.into();
+    let len = payload.len();
+    if let Some(chunk_size) = chunking::chunk_size(len) {
+        return chunking::send_chunked_reply(
+            query,
+            key_expr.into(),
+            payload,
+            encoding,
+            etag,
+            cache_control,
+            metadata,
+            chunk_size,
+        )
+        .await;
+    }
+    let mut attachment = cache_control.attachment(etag, false);
+    for (key, value) in metadata {
+        attachment.push_str(&format!(";{key}={value}"));
+    }
+    if let Some(signature) = signing::sign(&payload.to_bytes()) {
+        attachment.push_str(";signature=");
+        attachment.push_str(&signature);
+    }
+    match query
+        .reply(key_expr.into(), payload)
+        .encoding(encoding)
+        .attachment(attachment)
+        .await
+    {
+        Ok(()) => len,
+        Err(e) => {
+            tracing::warn!("Error sending reply for {}: {e}", query.key_expr());
+            0
+        }
+    }
+}
+
+// Send a "not modified" reply: an empty payload with the unchanged content hash and the
+// cache-control metadata in attachment, returning the number of payload bytes sent (always 0)
+pub(crate) async fn send_not_modified(
+    query: &Query,
+    key_expr: impl Into<KeyExpr<'static>>,
+    etag: &str,
+    cache_control: &reply::CacheControl,
+) -> usize {
+    query
+        .reply(key_expr.into(), ZBytes::default())
+        .attachment(cache_control.attachment(etag, true))
+        .await
+        .unwrap_or_else(|e| tracing::warn!("Error sending reply for {}: {e}", query.key_expr()));
+    0
+}
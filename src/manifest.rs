@@ -0,0 +1,116 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// The inventory manifest of the registry (type name => type hash, with the epoch of the registry
+// content), for the clients to determine in one round trip which schemas they miss or have stale.
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use zenoh::{bytes::Encoding, key_expr::keyexpr, query::Query};
+
+use crate::{
+    access_log::QueryStats, content_hash, query_reply_ke, registry::Registry, send_not_modified,
+    send_reply_err, send_reply_err_details, send_tagged_reply, ErrorCode, ErrorDetails,
+};
+
+// Key expression on which the manifest is replied
+pub(crate) const KE_MANIFEST: &str = "@ros2_types_manifest";
+
+// The inventory manifest
+#[derive(Debug, Serialize)]
+struct Manifest<'a> {
+    epoch: u64, // epoch of the registry content (time of its last change)
+    types: BTreeMap<&'a str, &'a str>, // type name => type hash
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+pub(crate) async fn handle_ros2_manifest_query(
+    query: Query,
+    registry: &mut Registry<'_>,
+) -> QueryStats {
+    tracing::debug!("Received query: {}", query.key_expr());
+    let mut stats = QueryStats::default();
+    let all_types = keyexpr::new("**").expect("valid key expression");
+    registry.load_pending_matching(all_types);
+    let registry = &*registry;
+    let cache_control = registry.get_cache_control();
+    let manifest = Manifest {
+        epoch: cache_control.epoch,
+        types: registry
+            .iter_types(all_types)
+            .filter(|t| registry.is_served(t))
+            .map(|t| (t.get_type_name(), t.type_hash.as_str()))
+            .collect(),
+    };
+
+    // JSON by default, or CBOR with the 'format=Cbor' parameter
+    let (payload, encoding) = match query.parameters().get("format") {
+        None | Some("Json") => (
+            serde_json::to_vec(&manifest).map_err(|e| e.to_string()),
+            Encoding::APPLICATION_JSON,
+        ),
+        Some("Cbor") => {
+            let mut payload = Vec::new();
+            (
+                ciborium::into_writer(&manifest, &mut payload)
+                    .map(|()| payload)
+                    .map_err(|e| e.to_string()),
+                Encoding::APPLICATION_CBOR,
+            )
+        }
+        Some(f) => {
+            send_reply_err_details(
+                &query,
+                ErrorCode::InvalidParameter,
+                format!("Unknown format '{f}' - accepted values are: [\"Json\", \"Cbor\"]"),
+                Some(ErrorDetails::invalid_value("format", f, ["Json", "Cbor"])),
+            )
+            .await;
+            return stats;
+        }
+    };
+    match payload {
+        Ok(payload) => {
+            let etag = content_hash(&payload);
+            let reply_ke = query_reply_ke(&query, registry.get_namespace());
+            stats.matched = 1;
+            stats.bytes_sent += if query
+                .parameters()
+                .get("if_none_match")
+                .is_some_and(|etags| etags.split('|').any(|e| e == etag))
+            {
+                send_not_modified(&query, reply_ke, &etag, &cache_control).await
+            } else {
+                send_tagged_reply(
+                    &query,
+                    reply_ke,
+                    payload,
+                    encoding,
+                    &etag,
+                    &cache_control,
+                    &[],
+                )
+                .await
+            };
+        }
+        Err(e) => {
+            send_reply_err(
+                &query,
+                ErrorCode::SerializationFailed,
+                format!("Failed to serialize manifest: {e}"),
+            )
+            .await
+        }
+    }
+    stats
+}
@@ -0,0 +1,314 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::BufWriter,
+    path::Path,
+    sync::Arc,
+};
+
+use mcap::records::{MessageHeader, Record};
+use serde::Serialize;
+use zenoh::key_expr::keyexpr;
+
+use crate::{registry::Registry, reply::mcap_schema_encoding, type_name};
+
+// Key of the Channel metadata where rosbag2 records the type hash of the topic
+const TOPIC_TYPE_HASH_KEY: &str = "topic_type_hash";
+
+// A Schema record of an MCAP file, with the topics of the channels using it
+pub(crate) struct McapSchemaRecord {
+    pub id: u16,
+    pub name: String,
+    pub encoding: String,
+    pub data: Vec<u8>,
+    // topic => type hash recorded in the channel metadata (if any)
+    pub topics: BTreeMap<String, Option<String>>,
+}
+
+// Status of a Schema record compared with the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SchemaStatus {
+    Ok,
+    // the record has no data
+    Missing,
+    // the record data is a strict prefix of the registry's schema
+    Truncated,
+    UnknownType,
+    EncodingMismatch,
+    DefinitionMismatch,
+    // the definition matches, but a channel recorded another type hash
+    HashMismatch,
+    // the registry can't generate the schema (e.g. missing dependencies)
+    Unverifiable,
+}
+
+// Report of the validation of the Schema records of an MCAP file against the registry
+#[derive(Debug, Serialize)]
+pub(crate) struct McapReport {
+    pub path: String,
+    pub schemas: usize,
+    pub mismatches: usize,
+    pub entries: Vec<McapReportEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct McapReportEntry {
+    pub id: u16,
+    pub name: String,
+    pub encoding: String,
+    pub status: SchemaStatus,
+    pub topics: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+// Read all the Schema records of an MCAP file (including those within chunks), sorted by id,
+// with the topics of the channels referencing them
+pub(crate) fn read_schema_records(buf: &[u8]) -> Result<Vec<McapSchemaRecord>, String> {
+    let mut schemas: BTreeMap<u16, McapSchemaRecord> = BTreeMap::new();
+    let mut channels = Vec::new();
+    let reader =
+        mcap::read::ChunkFlattener::new(buf).map_err(|e| format!("Invalid MCAP file: {e}"))?;
+    for record in reader {
+        match record.map_err(|e| format!("Invalid MCAP record: {e}"))? {
+            // the Schema records are repeated in the summary section: keep the first one
+            Record::Schema { header, data } => {
+                schemas
+                    .entry(header.id)
+                    .or_insert_with(|| McapSchemaRecord {
+                        id: header.id,
+                        name: header.name,
+                        encoding: header.encoding,
+                        data: data.into_owned(),
+                        topics: BTreeMap::new(),
+                    });
+            }
+            Record::Channel(channel) => channels.push(channel),
+            _ => {}
+        }
+    }
+    for channel in channels {
+        if let Some(schema) = schemas.get_mut(&channel.schema_id) {
+            schema
+                .topics
+                .entry(channel.topic)
+                .or_insert_with(|| channel.metadata.get(TOPIC_TYPE_HASH_KEY).cloned());
+        }
+    }
+    Ok(schemas.into_values().collect())
+}
+
+// Compare a Schema record with the registry's schema of its type
+pub(crate) fn check_schema(
+    registry: &mut Registry,
+    schema: &McapSchemaRecord,
+) -> (SchemaStatus, Option<String>, Option<String>) {
+    if let Ok(ke) = keyexpr::new(type_name::escape(&schema.name).as_ref()) {
+        registry.load_pending_matching(ke);
+    }
+    let Some(t) = registry.get_type(&schema.name) else {
+        return (SchemaStatus::UnknownType, None, None);
+    };
+    let type_hash = Some(t.type_hash.to_string());
+    // a recorder not finding the definition writes an empty record (possibly without encoding)
+    if schema.data.is_empty() {
+        return (SchemaStatus::Missing, type_hash, None);
+    }
+    let expected_encoding = mcap_schema_encoding(&t.definition_path);
+    if schema.encoding != expected_encoding {
+        let detail = format!("expected encoding '{expected_encoding}'");
+        return (SchemaStatus::EncodingMismatch, type_hash, Some(detail));
+    }
+    let expected = match registry.get_mcap_schema(t) {
+        Ok(expected) => expected,
+        Err(e) => return (SchemaStatus::Unverifiable, type_hash, Some(e)),
+    };
+    let data = String::from_utf8_lossy(&schema.data);
+    if data != expected {
+        if expected.starts_with(&*data) {
+            let detail = format!("{} of {} bytes", data.len(), expected.len());
+            return (SchemaStatus::Truncated, type_hash, Some(detail));
+        }
+        return (
+            SchemaStatus::DefinitionMismatch,
+            type_hash,
+            Some(first_difference(&data, &expected)),
+        );
+    }
+    // the definitions are equal, but the recorder may have seen another version of the type
+    let mismatching_topics: Vec<&str> = schema
+        .topics
+        .iter()
+        .filter(|(_, hash)| hash.as_deref().is_some_and(|h| h != t.type_hash.as_str()))
+        .map(|(topic, _)| topic.as_str())
+        .collect();
+    if !mismatching_topics.is_empty() {
+        let detail = format!(
+            "other type hash recorded for {}",
+            mismatching_topics.join(", ")
+        );
+        return (SchemaStatus::HashMismatch, type_hash, Some(detail));
+    }
+    (SchemaStatus::Ok, type_hash, None)
+}
+
+// Describe the first differing line of 2 definitions
+fn first_difference(recorded: &str, expected: &str) -> String {
+    let mut recorded_lines = recorded.lines();
+    let mut expected_lines = expected.lines();
+    for line in 1.. {
+        match (recorded_lines.next(), expected_lines.next()) {
+            (Some(r), Some(e)) if r == e => continue,
+            (Some(r), Some(e)) => return format!("line {line}: '{r}' instead of '{e}'"),
+            (Some(r), None) => return format!("line {line}: unexpected '{r}'"),
+            (None, Some(e)) => return format!("line {line}: missing '{e}'"),
+            (None, None) => break,
+        }
+    }
+    "different line endings".into()
+}
+
+// Validate the Schema records of an MCAP file against the registry's current definitions and
+// hashes, and return the report
+pub(crate) fn check_mcap(registry: &mut Registry, path: &Path) -> Result<McapReport, String> {
+    let buf = std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    check_mcap_content(registry, &buf, path.display().to_string())
+}
+
+// Validate the Schema records of the content of an MCAP file (with this path in the report)
+pub(crate) fn check_mcap_content(
+    registry: &mut Registry,
+    buf: &[u8],
+    path: String,
+) -> Result<McapReport, String> {
+    let schemas = read_schema_records(buf)?;
+    let entries: Vec<McapReportEntry> = schemas
+        .into_iter()
+        .map(|schema| {
+            let (status, type_hash, detail) = check_schema(registry, &schema);
+            McapReportEntry {
+                id: schema.id,
+                name: schema.name,
+                encoding: schema.encoding,
+                status,
+                topics: schema.topics.into_keys().collect(),
+                type_hash,
+                detail,
+            }
+        })
+        .collect();
+    Ok(McapReport {
+        path,
+        schemas: entries.len(),
+        mismatches: entries
+            .iter()
+            .filter(|e| e.status != SchemaStatus::Ok)
+            .count(),
+        entries,
+    })
+}
+
+// Rewrite an MCAP file to another one, replacing its missing or truncated Schema records with the
+// registry's MCAP schemas of their types. The channels, messages and metadata records are copied
+// as is. Return the names of the repaired schemas.
+pub(crate) fn repair_mcap(
+    registry: &mut Registry,
+    input: &Path,
+    output: &Path,
+) -> Result<Vec<String>, String> {
+    let buf =
+        std::fs::read(input).map_err(|e| format!("Failed to read {}: {e}", input.display()))?;
+
+    // the registry's schemas (encoding and data) replacing the broken ones, by schema name
+    let mut replacements: HashMap<String, (&'static str, Vec<u8>)> = HashMap::new();
+    for schema in read_schema_records(&buf)? {
+        match check_schema(registry, &schema) {
+            (SchemaStatus::Missing | SchemaStatus::Truncated, _, _) => {
+                let Some(t) = registry.get_type(&schema.name) else {
+                    continue;
+                };
+                match registry.get_mcap_schema(t) {
+                    Ok(data) => {
+                        let encoding = mcap_schema_encoding(&t.definition_path);
+                        replacements.insert(schema.name, (encoding, data.as_bytes().to_vec()));
+                    }
+                    Err(e) => tracing::warn!("Schema {} not repaired: {e}", schema.name),
+                }
+            }
+            (SchemaStatus::Ok, _, _) => {}
+            (status, _, _) => {
+                tracing::warn!("Schema {} not repaired: {status:?}", schema.name)
+            }
+        }
+    }
+
+    let write_err = |e: mcap::McapError| format!("Failed to write {}: {e}", output.display());
+    let file =
+        File::create(output).map_err(|e| format!("Failed to create {}: {e}", output.display()))?;
+    let mut writer = mcap::Writer::new(BufWriter::new(file)).map_err(write_err)?;
+    // the channels of the input file (shared by their messages) => their id in the output file
+    let mut channel_ids: HashMap<*const mcap::Channel, u16> = HashMap::new();
+    let messages = mcap::MessageStream::new(&buf).map_err(|e| format!("Invalid MCAP file: {e}"))?;
+    for message in messages {
+        let message = message.map_err(|e| format!("Invalid MCAP message: {e}"))?;
+        let channel_id = match channel_ids.get(&Arc::as_ptr(&message.channel)) {
+            Some(id) => *id,
+            None => {
+                let mut channel = (*message.channel).clone();
+                let replacement = channel
+                    .schema
+                    .as_ref()
+                    .and_then(|schema| replacements.get(&schema.name).map(|r| (schema, r)));
+                if let Some((schema, (encoding, data))) = replacement {
+                    channel.schema = Some(Arc::new(mcap::Schema {
+                        name: schema.name.clone(),
+                        encoding: encoding.to_string(),
+                        data: Cow::Owned(data.clone()),
+                    }));
+                }
+                let id = writer.add_channel(&channel).map_err(write_err)?;
+                channel_ids.insert(Arc::as_ptr(&message.channel), id);
+                id
+            }
+        };
+        let header = MessageHeader {
+            channel_id,
+            sequence: message.sequence,
+            log_time: message.log_time,
+            publish_time: message.publish_time,
+        };
+        writer
+            .write_to_known_channel(&header, &message.data)
+            .map_err(write_err)?;
+    }
+    let reader =
+        mcap::read::ChunkFlattener::new(&buf).map_err(|e| format!("Invalid MCAP file: {e}"))?;
+    for record in reader {
+        if let Record::Metadata(metadata) =
+            record.map_err(|e| format!("Invalid MCAP record: {e}"))?
+        {
+            writer.write_metadata(&metadata).map_err(write_err)?;
+        }
+    }
+    writer.finish().map_err(write_err)?;
+
+    let mut repaired: Vec<String> = replacements.into_keys().collect();
+    repaired.sort_unstable();
+    Ok(repaired)
+}
@@ -0,0 +1,184 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Mirroring of an upstream registry, synchronized by deltas for low-bandwidth links: the upstream
+// inventory manifest (type name => type hash, on `@ros2_admin/inventory`) is compared with the
+// mirrored one, and only the new or changed types are fetched (as snapshot entries, with the
+// `Snapshot` format), while the types not in the upstream registry anymore are removed.
+use std::{collections::HashMap, time::Duration};
+
+use futures::{channel::mpsc::UnboundedSender, StreamExt};
+use zenoh::{
+    key_expr::{format::keformat, keyexpr, OwnedKeyExpr},
+    query::ReplyKeyExpr,
+    sample::Locality,
+    Session,
+};
+
+use crate::{
+    chunking, keformat_ros2_admin, keformat_ros2_types, namespaced,
+    snapshot::{self, SnapshotEntry},
+    type_name,
+};
+
+// Maximum number of types fetched concurrently from the upstream registry
+const FETCH_CONCURRENCY: usize = 16;
+
+// Changes to apply to the mirror registry
+#[derive(Debug, Default)]
+pub(crate) struct MirrorUpdate {
+    pub removed: Vec<String>, // names of the types not in the upstream registry anymore
+    pub added: Vec<SnapshotEntry>, // the new or changed types (replacing the mirrored ones)
+}
+
+// Periodically synchronize with the upstream registry (served under the `upstream` namespace,
+// if any, and requiring this token for its `@ros2_admin/**` key space, if any), sending the
+// changes to apply to the mirror registry
+pub(crate) async fn run(
+    session: Session,
+    upstream: Option<OwnedKeyExpr>,
+    token: Option<String>,
+    interval: Duration,
+    updates: UnboundedSender<MirrorUpdate>,
+) -> Result<(), String> {
+    // the inventory of the mirrored types
+    let mut mirrored: HashMap<String, String> = HashMap::new();
+    loop {
+        match sync(
+            &session,
+            upstream.as_deref(),
+            token.as_deref(),
+            &mut mirrored,
+        )
+        .await
+        {
+            Ok(update) if update.removed.is_empty() && update.added.is_empty() => {
+                tracing::debug!("Mirror up to date ({} types)", mirrored.len())
+            }
+            Ok(update) => {
+                tracing::info!(
+                    "Mirror synchronized: {} new or changed types, {} removed types",
+                    update.added.len(),
+                    update.removed.len()
+                );
+                updates
+                    .unbounded_send(update)
+                    .map_err(|_| "Mirror registry closed".to_string())?;
+            }
+            Err(e) => tracing::warn!("Mirror synchronization failed: {e}"),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+// Compare the upstream inventory with the mirrored one and fetch the new or changed types,
+// updating the mirrored inventory. The types failing to be fetched are retried at next sync.
+async fn sync(
+    session: &Session,
+    upstream: Option<&keyexpr>,
+    token: Option<&str>,
+    mirrored: &mut HashMap<String, String>,
+) -> Result<MirrorUpdate, String> {
+    let inventory = fetch_inventory(session, upstream, token).await?;
+    let removed: Vec<String> = mirrored
+        .keys()
+        .filter(|name| !inventory.contains_key(*name))
+        .cloned()
+        .collect();
+    let changed: Vec<(&String, &String)> = inventory
+        .iter()
+        .filter(|(name, hash)| mirrored.get(*name) != Some(*hash))
+        .collect();
+    let fetched: Vec<Result<SnapshotEntry, String>> = futures::stream::iter(changed)
+        .map(|(name, hash)| fetch_type(session, upstream, name, hash))
+        .buffer_unordered(FETCH_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut update = MirrorUpdate {
+        removed,
+        added: Vec::new(),
+    };
+    for name in &update.removed {
+        mirrored.remove(name);
+    }
+    for entry in fetched {
+        match entry {
+            Ok(entry) => {
+                mirrored.insert(entry.type_name.clone(), entry.type_hash.clone());
+                update.added.push(entry);
+            }
+            Err(e) => tracing::warn!("{e}"),
+        }
+    }
+    Ok(update)
+}
+
+// Get the inventory manifest of the upstream registry (excluding the local one)
+async fn fetch_inventory(
+    session: &Session,
+    upstream: Option<&keyexpr>,
+    token: Option<&str>,
+) -> Result<HashMap<String, String>, String> {
+    let ke = keformat!(keformat_ros2_admin::formatter(), operation = "inventory")
+        .map_err(|e| format!("Failed to build the inventory key expression: {e}"))?;
+    let replies = session
+        .get(namespaced(upstream, ke))
+        .allowed_destination(Locality::Remote)
+        .accept_replies(ReplyKeyExpr::Any)
+        .attachment(token.map(|token| format!("token={token}")))
+        .await
+        .map_err(|e| format!("Failed to query the upstream inventory: {e}"))?;
+    let reply = chunking::recv_replies(replies)
+        .await
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No reply from the upstream registry".to_string())?;
+    let sample = reply.map_err(|e| {
+        format!(
+            "Upstream inventory query failed: {}",
+            e.payload().try_to_string().unwrap_or_default()
+        )
+    })?;
+    serde_json::from_slice(&sample.payload().to_bytes())
+        .map_err(|e| format!("Invalid upstream inventory: {e}"))
+}
+
+// Get the snapshot entry of a type with this hash from the upstream registry
+async fn fetch_type(
+    session: &Session,
+    upstream: Option<&keyexpr>,
+    name: &str,
+    hash: &str,
+) -> Result<SnapshotEntry, String> {
+    let ke = keformat!(
+        keformat_ros2_types::formatter(),
+        type_name = type_name::escape(name)
+    )
+    .map_err(|e| format!("Failed to build the key expression of type {name}: {e}"))?;
+    let selector = format!("{}?format=Snapshot;hash={hash}", namespaced(upstream, ke));
+    let replies = session
+        .get(&selector)
+        .allowed_destination(Locality::Remote)
+        .accept_replies(ReplyKeyExpr::Any)
+        .await
+        .map_err(|e| format!("Failed to query '{selector}': {e}"))?;
+    match chunking::recv_replies(replies).await.into_iter().next() {
+        Some(Ok(sample)) => snapshot::decode_entry(&sample.payload().to_bytes()),
+        Some(Err(e)) => Err(format!(
+            "Failed to fetch {name} from the upstream registry: {}",
+            e.payload().try_to_string().unwrap_or_default()
+        )),
+        None => Err(format!("Type {name} not found in the upstream registry")),
+    }
+}
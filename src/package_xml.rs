@@ -0,0 +1,119 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Parsing of the package.xml manifests of the packages (format 2 or 3, see REP 149), to serve
+// their name, version, maintainers and interface dependencies.
+use std::path::Path;
+
+use serde::Serialize;
+
+// Tags of the dependencies in a package.xml
+const DEPEND_TAGS: &[&str] = &[
+    "depend",
+    "build_depend",
+    "build_export_depend",
+    "exec_depend",
+];
+
+#[derive(Debug, Clone)]
+pub(crate) struct PackageManifest {
+    pub name: String,
+    pub version: String,
+    pub maintainers: Vec<Maintainer>,
+    pub dependencies: Vec<String>, // all the dependencies, sorted and deduplicated
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Maintainer {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+// The manifest of a package as replied, with only its dependencies defining interfaces
+#[derive(Debug, Serialize)]
+pub(crate) struct ManifestReply<'a> {
+    name: &'a str,
+    version: &'a str,
+    maintainers: &'a [Maintainer],
+    interface_dependencies: Vec<&'a str>,
+}
+
+impl PackageManifest {
+    // Return the reply for this manifest, with the dependencies on the packages defining interfaces
+    pub(crate) fn to_reply(
+        &self,
+        is_interface_package: impl Fn(&str) -> bool,
+    ) -> ManifestReply<'_> {
+        ManifestReply {
+            name: &self.name,
+            version: &self.version,
+            maintainers: &self.maintainers,
+            interface_dependencies: self
+                .dependencies
+                .iter()
+                .map(String::as_str)
+                .filter(|d| is_interface_package(d))
+                .collect(),
+        }
+    }
+}
+
+// Read the package.xml file of a package directory in a share directory
+// (e.g. "<prefix>/share/std_msgs/package.xml")
+pub(crate) fn read(package_dir: &Path) -> Result<PackageManifest, String> {
+    let path = package_dir.join("package.xml");
+    let xml = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    parse(&xml).map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+pub(crate) fn parse(xml: &str) -> Result<PackageManifest, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| e.to_string())?;
+    let root = doc.root_element();
+    if !root.has_tag_name("package") {
+        return Err(format!(
+            "unexpected root element <{}>",
+            root.tag_name().name()
+        ));
+    }
+    let text = |node: roxmltree::Node| node.text().unwrap_or_default().trim().to_string();
+    let element = |tag: &str| {
+        root.children()
+            .find(|n| n.has_tag_name(tag))
+            .map(text)
+            .ok_or_else(|| format!("missing <{tag}> element"))
+    };
+
+    let mut dependencies: Vec<String> = root
+        .children()
+        .filter(|n| DEPEND_TAGS.iter().any(|tag| n.has_tag_name(*tag)))
+        .map(text)
+        .filter(|d| !d.is_empty())
+        .collect();
+    dependencies.sort_unstable();
+    dependencies.dedup();
+
+    Ok(PackageManifest {
+        name: element("name")?,
+        version: element("version")?,
+        maintainers: root
+            .children()
+            .filter(|n| n.has_tag_name("maintainer"))
+            .map(|n| Maintainer {
+                name: text(n),
+                email: n.attribute("email").map(str::to_string),
+            })
+            .collect(),
+        dependencies,
+    })
+}
@@ -0,0 +1,114 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+// The registry as a zenohd plugin, hosted in-process by zenohd and configured in its
+// `plugins/ros2_types_registry` section (with the same options as the command line), rather than
+// running its own Zenoh runtime. Built as a dynamic library with the "dynamic_plugin" feature.
+use std::{future::Future, sync::OnceLock};
+
+use zenoh::{
+    internal::{
+        plugins::{Response, RunningPlugin, RunningPluginTrait, ZenohPlugin},
+        runtime::Runtime,
+        zerror,
+    },
+    key_expr::KeyExpr,
+};
+use zenoh_plugin_trait::{plugin_long_version, plugin_version, Plugin, PluginControl};
+
+use crate::{admin_space, args};
+
+// Number of worker threads of the Tokio runtime of the plugin, if not run within one
+const WORKER_THREADS: usize = 2;
+
+#[cfg(feature = "dynamic_plugin")]
+zenoh_plugin_trait::declare_plugin!(Ros2TypesRegistryPlugin);
+
+pub struct Ros2TypesRegistryPlugin;
+
+impl ZenohPlugin for Ros2TypesRegistryPlugin {}
+
+impl Plugin for Ros2TypesRegistryPlugin {
+    type StartArgs = Runtime;
+    type Instance = RunningPlugin;
+
+    const DEFAULT_NAME: &'static str = "ros2_types_registry";
+    const PLUGIN_VERSION: &'static str = plugin_version!();
+    const PLUGIN_LONG_VERSION: &'static str = plugin_long_version!();
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> zenoh::Result<RunningPlugin> {
+        let args = {
+            let runtime_conf = runtime.config().lock();
+            let plugin_conf = runtime_conf
+                .plugin(name)
+                .ok_or_else(|| zerror!("Plugin `{name}`: missing config"))?;
+            args::args_from_config(plugin_conf)
+                .map_err(|e| zerror!("Plugin `{name}` configuration error: {e}"))?
+        };
+        let runtime = runtime.clone();
+        spawn_runtime(async move {
+            let session = match zenoh::session::init(runtime.into()).await {
+                Ok(session) => session,
+                Err(e) => {
+                    tracing::error!("Failed to create Zenoh session: {e}");
+                    return;
+                }
+            };
+            // the logging is managed by zenohd
+            if let Err(e) = crate::run(args, session, None).await {
+                tracing::error!("The types registry stopped: {e}");
+            }
+        });
+        Ok(Box::new(Ros2TypesRegistryPlugin))
+    }
+}
+
+impl PluginControl for Ros2TypesRegistryPlugin {}
+
+impl RunningPluginTrait for Ros2TypesRegistryPlugin {
+    // The registry state in the admin space of zenohd, under the status of the plugin
+    fn adminspace_getter<'a>(
+        &'a self,
+        key_expr: &'a KeyExpr<'a>,
+        plugin_status_key: &str,
+    ) -> zenoh::Result<Vec<Response>> {
+        Ok(admin_space::responses(plugin_status_key, key_expr)
+            .into_iter()
+            .map(|(key, value)| Response::new(key, value))
+            .collect())
+    }
+}
+
+// Spawn a task on the current Tokio runtime if any (e.g. statically linked in a binary),
+// otherwise on the runtime of the plugin (e.g. dynamically loaded by zenohd)
+fn spawn_runtime<F>(task: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(task);
+        }
+        Err(_) => {
+            RUNTIME
+                .get_or_init(|| {
+                    tokio::runtime::Builder::new_multi_thread()
+                        .worker_threads(WORKER_THREADS)
+                        .enable_all()
+                        .build()
+                        .expect("Unable to create the Tokio runtime of the plugin")
+                })
+                .spawn(task);
+        }
+    }
+}
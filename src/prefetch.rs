@@ -0,0 +1,53 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Proactive prefetch of the types in use on the ROS graph: the topics and services are discovered
+// from the rmw_zenoh liveliness tokens, and the MCAP schemas and JSON descriptions of their types
+// are pre-generated, so the first request after boot (e.g. of a recorder) is served from cache.
+use zenoh::{handlers::FifoChannelHandler, pubsub::Subscriber, sample::Sample, Session};
+
+// Liveliness tokens of the rmw_zenoh entities, with format:
+// @ros2_lv/<domain_id>/<zid>/<nid>/<eid>/<kind>/<enclave>/<namespace>/<node>/<topic>/<type>/<type_hash>/<qos>
+const KE_RMW_ZENOH_LIVELINESS: &str = "@ros2_lv/**";
+
+// Kinds of the rmw_zenoh entities having a type: publishers, subscriptions, services servers
+// and clients
+const TYPED_ENTITY_KINDS: &[&str] = &["MP", "MS", "SS", "SC"];
+
+// Subscribe to the liveliness tokens of the rmw_zenoh entities (including the existing ones)
+pub(crate) async fn subscribe(
+    session: &Session,
+) -> Result<Subscriber<FifoChannelHandler<Sample>>, String> {
+    session
+        .liveliness()
+        .declare_subscriber(KE_RMW_ZENOH_LIVELINESS)
+        .history(true)
+        .await
+        .map_err(|e| format!("Failed to subscribe to rmw_zenoh liveliness tokens: {e}"))
+}
+
+// Parse a rmw_zenoh liveliness token of a topic or service entity, returning the ROS type name
+// (e.g. "std_msgs/msg/String" for "std_msgs::msg::dds_::String_")
+pub(crate) fn parse_token(ke: &str) -> Option<String> {
+    let chunks: Vec<&str> = ke.split('/').collect();
+    if chunks.len() < 12 || !TYPED_ENTITY_KINDS.contains(&chunks[5]) {
+        return None;
+    }
+    match chunks[10].split("::").collect::<Vec<_>>().as_slice() {
+        [package, kind, "dds_", name] => Some(format!(
+            "{package}/{kind}/{}",
+            name.strip_suffix('_').unwrap_or(name)
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,172 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Per-client rate limiting of the queries, with a token bucket per requester Zenoh ID, so a
+// misbehaving client looping on queries can't starve the host's CPU. The clients being told apart
+// by the source info of their queries (not sent by default), the queries without source info share
+// a single "anonymous" bucket. The queries of the local session (e.g. of the in-process HTTP and gRPC servers, which
+// carry a secret of this process in their attachment) and those providing the admin token (e.g. of
+// a mirror registry) are not limited.
+use std::{collections::HashMap, sync::OnceLock, time::Instant};
+
+use ring::rand::SystemRandom;
+use zenoh::query::Query;
+
+use crate::{
+    access_log::requester_zid,
+    auth::{self, constant_time_eq},
+    send_reply_err_details, ErrorCode, ErrorDetails,
+};
+
+// The secret of this process sent as `local=<secret>` in the attachment of the queries of its
+// in-process clients (None if it can't be generated)
+static LOCAL_SECRET: OnceLock<Option<String>> = OnceLock::new();
+
+// Maximum number of tracked clients, above which the least recently refilled buckets are dropped
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+// The bucket shared by the queries without source info
+const ANONYMOUS_CLIENT: &str = "anonymous";
+
+// The token bucket of a client
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    rate: Option<f64>, // tokens added per second to each bucket (no limit if None)
+    burst: f64,        // capacity of each bucket
+    buckets: HashMap<String, Bucket>,
+    local_zid: String, // the Zenoh ID of the local session, whose queries are not limited
+    admin_token: Option<String>, // the token of the trusted clients (e.g. mirrors), not limited
+}
+
+impl RateLimiter {
+    // Create a RateLimiter allowing `rate` queries per second per client (no limit if None or 0),
+    // with bursts of at most `burst` queries
+    pub fn new(
+        rate: Option<u32>,
+        burst: u32,
+        local_zid: String,
+        admin_token: Option<String>,
+    ) -> Self {
+        RateLimiter {
+            rate: rate.filter(|rate| *rate > 0).map(f64::from),
+            burst: f64::from(burst.max(1)),
+            buckets: HashMap::new(),
+            local_zid,
+            admin_token,
+        }
+    }
+
+    // Return true if the query can be handled, otherwise reply a `rate_limited` error with the
+    // delay after which the client can retry, and return false
+    pub async fn admit(&mut self, query: &Query) -> bool {
+        let Some(rate) = self.rate else {
+            return true;
+        };
+        if is_local(query)
+            || (self.admin_token.is_some()
+                && auth::is_authorized(query, self.admin_token.as_deref()))
+        {
+            return true;
+        }
+        let client = requester_zid(query).unwrap_or_else(|| ANONYMOUS_CLIENT.to_string());
+        if client == self.local_zid {
+            return true;
+        }
+        let now = Instant::now();
+        if self.buckets.len() >= MAX_TRACKED_CLIENTS && !self.buckets.contains_key(&client) {
+            self.evict_least_recent();
+        }
+        let bucket = self.buckets.entry(client).or_insert(Bucket {
+            tokens: self.burst,
+            last_refill: now,
+        });
+        bucket.tokens = (bucket.tokens
+            + now.duration_since(bucket.last_refill).as_secs_f64() * rate)
+            .min(self.burst);
+        bucket.last_refill = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return true;
+        }
+
+        let retry_after_ms = ((1.0 - bucket.tokens) / rate * 1000.0).ceil() as u64;
+        send_reply_err_details(
+            query,
+            ErrorCode::RateLimited,
+            format!(
+                "Rate limit exceeded ({rate} queries per second) - retry in {retry_after_ms} ms"
+            ),
+            Some(ErrorDetails {
+                retry_after_ms: Some(retry_after_ms),
+                ..Default::default()
+            }),
+        )
+        .await;
+        false
+    }
+
+    // Drop the least recently refilled bucket (but the anonymous one, shared by all the clients
+    // without source info), to track a new client
+    fn evict_least_recent(&mut self) {
+        let least_recent = self
+            .buckets
+            .iter()
+            .filter(|(client, _)| client.as_str() != ANONYMOUS_CLIENT)
+            .min_by_key(|(_, bucket)| bucket.last_refill)
+            .map(|(client, _)| client.clone());
+        if let Some(client) = least_recent {
+            self.buckets.remove(&client);
+        }
+    }
+}
+
+// Return the attachment of the queries of the in-process clients, exempting them from the rate
+// limiting (None if no secret could be generated)
+pub(crate) fn local_attachment() -> Option<String> {
+    LOCAL_SECRET
+        .get_or_init(generate_secret)
+        .as_ref()
+        .map(|secret| format!("local={secret}"))
+}
+
+fn generate_secret() -> Option<String> {
+    match ring::rand::generate::<[u8; 16]>(&SystemRandom::new()) {
+        Ok(random) => Some(random.expose().iter().map(|b| format!("{b:02x}")).collect()),
+        Err(_) => {
+            tracing::warn!("Failed to generate the secret of the local queries");
+            None
+        }
+    }
+}
+
+// Return true if the query comes from an in-process client
+fn is_local(query: &Query) -> bool {
+    let Some(Some(secret)) = LOCAL_SECRET.get() else {
+        return false;
+    };
+    query
+        .attachment()
+        .and_then(|attachment| attachment.try_to_string().ok())
+        .is_some_and(|attachment| {
+            attachment
+                .split(';')
+                .filter_map(|entry| entry.strip_prefix("local="))
+                .any(|s| constant_time_eq(s.as_bytes(), secret.as_bytes()))
+        })
+}
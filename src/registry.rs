@@ -12,22 +12,188 @@
 //
 
 use core::convert::TryFrom;
-use std::path::PathBuf;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+use serde::Serialize;
+use strum::{AsRefStr, EnumString};
 use zenoh::key_expr::{
     keyexpr,
     keyexpr_tree::{IKeyExprTree, IKeyExprTreeMut, KeBoxTree},
-    KeyExpr,
 };
 use zenoh_keyexpr::{keyexpr_tree::traits::IKeyExprTreeNode, OwnedKeyExpr};
 
 use crate::{
-    type_description::HashedTypeDescription,
+    alias::Alias,
+    definition_cache::{self, DefinitionCache},
+    field_type::FieldTypeId,
+    interner,
+    package_xml::{self, PackageManifest},
+    reply::CacheControl,
+    snapshot::SnapshotEntry,
+    type_description::{self, IndividualTypeDescription, TypeSource, MAX_NESTING_DEPTH},
     type_info::{TypeInfo, TypeKind},
+    type_name,
 };
 
+// Order of the dependencies definitions appended to a type definition in a MCAP schema
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, AsRefStr, EnumString)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub(crate) enum McapDependencyOrder {
+    #[default]
+    Rosbag2, // the order in which rosbag2 discovers them (depth-first)
+    Lexicographic, // the lexicographic order of their names
+}
+
+// Files and directories ignored when loading types: hidden ones and editors temporary files
+pub(crate) const DEFAULT_IGNORE_PATTERNS: &[&str] = &[".*", "*~", "#*#", "*.swp", "*.bak"];
+
+// Options of the walk of the directories when loading types
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WalkOptions {
+    // follow the symbolic links to directories and files
+    pub follow_links: bool,
+    // maximum depth of the walk in each directory (no limit if None)
+    pub max_depth: Option<usize>,
+    // don't walk into other file systems than the one of each directory
+    pub same_file_system: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        WalkOptions {
+            follow_links: true,
+            max_depth: None,
+            same_file_system: false,
+        }
+    }
+}
+
+// Default time (in seconds) during which the replies can be reused by the clients caches
+pub(crate) const DEFAULT_CACHE_MAX_AGE: u64 = 300;
+
+// A superseded or removed version of a type, valid from the `since` epoch until the `until` one
+pub(crate) struct TypeVersion {
+    pub type_info: TypeInfo,
+    pub since: u64,
+    pub until: u64,
+}
+
+// State of a registry paused for maintenance (e.g. during a workspace upgrade)
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Maintenance {
+    pub since: u64, // time of the pause (in seconds since UNIX epoch)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+// A reference of a loaded type to a type not found in the registry
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub(crate) struct DanglingReference {
+    pub type_name: String, // the referencing type
+    pub missing: String,   // the referenced type, not found
+    // the field referencing it (e.g. "my_msgs/msg/Foo_Request.pose"), or None if only listed in the
+    // referenced_type_descriptions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+}
+
+// Changes of the registry content after a rescan of its directories
+#[derive(Debug, Default, Clone, Serialize)]
+pub(crate) struct ContentChanges {
+    pub added: usize,   // number of new types
+    pub changed: usize, // number of types with a different type hash
+    pub removed: usize, // number of types not found anymore
+}
+
+// Retention policy of the types history (no limit if None)
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct HistoryRetention {
+    pub max_versions: Option<usize>, // maximum number of superseded versions kept per type
+    pub max_age: Option<u64>,        // maximum time (in seconds) since a version was superseded
+    pub max_memory: Option<usize>,   // maximum memory used by the history (in bytes)
+}
+
+// Statistics on the garbage collection runs of the types history
+#[derive(Debug, Default, Clone, Serialize)]
+pub(crate) struct HistoryGcStats {
+    pub runs: u64,             // number of GC runs
+    pub last_run: u64,         // time of the last GC run (in seconds since UNIX epoch)
+    pub removed_versions: u64, // total number of versions removed by the GC runs
+    pub freed_memory: u64,     // total memory freed by the GC runs (in bytes)
+    pub versions: usize,       // number of versions currently in history
+    pub memory: usize,         // memory currently used by the history (in bytes)
+}
+
 pub(crate) struct Registry<'a> {
     types: KeBoxTree<TypeInfo>,
+    // secondary indexes: type hash -> type name, package name -> type names, and referenced type
+    // name (original) -> names of the loaded types referencing it
+    types_by_hash: HashMap<String, OwnedKeyExpr>,
+    types_by_package: BTreeMap<String, Vec<OwnedKeyExpr>>,
+    dependents: HashMap<Arc<str>, Vec<OwnedKeyExpr>>,
+    // manifests parsed from the package.xml files of the packages loaded from directories
+    // (None if not found or invalid)
+    package_manifests: BTreeMap<String, Option<PackageManifest>>,
+    // in lazy mode, the definitions are not kept in memory but read on demand via this cache
+    lazy_definitions: Option<DefinitionCache>,
+    // in warm-up mode, the types files are only indexed at startup and parsed later;
+    // this is the list of files per type name waiting to be parsed
+    warm_up: bool,
+    pending: BTreeMap<String, Vec<(PathBuf, TypeKind)>>,
+    // in bounded-memory mode, the least recently used types are evicted (to be re-parsed
+    // on demand) when the estimated memory used exceeds the budget
+    memory_budget: Option<usize>,
+    evicted: BTreeMap<String, Vec<(PathBuf, TypeKind)>>,
+    memory_used: usize,
+    // total size of the memoized replies of the loaded types (counted by the types when memoized)
+    memoized_bytes: Arc<AtomicUsize>,
+    access_clock: AtomicU64,
+    // names patterns of the files and directories ignored when loading types
+    ignore_patterns: Vec<String>,
+    walk_options: WalkOptions,
+    // canonical paths of the directories already walked, not walked again when reached via
+    // another path (e.g. a symbolic link to a share directory, or a symbolic links cycle)
+    walked_dirs: HashSet<PathBuf>,
+    // reject the JSON files with unknown fields, rather than ignoring those fields
+    strict_json: bool,
+    mcap_dependency_order: McapDependencyOrder,
+    // epoch of the content (time of its last change, in seconds since UNIX epoch) and time during
+    // which the replies can be reused by the clients caches (in seconds)
+    epoch: u64,
+    cache_max_age: u64,
+    // epoch of the last change of each type (by type name), with its type hash (None while the
+    // type is only indexed), to reply only the types changed since a given epoch
+    type_epochs: HashMap<String, (Option<String>, u64)>,
+    // superseded or removed versions of the types (by type name), oldest first
+    history: HashMap<OwnedKeyExpr, Vec<TypeVersion>>,
+    // number of versions of each type (by type name) removed from history by the GC
+    history_dropped: HashMap<OwnedKeyExpr, usize>,
+    history_retention: HistoryRetention,
+    history_gc_stats: HistoryGcStats,
+    // key namespace of the tenant served by this registry, if any
+    namespace: Option<OwnedKeyExpr>,
+    // aliases of types or packages, served with their target types
+    aliases: Vec<Alias>,
+    // key expressions of the types which are loaded but not served (e.g. proprietary types)
+    denied: Vec<OwnedKeyExpr>,
+    // other versions of the types found in lower-priority directories (e.g. an underlay of
+    // AMENT_PREFIX_PATH), overlaid by the first loaded version (by type name)
+    overlaid: HashMap<OwnedKeyExpr, Vec<TypeInfo>>,
+    // while paused for maintenance, the types aren't served
+    maintenance: Option<Maintenance>,
+    // references to types not found in the registry, found by the last check_references(), and
+    // if the registry isn't ready while there are some
+    dangling_references: Vec<DanglingReference>,
+    strict_dependencies: bool,
     size: usize,
     _marker: std::marker::PhantomData<&'a TypeInfo>,
 }
@@ -36,26 +202,179 @@ impl<'a> Registry<'a> {
     pub fn new() -> Self {
         Self {
             types: KeBoxTree::new(),
+            types_by_hash: HashMap::new(),
+            types_by_package: BTreeMap::new(),
+            dependents: HashMap::new(),
+            package_manifests: BTreeMap::new(),
+            lazy_definitions: None,
+            warm_up: false,
+            pending: BTreeMap::new(),
+            memory_budget: None,
+            evicted: BTreeMap::new(),
+            memory_used: 0,
+            memoized_bytes: Arc::default(),
+            access_clock: AtomicU64::new(0),
+            ignore_patterns: DEFAULT_IGNORE_PATTERNS
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            walk_options: WalkOptions::default(),
+            walked_dirs: HashSet::new(),
+            strict_json: false,
+            mcap_dependency_order: McapDependencyOrder::default(),
+            epoch: 0,
+            cache_max_age: DEFAULT_CACHE_MAX_AGE,
+            type_epochs: HashMap::new(),
+            history: HashMap::new(),
+            history_dropped: HashMap::new(),
+            history_retention: HistoryRetention::default(),
+            history_gc_stats: HistoryGcStats::default(),
+            namespace: None,
+            aliases: Vec::new(),
+            denied: Vec::new(),
+            overlaid: HashMap::new(),
+            maintenance: None,
+            dangling_references: Vec::new(),
+            strict_dependencies: false,
             size: 0,
             _marker: std::marker::PhantomData,
         }
     }
 
+    // Don't keep the definitions contents in memory, but read them on demand,
+    // caching at most `cache_size` of them
+    pub fn with_lazy_definitions(mut self, cache_size: usize) -> Self {
+        self.lazy_definitions = Some(DefinitionCache::new(cache_size));
+        self
+    }
+
+    // Only index the types files when loading directories, parsing them later either
+    // progressively via warm_up_step(), or on demand when a matching type is queried
+    pub fn with_warm_up(mut self) -> Self {
+        self.warm_up = true;
+        self
+    }
+
+    // Evict the least recently used types when the estimated memory used exceeds the budget
+    // (in bytes). The evicted types are re-parsed from disk on demand.
+    pub fn with_memory_budget(mut self, memory_budget: usize) -> Self {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+
+    // Also ignore the files and directories with a name matching one of those patterns
+    // when loading types (with '*' matching any characters and '?' any single character)
+    pub fn with_ignore_patterns(mut self, patterns: impl IntoIterator<Item = String>) -> Self {
+        self.ignore_patterns.extend(patterns);
+        self
+    }
+
+    // Set the options of the walk of the directories when loading types
+    pub fn with_walk_options(mut self, options: WalkOptions) -> Self {
+        self.walk_options = options;
+        self
+    }
+
+    // Don't declare the registry ready while some loaded types reference types not found in it
+    pub fn with_strict_dependencies(mut self) -> Self {
+        self.strict_dependencies = true;
+        self
+    }
+
+    // Reject the JSON files with unknown fields, rather than ignoring those fields with a warning
+    pub fn with_strict_json(mut self) -> Self {
+        self.strict_json = true;
+        self
+    }
+
+    // Limit the superseded versions of the types kept in history
+    pub fn with_history_retention(mut self, retention: HistoryRetention) -> Self {
+        self.history_retention = retention;
+        self
+    }
+
+    // Set the order of the dependencies definitions in the generated MCAP schemas
+    pub fn with_mcap_dependency_order(mut self, order: McapDependencyOrder) -> Self {
+        self.mcap_dependency_order = order;
+        self
+    }
+
+    // Set the time (in seconds) during which the replies can be reused by the clients caches
+    pub fn with_cache_max_age(mut self, max_age: u64) -> Self {
+        self.cache_max_age = max_age;
+        self
+    }
+
+    // Serve the types under this key namespace (e.g. "robot_a" for "robot_a/@ros2_types/**")
+    pub fn with_namespace(mut self, namespace: OwnedKeyExpr) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    // Serve the queries on those aliases with their target types
+    pub fn with_aliases(mut self, aliases: impl IntoIterator<Item = Alias>) -> Self {
+        self.aliases.extend(aliases);
+        self
+    }
+
+    // Don't serve the types matching one of those key expressions (e.g. "proprietary_msgs/**"),
+    // even if loaded
+    pub fn with_denied_types(mut self, patterns: impl IntoIterator<Item = OwnedKeyExpr>) -> Self {
+        self.denied.extend(patterns);
+        self
+    }
+
+    #[tracing::instrument(skip_all, fields(dir = %dir.display()))]
     pub fn load_types_from_dir(&mut self, dir: &PathBuf) {
         tracing::debug!("Loading types from {}", dir.display());
 
         let mut count = 0usize;
-        for entry in walkdir::WalkDir::new(dir)
-            .follow_links(true)
+        // the ignore patterns and walked directories are taken to not borrow self during the walk
+        let ignore_patterns = self.ignore_patterns.clone();
+        let mut walked_dirs = std::mem::take(&mut self.walked_dirs);
+        let mut walker = walkdir::WalkDir::new(dir)
+            .follow_links(self.walk_options.follow_links)
+            .same_file_system(self.walk_options.same_file_system);
+        if let Some(max_depth) = self.walk_options.max_depth {
+            walker = walker.max_depth(max_depth);
+        }
+        let entries: Vec<walkdir::DirEntry> = walker
             .into_iter()
-            .filter_map(|e| {
-                if let Err(err) = &e {
+            .filter_entry(|e| {
+                if e.depth() > 0 && is_ignored(e.file_name(), &ignore_patterns) {
+                    return false;
+                }
+                if !e.file_type().is_dir() {
+                    return true;
+                }
+                match std::fs::canonicalize(e.path()) {
+                    Ok(canonical) if !walked_dirs.insert(canonical.clone()) => {
+                        tracing::debug!(
+                            "Skip {}: {} already walked",
+                            e.path().display(),
+                            canonical.display()
+                        );
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .filter_map(|e| match e {
+                Ok(e) => Some(e),
+                Err(err) if err.loop_ancestor().is_some() => {
+                    tracing::debug!("Skip symbolic links cycle: {err}");
+                    None
+                }
+                Err(err) => {
                     tracing::warn!("Error accessing entry: {err}");
+                    None
                 }
-                e.ok()
             })
-            .filter(|e| e.path().is_file())
-        {
+            // the type of the target of the symbolic links if followed, of the links otherwise
+            .filter(|e| e.file_type().is_file())
+            .collect();
+        self.walked_dirs = walked_dirs;
+        for entry in entries {
             if let Some(extension) = entry.path().extension() {
                 let kind = if extension == "msg" {
                     TypeKind::MSG
@@ -66,15 +385,615 @@ impl<'a> Registry<'a> {
                 } else {
                     continue;
                 };
+                self.load_package_manifest(entry.path());
+
+                if self.warm_up {
+                    if let Some(type_name) = type_name_from_path(entry.path(), &kind) {
+                        let epoch = self.next_epoch();
+                        self.type_epochs
+                            .entry(type_name.clone())
+                            .or_insert((None, epoch));
+                        self.pending
+                            .entry(type_name)
+                            .or_default()
+                            .push((entry.path().into(), kind));
+                        count += 1;
+                        continue;
+                    }
+                }
 
                 match self.load_type_from_file(entry.path().into(), kind) {
-                    Ok(()) => count += 1,
+                    Ok(()) => {
+                        count += 1;
+                        self.size += 1;
+                    }
                     Err(e) => tracing::warn!("  {e}"),
                 }
             }
         }
-        tracing::info!("{} types loaded from {}", count, dir.display());
-        self.size += count;
+        if count > 0 {
+            self.update_epoch();
+        }
+        if self.warm_up {
+            tracing::info!("{} types indexed from {}", count, dir.display());
+        } else {
+            tracing::info!("{} types loaded from {}", count, dir.display());
+        }
+    }
+
+    // Parse the package.xml of the package of a definition file, if not done yet
+    // (e.g. "<prefix>/share/std_msgs/package.xml" for "<prefix>/share/std_msgs/msg/String.msg")
+    fn load_package_manifest(&mut self, definition_path: &std::path::Path) {
+        let Some(package_dir) = definition_path.parent().and_then(|p| p.parent()) else {
+            return;
+        };
+        let Some(package) = package_dir.file_name().and_then(|n| n.to_str()) else {
+            return;
+        };
+        if !self.package_manifests.contains_key(package) {
+            let manifest = package_xml::read(package_dir)
+                .map_err(|e| tracing::debug!("{e}"))
+                .ok();
+            self.package_manifests.insert(package.to_string(), manifest);
+        }
+    }
+
+    // Get the manifest of a package, parsed from its package.xml
+    pub fn get_package_manifest(&self, package: &str) -> Option<&PackageManifest> {
+        self.package_manifests.get(package)?.as_ref()
+    }
+
+    // Return the key namespace under which the types are served, if any
+    pub fn get_namespace(&self) -> Option<&keyexpr> {
+        self.namespace.as_deref()
+    }
+
+    // If the selection of types is on an alias, return this alias and the selection of its target
+    // types (the first declared alias applying)
+    pub fn get_alias(&self, selection: &keyexpr) -> Option<(Alias, OwnedKeyExpr)> {
+        self.aliases.iter().find_map(|alias| {
+            alias
+                .resolve(selection)
+                .map(|target| (alias.clone(), target))
+        })
+    }
+
+    // Return true if the type can be served (i.e. it doesn't match a denied key expression)
+    pub fn is_served(&self, t: &TypeInfo) -> bool {
+        !self.denied.iter().any(|ke| ke.includes(&t.full_name))
+    }
+
+    // Pause serving the types for maintenance (or update the reason of the current pause)
+    pub fn pause(&mut self, reason: Option<String>) -> &Maintenance {
+        let since = self.maintenance.as_ref().map_or_else(
+            || {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default()
+            },
+            |m| m.since,
+        );
+        self.maintenance.insert(Maintenance { since, reason })
+    }
+
+    // Resume serving the types, returning the maintenance which ended, if any
+    pub fn resume(&mut self) -> Option<Maintenance> {
+        self.maintenance.take()
+    }
+
+    // Return the current maintenance, if paused
+    pub fn get_maintenance(&self) -> Option<&Maintenance> {
+        self.maintenance.as_ref()
+    }
+
+    // Return the cache-control metadata of the replies on the current content
+    pub fn get_cache_control(&self) -> CacheControl {
+        CacheControl {
+            epoch: self.epoch,
+            max_age: self.cache_max_age,
+        }
+    }
+
+    // Load all the types of a snapshot, keeping their definitions in memory (the original
+    // files may not exist on this host). Return the number of types loaded.
+    pub fn load_types_from_snapshot(&mut self, entries: Vec<SnapshotEntry>) -> usize {
+        let mut count = 0usize;
+        for entry in entries {
+            let Ok(kind) = TypeKind::try_from(entry.kind.as_str()) else {
+                tracing::warn!(
+                    "  Invalid kind '{}' of {} in snapshot",
+                    entry.kind,
+                    entry.type_name
+                );
+                continue;
+            };
+            match self.load_type_from_json(
+                &entry.description,
+                kind,
+                Some(interner::intern(&entry.definition)),
+                entry.json_path,
+                entry.definition_path,
+            ) {
+                Ok(()) => {
+                    count += 1;
+                    self.size += 1;
+                }
+                Err(e) => tracing::warn!("  {e}"),
+            }
+            if self
+                .get_type(&entry.type_name)
+                .is_some_and(|t| t.type_hash != entry.type_hash)
+            {
+                tracing::warn!(
+                    "  Type hash of {} differs from the one recorded in snapshot ({})",
+                    entry.type_name,
+                    entry.type_hash
+                );
+            }
+        }
+        if count > 0 {
+            self.update_epoch();
+        }
+        count
+    }
+
+    // Remove a type (e.g. not in a mirrored registry anymore, or before loading its new version),
+    // keeping it in the history of the type if loaded. Return true if it was loaded, or waiting to
+    // be parsed (pending or evicted, and then not re-parsed anymore).
+    pub fn remove_type(&mut self, type_name: &str) -> bool {
+        self.remove_escaped_type(&type_name::escape(type_name))
+    }
+
+    // Remove a type by its escaped name (i.e. its key expression, as in `type_epochs`)
+    fn remove_escaped_type(&mut self, type_name: &str) -> bool {
+        let unparsed =
+            self.pending.remove(type_name).is_some() | self.evicted.remove(type_name).is_some();
+        let Ok(ke) = keyexpr::new(type_name) else {
+            return unparsed;
+        };
+        let Some(mut t) = self.types.remove(ke) else {
+            // an evicted type keeps its hash entry and package entry, to be re-parsed on demand
+            if unparsed {
+                if let Some((Some(hash), _)) = self.type_epochs.remove(ke.as_str()) {
+                    self.types_by_hash.remove(&hash);
+                }
+                if let Some(package) = ke.as_str().split('/').next() {
+                    self.remove_from_package(package, ke);
+                }
+            }
+            return unparsed;
+        };
+        self.memory_used -= t.memory_size;
+        // the memoized replies of the versions in history aren't counted in the memory used
+        self.memoized_bytes
+            .fetch_sub(t.memoized_size(), Ordering::Relaxed);
+        t.memoized_bytes = Arc::default();
+        // the definition is kept in memory, as the file may be replaced by the new version
+        if t.definition_content.is_none() {
+            match self.get_definition_content(&t) {
+                Ok(content) => {
+                    t.memory_size += content.len();
+                    t.definition_content = Some(content);
+                }
+                Err(e) => tracing::warn!("Definition of {type_name} not kept in history: {e}"),
+            }
+        }
+        self.invalidate_dependents(t.get_type_name());
+        self.remove_from_dependents(&t);
+        let since = self.get_type_epoch(&t);
+        let until = self.next_epoch();
+        self.types_by_hash.remove(&t.type_hash);
+        self.remove_from_package(&t.package_name, &t.full_name);
+        self.type_epochs.remove(t.full_name.as_str());
+        self.history_gc_stats.versions += 1;
+        self.history_gc_stats.memory += t.memory_size;
+        self.size -= 1;
+        self.update_epoch();
+        self.history
+            .entry(t.full_name.clone())
+            .or_default()
+            .push(TypeVersion {
+                type_info: t,
+                since,
+                until,
+            });
+        true
+    }
+
+    // Remove a type from the types of its package (and the package if it has no types anymore)
+    fn remove_from_package(&mut self, package: &str, type_name: &keyexpr) {
+        if let Some(package_types) = self.types_by_package.get_mut(package) {
+            package_types.retain(|n| **n != *type_name);
+            if package_types.is_empty() {
+                self.types_by_package.remove(package);
+            }
+        }
+    }
+
+    // Forget the memoized MCAP schemas of the types depending on a type (thus containing its
+    // definition), when this type is removed or replaced
+    fn invalidate_dependents(&mut self, type_name: &str) {
+        let Some(dependents) = self.dependents.get(type_name) else {
+            return;
+        };
+        let memoized: Vec<OwnedKeyExpr> = dependents
+            .iter()
+            .filter(|name| {
+                self.types
+                    .weight_at(name)
+                    .is_some_and(|t| t.mcap_schema.get().is_some())
+            })
+            .cloned()
+            .collect();
+        for name in memoized {
+            if let Some(mut t) = self.types.remove(&name) {
+                if let Some(schema) = t.mcap_schema.take() {
+                    self.memoized_bytes
+                        .fetch_sub(schema.len(), Ordering::Relaxed);
+                }
+                self.types.insert(&name, t);
+            }
+        }
+    }
+
+    // Record a loaded type as dependent of the types it references
+    fn add_to_dependents(&mut self, t: &TypeInfo) {
+        for d in &t
+            .type_description
+            .type_description_msg
+            .referenced_type_descriptions
+        {
+            self.dependents
+                .entry(d.type_name.clone())
+                .or_default()
+                .push(t.full_name.clone());
+        }
+    }
+
+    // Forget a type unloaded (removed or evicted) as dependent of the types it references
+    fn remove_from_dependents(&mut self, t: &TypeInfo) {
+        for d in &t
+            .type_description
+            .type_description_msg
+            .referenced_type_descriptions
+        {
+            if let Some(dependents) = self.dependents.get_mut(&d.type_name) {
+                dependents.retain(|n| *n != t.full_name);
+                if dependents.is_empty() {
+                    self.dependents.remove(&d.type_name);
+                }
+            }
+        }
+    }
+
+    // Replace the content of the registry with the one of another registry loaded from the same
+    // directories (i.e. a rescan), keeping the history (with the changed and removed types), the
+    // epochs of the unchanged types and the maintenance state. Return the changes of the content.
+    pub fn replace_content(&mut self, mut other: Registry<'a>) -> ContentChanges {
+        let mut changes = ContentChanges {
+            added: other
+                .type_epochs
+                .keys()
+                .filter(|name| !self.type_epochs.contains_key(*name))
+                .count(),
+            ..Default::default()
+        };
+        let current: Vec<(String, Option<String>, u64)> = self
+            .type_epochs
+            .iter()
+            .map(|(name, (hash, epoch))| (name.clone(), hash.clone(), *epoch))
+            .collect();
+        let mut unchanged = HashSet::new();
+        for (name, hash, epoch) in current {
+            // in warm-up mode, parse the rescanned type to compare it with the parsed one
+            if hash.is_some()
+                && other
+                    .type_epochs
+                    .get(&name)
+                    .is_some_and(|(other_hash, _)| other_hash.is_none())
+            {
+                other.load_pending(&name);
+            }
+            match other.type_epochs.get_mut(&name) {
+                Some((other_hash, other_epoch)) if *other_hash == hash => {
+                    *other_epoch = epoch;
+                    unchanged.insert(name);
+                }
+                Some(_) => {
+                    changes.changed += 1;
+                    self.remove_escaped_type(&name);
+                }
+                None => {
+                    changes.removed += 1;
+                    self.remove_escaped_type(&name);
+                }
+            }
+        }
+
+        // the added and changed types get the epoch of this change, always after the current one
+        if changes.added + changes.changed + changes.removed > 0 {
+            self.update_epoch();
+        }
+        for (name, (_, epoch)) in other.type_epochs.iter_mut() {
+            if !unchanged.contains(name) {
+                *epoch = self.epoch;
+            }
+        }
+        other.epoch = self.epoch;
+        other.history = std::mem::take(&mut self.history);
+        other.history_dropped = std::mem::take(&mut self.history_dropped);
+        other.history_gc_stats = std::mem::take(&mut self.history_gc_stats);
+        other.maintenance = self.maintenance.take();
+        *self = other;
+        changes
+    }
+
+    // Remove the versions of the types history exceeding the retention policy (the oldest
+    // superseded ones first), returning the number of removed versions
+    pub fn collect_history_garbage(&mut self) -> usize {
+        let retention = self.history_retention;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut removed: Vec<TypeVersion> = Vec::new();
+        for versions in self.history.values_mut() {
+            if let Some(max_versions) = retention.max_versions {
+                let excess = versions.len().saturating_sub(max_versions);
+                removed.extend(versions.drain(..excess));
+            }
+            if let Some(max_age) = retention.max_age {
+                let expired = versions
+                    .iter()
+                    .take_while(|v| v.until + max_age < now)
+                    .count();
+                removed.extend(versions.drain(..expired));
+            }
+        }
+        if let Some(max_memory) = retention.max_memory {
+            let mut memory = self.history_gc_stats.memory
+                - removed
+                    .iter()
+                    .map(|v| v.type_info.memory_size)
+                    .sum::<usize>();
+            while memory > max_memory {
+                // the version superseded first
+                let Some(oldest) = self
+                    .history
+                    .values_mut()
+                    .filter(|versions| !versions.is_empty())
+                    .min_by_key(|versions| versions[0].until)
+                else {
+                    break;
+                };
+                let version = oldest.remove(0);
+                memory -= version.type_info.memory_size;
+                removed.push(version);
+            }
+        }
+        self.history.retain(|_, versions| !versions.is_empty());
+        for version in &removed {
+            *self
+                .history_dropped
+                .entry(version.type_info.full_name.clone())
+                .or_default() += 1;
+        }
+
+        let freed: usize = removed.iter().map(|v| v.type_info.memory_size).sum();
+        let stats = &mut self.history_gc_stats;
+        stats.runs += 1;
+        stats.last_run = now;
+        stats.removed_versions += removed.len() as u64;
+        stats.freed_memory += freed as u64;
+        stats.versions -= removed.len();
+        stats.memory -= freed;
+        if !removed.is_empty() {
+            tracing::debug!(
+                "History GC: {} versions removed ({freed} bytes freed)",
+                removed.len()
+            );
+        }
+        removed.len()
+    }
+
+    // Return the statistics on the garbage collection runs of the types history
+    pub fn get_history_gc_stats(&self) -> &HistoryGcStats {
+        &self.history_gc_stats
+    }
+
+    // Return the superseded or removed versions of a type, oldest first
+    pub fn get_type_history(&self, t: &TypeInfo) -> &[TypeVersion] {
+        self.history
+            .get(&t.full_name)
+            .map_or(&[], |versions| versions.as_slice())
+    }
+
+    // Return the versions of a type (the superseded or removed ones, oldest first, followed by the
+    // current one if any), with the number of older versions removed from history by the GC
+    pub fn get_type_versions(&self, type_name: &keyexpr) -> (usize, Vec<&TypeInfo>) {
+        let mut versions: Vec<&TypeInfo> = self
+            .history
+            .get(type_name)
+            .map(|versions| versions.iter().map(|v| &v.type_info).collect())
+            .unwrap_or_default();
+        if let Some(current) = self.types.weight_at(type_name) {
+            self.touch(current);
+            versions.push(current);
+        }
+        let dropped = self.history_dropped.get(type_name).copied().unwrap_or(0);
+        (dropped, versions)
+    }
+
+    // Return the names of the types having superseded or removed versions in history
+    pub fn iter_history_names(&self) -> impl Iterator<Item = &keyexpr> {
+        self.history.keys().map(|name| &**name)
+    }
+
+    // Return the versions of the types matching a key expression that were current at an epoch
+    // (i.e. the current versions added before this epoch, and the superseded or removed ones)
+    pub fn get_types_at<'b>(&'b self, ke: &'b keyexpr, at: u64) -> Vec<&'b TypeInfo> {
+        let mut types: Vec<&TypeInfo> = self
+            .iter_types(ke)
+            .filter(|t| self.get_type_epoch(t) <= at)
+            .collect();
+        types.extend(
+            self.history
+                .iter()
+                .filter(|(name, _)| ke.includes(name))
+                .filter_map(|(_, versions)| {
+                    versions
+                        .iter()
+                        .find(|v| v.since <= at && at < v.until)
+                        .map(|v| &v.type_info)
+                }),
+        );
+        types.sort_unstable_by(|a, b| a.full_name.cmp(&b.full_name));
+        types
+    }
+
+    // Return the epoch of the last change of a type (i.e. when it was added or its hash changed)
+    pub fn get_type_epoch(&self, t: &TypeInfo) -> u64 {
+        self.type_epochs
+            .get(t.full_name.as_str())
+            .map_or(self.epoch, |(_, epoch)| *epoch)
+    }
+
+    // Record a change of the content, setting the epoch to the current time
+    // (always increasing, even with several changes in the same second)
+    fn update_epoch(&mut self) {
+        self.epoch = self.next_epoch();
+    }
+
+    // Return the epoch of the next change of the content
+    fn next_epoch(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+            .max(self.epoch + 1)
+    }
+
+    // Record the epoch of a type being inserted, unless it's the same version as the recorded
+    // one (i.e. an indexed type being parsed, or an evicted type being re-parsed)
+    fn record_type_epoch(&mut self, t: &TypeInfo) {
+        let epoch = self.next_epoch();
+        match self.type_epochs.get_mut(t.full_name.as_str()) {
+            Some((hash @ None, _)) => *hash = Some(t.type_hash.clone()),
+            Some((Some(hash), _)) if *hash == t.type_hash => {}
+            _ => {
+                self.type_epochs
+                    .insert(t.full_name.to_string(), (Some(t.type_hash.clone()), epoch));
+            }
+        }
+    }
+
+    // Return the number of indexed types waiting to be parsed
+    pub fn get_pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Parse up to `n` pending types (with their dependencies), returning the number of types parsed
+    pub fn warm_up_step(&mut self, n: usize) -> usize {
+        let mut count = 0;
+        while count < n {
+            let Some(type_name) = self.pending.keys().next().cloned() else {
+                break;
+            };
+            count += self.load_pending(&type_name);
+        }
+        count
+    }
+
+    // Parse the pending or evicted types matching a key expression, and the pending or evicted
+    // dependencies of the matching types (including the loaded ones)
+    pub fn load_pending_matching(&mut self, ke: &keyexpr) {
+        if self.pending.is_empty() && self.evicted.is_empty() {
+            return;
+        }
+        let matching: Vec<String> = self
+            .pending
+            .keys()
+            .chain(self.evicted.keys())
+            .filter(|name| keyexpr::new(name.as_str()).map_or(false, |n| ke.intersects(n)))
+            .cloned()
+            .chain(
+                self.types
+                    .included_nodes(ke)
+                    .filter_map(|n| n.weight())
+                    .map(|t| t.full_name.to_string()),
+            )
+            .collect();
+        for type_name in matching {
+            self.load_pending(&type_name);
+        }
+    }
+
+    // Parse a type (if pending or evicted) and pre-generate its MCAP schema and JSON descriptions,
+    // returning true if the type is in the registry
+    pub fn prefetch(&mut self, type_name: &str) -> bool {
+        self.load_pending(&type_name::escape(type_name));
+        let Some(t) = self.get_type(type_name) else {
+            return false;
+        };
+        if let Err(e) = self
+            .get_mcap_schema(t)
+            .and_then(|_| t.get_description_json())
+            .and_then(|_| t.get_full_description_json())
+        {
+            tracing::debug!("Failed to prefetch {type_name}: {e}");
+        }
+        true
+    }
+
+    // Parse the pending or evicted type with this hash (and its pending or evicted dependencies),
+    // returning false if the hash is unknown (e.g. of a type still pending in warm-up mode)
+    pub fn load_pending_by_hash(&mut self, type_hash: &str) -> bool {
+        match self.types_by_hash.get(type_hash).cloned() {
+            Some(type_name) => {
+                self.load_pending(type_name.as_str());
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Parse a pending or evicted type and its pending or evicted dependencies (even if the type
+    // itself is loaded), returning the number of types parsed
+    fn load_pending(&mut self, type_name: &str) -> usize {
+        let mut count = 0;
+        let files = self
+            .pending
+            .remove(type_name)
+            .or_else(|| self.evicted.remove(type_name))
+            .unwrap_or_default();
+        for (path, kind) in files {
+            match self.load_type_from_file(path, kind) {
+                Ok(()) => {
+                    count += 1;
+                    self.size += 1;
+                }
+                Err(e) => tracing::warn!("  {e}"),
+            }
+        }
+        // the referenced types are all the (transitive) dependencies of the type
+        let dependencies: Vec<String> = keyexpr::new(type_name)
+            .ok()
+            .and_then(|ke| self.types.weight_at(ke))
+            .map(|t| {
+                t.type_description
+                    .type_description_msg
+                    .referenced_type_descriptions
+                    .iter()
+                    .map(|d| type_name::escape(&d.type_name).into_owned())
+                    .filter(|dep| self.is_unparsed(dep))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for dep in dependencies {
+            count += self.load_pending(&dep);
+        }
+        count
     }
 
     pub fn load_type_from_file(
@@ -92,16 +1011,68 @@ impl<'a> Registry<'a> {
         }
         let json_str = std::fs::read_to_string(&json_path)
             .map_err(|e| format!("Failed to read JSON file {}: {}", json_path.display(), e))?;
-        let type_description: HashedTypeDescription = serde_json::from_str(&json_str)
+
+        // Read the definition file content, unless in lazy mode
+        let definition_content = match self.lazy_definitions {
+            Some(_) => None,
+            None => Some(interner::intern(&definition_cache::read_definition(
+                &definition_path,
+            )?)),
+        };
+
+        self.load_type_from_json(
+            &json_str,
+            kind,
+            definition_content,
+            json_path,
+            definition_path,
+        )
+    }
+
+    // Load a type from the content of its JSON file, with its definition content if already read
+    // (otherwise read on demand from `definition_path`)
+    pub fn load_type_from_json(
+        &mut self,
+        json_str: &str,
+        kind: TypeKind,
+        definition_content: Option<Arc<str>>,
+        json_path: PathBuf,
+        definition_path: PathBuf,
+    ) -> Result<(), String> {
+        let (type_description, unknown_fields) = type_description::from_json(json_str)
             .map_err(|e| format!("Failed to parse JSON file {}: {}", json_path.display(), e))?;
+        if !unknown_fields.is_empty() {
+            if self.strict_json {
+                return Err(format!(
+                    "Unknown fields in JSON file {}: {:?}",
+                    json_path.display(),
+                    unknown_fields
+                ));
+            }
+            tracing::warn!(
+                "Ignored unknown fields in JSON file {}: {:?}",
+                json_path.display(),
+                unknown_fields
+            );
+        }
+        let violations = type_description.type_description_msg.check_field_types();
+        if !violations.is_empty() {
+            return Err(format!(
+                "Inconsistent field types in JSON file {}: {:?}",
+                json_path.display(),
+                violations
+            ));
+        }
 
-        // Get this type name
+        // Get this type name, as a key expression
         let type_name = OwnedKeyExpr::try_from(
-            type_description
-                .type_description_msg
-                .type_description
-                .type_name
-                .clone(),
+            type_name::escape(
+                &type_description
+                    .type_description_msg
+                    .type_description
+                    .type_name,
+            )
+            .into_owned(),
         )
         .map_err(|e| {
             format!(
@@ -115,16 +1086,11 @@ impl<'a> Registry<'a> {
             )
         })?;
 
-        // Read the definition file content
-        let definition_content = std::fs::read_to_string(&definition_path).map_err(|e| {
-            format!(
-                "Failed to read definition file {}: {}",
-                definition_path.display(),
-                e
-            )
-        })?;
-
-        let type_info = TypeInfo::new(
+        let memory_size = json_str.len()
+            + definition_content
+                .as_ref()
+                .map_or(0, |content| content.len());
+        let mut type_info = TypeInfo::new(
             type_name,
             kind,
             type_description,
@@ -132,6 +1098,11 @@ impl<'a> Registry<'a> {
             json_path,
             definition_path,
         )?;
+        type_info.memory_size = memory_size;
+        type_info.package_version = self
+            .get_package_manifest(&type_info.package_name)
+            .map(|manifest| manifest.version.clone());
+        self.touch(&type_info);
 
         // Check if already loaded
         if let Some(existing) = self.types.weight_at(&type_info.full_name) {
@@ -139,8 +1110,18 @@ impl<'a> Registry<'a> {
                 // Already loaded, same version - skip
                 return Ok(());
             } else {
-                return Err(format!("Found conflicting hash for {} loaded from {} : see {}. Check types definitions!",
-                    type_info.full_name, existing.json_path.display(), type_info.json_path.display()));
+                let error = format!("Found conflicting hash for {} loaded from {} : see {}, only served with all_versions. Check types definitions!",
+                    type_info.full_name, existing.json_path.display(), type_info.json_path.display());
+                // keep this version as overlaid by the loaded one, if not already (e.g. reloaded
+                // after an eviction)
+                let versions = self
+                    .overlaid
+                    .entry(type_info.full_name.clone())
+                    .or_default();
+                if !versions.iter().any(|v| v.type_hash == type_info.type_hash) {
+                    versions.push(type_info);
+                }
+                return Err(error);
             }
         }
 
@@ -151,6 +1132,26 @@ impl<'a> Registry<'a> {
             type_info.definition_path.display()
         );
 
+        // a new version of a type (e.g. evicted, and changed on disk before being re-parsed)
+        // replaces the hash entry of the previous one
+        if let Some((Some(previous_hash), _)) = self.type_epochs.get(type_info.full_name.as_str()) {
+            if *previous_hash != type_info.type_hash {
+                self.types_by_hash.remove(previous_hash);
+            }
+        }
+        self.types_by_hash
+            .insert(type_info.type_hash.clone(), type_info.full_name.clone());
+        let package_types = self
+            .types_by_package
+            .entry(type_info.package_name.clone())
+            .or_default();
+        if !package_types.contains(&type_info.full_name) {
+            package_types.push(type_info.full_name.clone());
+        }
+        self.memory_used += type_info.memory_size;
+        type_info.memoized_bytes = Arc::clone(&self.memoized_bytes);
+        self.add_to_dependents(&type_info);
+        self.record_type_epoch(&type_info);
         self.types.insert(&type_info.full_name.clone(), type_info);
 
         Ok(())
@@ -160,43 +1161,371 @@ impl<'a> Registry<'a> {
         self.size
     }
 
-    // Get all types matching a key expression
-    pub fn get_types(&'a self, ke: &'a keyexpr) -> Vec<&'a TypeInfo> {
+    // Iterate lazily over all types matching a key expression
+    pub fn iter_types<'b>(&'b self, ke: &'b keyexpr) -> impl Iterator<Item = &'b TypeInfo> + 'b {
         tracing::debug!("Searching types matching {}", ke);
         self.types
             .included_nodes(ke)
             .filter_map(|n| n.weight())
-            .collect()
+            .inspect(|t| self.touch(t))
     }
 
-    // Generate a concatenated type definition with its dependencies, in the same way than rosbag2 here:
-    // https://github.com/ros2/rosbag2/blob/cfb7c2114b76a53e459c7032b7c5d44fb477475d/rosbag2_cpp/include/rosbag2_cpp/message_definitions/local_message_definition_source.hpp#L88
-    pub(crate) fn get_mcap_schema(&self, t: &TypeInfo) -> String {
-        const SEPARATOR: &str =
-            "\n================================================================================\n";
+    // Get a type by its full name (e.g. "std_msgs/msg/String")
+    pub fn get_type(&self, type_name: &str) -> Option<&TypeInfo> {
+        let type_name = type_name::escape(type_name);
+        let ke = keyexpr::new(type_name.as_ref()).ok()?;
+        let t = self.types.weight_at(ke)?;
+        self.touch(t);
+        Some(t)
+    }
 
-        // Add main type definition
-        let mut result = t.definition_content.clone();
+    // Record an access to a type, for LRU eviction
+    fn touch(&self, t: &TypeInfo) {
+        let now = self.access_clock.fetch_add(1, Ordering::Relaxed);
+        t.last_access.store(now, Ordering::Relaxed);
+    }
+
+    // In bounded-memory mode, evict the least recently used types until the estimated memory
+    // used (including their memoized replies) is back under 90% of the budget. The evicted types
+    // are re-parsed on demand.
+    pub fn enforce_memory_budget(&mut self) {
+        let Some(budget) = self.memory_budget else {
+            return;
+        };
+        let mut memory_used = self.memory_used + self.memoized_bytes.load(Ordering::Relaxed);
+        if memory_used <= budget {
+            return;
+        }
+        let target = budget / 10 * 9;
+
+        let mut candidates: Vec<(u64, OwnedKeyExpr)> = self
+            .types_by_hash
+            .values()
+            .filter_map(|name| self.types.weight_at(name))
+            .map(|t| (t.last_access.load(Ordering::Relaxed), t.full_name.clone()))
+            .collect();
+        candidates.sort_unstable_by_key(|(last_access, _)| *last_access);
+
+        let mut evicted = 0usize;
+        for (_, type_name) in candidates {
+            if memory_used <= target {
+                break;
+            }
+            if let Some(t) = self.types.remove(&type_name) {
+                memory_used -= t.memory_size + t.memoized_size();
+                self.memory_used -= t.memory_size;
+                self.memoized_bytes
+                    .fetch_sub(t.memoized_size(), Ordering::Relaxed);
+                self.remove_from_dependents(&t);
+                self.size -= 1;
+                evicted += 1;
+                self.evicted
+                    .entry(type_name.to_string())
+                    .or_default()
+                    .push((t.definition_path, t.kind));
+            }
+        }
+        tracing::debug!(
+            "Evicted {evicted} types to stay under memory budget ({memory_used} bytes used)"
+        );
+    }
+
+    // Get a type by its hash (e.g. "RIHS01_df668c740482bbd48fb39d76a70dfd4bd59db1288021743503259e948f6b1a18")
+    pub fn get_type_by_hash(&self, type_hash: &str) -> Option<&TypeInfo> {
+        self.types_by_hash
+            .get(type_hash)
+            .and_then(|name| self.types.weight_at(name))
+            .map(|t| {
+                self.touch(t);
+                t
+            })
+    }
+
+    // Iterate over the overlaid versions of the types matching a key expression (i.e. the versions
+    // found after another one with a different hash, which is the preferred one)
+    pub fn iter_overlaid<'b>(&'b self, ke: &'b keyexpr) -> impl Iterator<Item = &'b TypeInfo> + 'b {
+        self.overlaid
+            .iter()
+            .filter(move |(type_name, _)| ke.includes(type_name))
+            .flat_map(|(_, versions)| versions)
+    }
+
+    // Return true if a type is an overlaid version (i.e. not the preferred one of its type name)
+    pub fn is_overlaid(&self, t: &TypeInfo) -> bool {
+        self.types
+            .weight_at(&t.full_name)
+            .map_or(true, |preferred| preferred.type_hash != t.type_hash)
+    }
 
-        // Add type definitions of dependencies
+    // Get the names of all packages with their types, sorted by package name
+    pub fn get_packages(&self) -> impl Iterator<Item = (&String, &Vec<OwnedKeyExpr>)> {
+        self.types_by_package.iter()
+    }
+
+    // Return the sources of a type and of its dependencies found in the registry, as returned by
+    // the GetTypeDescription service with include_type_sources=true
+    pub(crate) fn get_type_sources(&self, t: &TypeInfo) -> Result<Vec<TypeSource>, String> {
+        let mut sources = vec![TypeSource {
+            type_name: t.get_type_name().to_string(),
+            encoding: t.kind.as_ref().to_lowercase(),
+            raw_file_contents: self.get_definition_content(t)?,
+        }];
         for dep in &t
             .type_description
             .type_description_msg
             .referenced_type_descriptions
         {
-            let dep_type_name = KeyExpr::try_from(&dep.type_name)
-                .expect("Shouldn't happen: all type names are valid keyexpr!");
-            match self.types.weight_at(&dep_type_name) {
-                Some(dep_info) => {
-                    result.push_str(SEPARATOR);
+            if is_inner_type(t, &dep.type_name) {
+                sources.push(TypeSource {
+                    type_name: dep.type_name.to_string(),
+                    encoding: "implicit".into(),
+                    raw_file_contents: interner::empty(),
+                });
+            } else if let Some(dep_info) = self.get_type(&dep.type_name) {
+                sources.push(TypeSource {
+                    type_name: dep.type_name.to_string(),
+                    encoding: dep_info.kind.as_ref().to_lowercase(),
+                    raw_file_contents: self.get_definition_content(dep_info)?,
+                });
+            }
+        }
+        Ok(sources)
+    }
+
+    // Generate a concatenated type definition with its dependencies, in the same way than rosbag2 here:
+    // https://github.com/ros2/rosbag2/blob/cfb7c2114b76a53e459c7032b7c5d44fb477475d/rosbag2_cpp/include/rosbag2_cpp/message_definitions/local_message_definition_source.hpp#L88
+    // The result is memoized in the TypeInfo (except in lazy or bounded-memory mode, not to keep
+    // all the definitions in memory), and invalidated when the type or one of its dependencies is
+    // removed or replaced.
+    pub fn get_mcap_schema<'t>(&self, t: &'t TypeInfo) -> Result<Cow<'t, str>, String> {
+        if let Some(schema) = t.mcap_schema.get() {
+            return Ok(Cow::Borrowed(schema));
+        }
+        let schema = self.build_mcap_schema(t)?;
+        if self.lazy_definitions.is_some() || self.memory_budget.is_some() {
+            return Ok(Cow::Owned(schema));
+        }
+        Ok(Cow::Borrowed(t.memoize(&t.mcap_schema, schema)))
+    }
 
-                    result.push_str(dep_info.kind.as_ref());
-                    result.push_str(": ");
-                    result.push_str(&dep_info.get_short_type_name());
-                    result.push('\n');
+    // Return the names of the dependencies of a type which are not found in the registry
+    pub fn get_missing_dependencies<'t>(&self, t: &'t TypeInfo) -> Vec<&'t str> {
+        t.type_description
+            .type_description_msg
+            .referenced_type_descriptions
+            .iter()
+            .map(|dep| &*dep.type_name)
+            .filter(|dep_type_name| !is_inner_type(t, dep_type_name))
+            .filter(|dep_type_name| !self.is_known(dep_type_name))
+            .collect()
+    }
+
+    // Return the types having dependencies not found in the registry, sorted by type name,
+    // with the names of those missing dependencies
+    pub fn get_unresolved_dependencies(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut unresolved: Vec<(&str, Vec<&str>)> = self
+            .types_by_hash
+            .values()
+            .filter_map(|name| self.types.weight_at(name))
+            .map(|t| (t.get_type_name(), self.get_missing_dependencies(t)))
+            .filter(|(_, missing)| !missing.is_empty())
+            .collect();
+        unresolved.sort_unstable_by_key(|(type_name, _)| *type_name);
+        unresolved
+    }
 
-                    result.push_str(&dep_info.definition_content);
+    // Check that the referenced_type_descriptions and the nested types of the fields of all the
+    // loaded types resolve to types of the registry (loaded, or waiting to be parsed), keeping the
+    // dangling references found, sorted. Return their number.
+    pub fn check_references(&mut self) -> usize {
+        let mut dangling = std::collections::BTreeSet::new();
+        for t in self
+            .types_by_hash
+            .values()
+            .filter_map(|name| self.types.weight_at(name))
+        {
+            let description = &t.type_description.type_description_msg;
+            for referenced in &description.referenced_type_descriptions {
+                if !is_inner_type(t, &referenced.type_name) && !self.is_known(&referenced.type_name)
+                {
+                    dangling.insert(DanglingReference {
+                        type_name: t.get_type_name().to_string(),
+                        missing: referenced.type_name.to_string(),
+                        field: None,
+                    });
+                }
+            }
+            for d in std::iter::once(&description.type_description)
+                .chain(description.referenced_type_descriptions.iter())
+            {
+                for field in &d.fields {
+                    let nested = &*field.r#type.nested_type_name;
+                    if !nested.is_empty() && !is_inner_type(t, nested) && !self.is_known(nested) {
+                        dangling.insert(DanglingReference {
+                            type_name: t.get_type_name().to_string(),
+                            missing: nested.to_string(),
+                            field: Some(format!("{}.{}", d.type_name, field.name)),
+                        });
+                    }
                 }
+            }
+        }
+        self.dangling_references = dangling.into_iter().collect();
+        for reference in &self.dangling_references {
+            tracing::debug!(
+                "Dangling reference of {} to {}",
+                reference.type_name,
+                reference.missing
+            );
+        }
+        self.dangling_references.len()
+    }
+
+    // Return the dangling references found by the last check_references()
+    pub fn get_dangling_references(&self) -> &[DanglingReference] {
+        &self.dangling_references
+    }
+
+    // Check if the registry can be declared ready, i.e. without dangling references if required
+    pub fn is_resolved(&self) -> bool {
+        !self.strict_dependencies || self.dangling_references.is_empty()
+    }
+
+    // Return the other packages having types which directly reference types of a package (in
+    // their fields, or the fields of their inner types for services and actions), sorted by
+    // package name, with the sorted names of those referencing types
+    pub fn get_package_dependents(&self, package: &str) -> BTreeMap<&str, Vec<&str>> {
+        let prefix = format!("{}/", type_name::unescape(package));
+        let mut dependents: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for t in self
+            .types_by_hash
+            .values()
+            .filter_map(|name| self.types.weight_at(name))
+            .filter(|t| t.package_name != package)
+        {
+            if direct_dependencies(t).any(|dep| dep.starts_with(&prefix)) {
+                dependents
+                    .entry(t.package_name.as_str())
+                    .or_default()
+                    .push(t.get_type_name());
+            }
+        }
+        for type_names in dependents.values_mut() {
+            type_names.sort_unstable();
+        }
+        dependents
+    }
+
+    // Check if a type is loaded, or waiting to be parsed (pending or evicted)
+    fn is_known(&self, type_name: &str) -> bool {
+        let type_name = type_name::escape(type_name);
+        self.is_unparsed(&type_name)
+            || keyexpr::new(type_name.as_ref())
+                .map_or(false, |ke| self.types.weight_at(ke).is_some())
+    }
+
+    // Check if a type (by escaped name) is waiting to be parsed, i.e. pending or evicted
+    fn is_unparsed(&self, type_name: &str) -> bool {
+        self.pending.contains_key(type_name) || self.evicted.contains_key(type_name)
+    }
+
+    // Get the content of the original .msg/.srv/.action file of a type
+    pub(crate) fn get_definition_content(&self, t: &TypeInfo) -> Result<Arc<str>, String> {
+        match (&t.definition_content, &self.lazy_definitions) {
+            (Some(content), _) => Ok(content.clone()),
+            (None, Some(cache)) => cache.get_or_read(&t.definition_path),
+            (None, None) => definition_cache::read_definition(&t.definition_path).map(Arc::from),
+        }
+    }
+
+    pub fn build_mcap_schema(&self, t: &TypeInfo) -> Result<String, String> {
+        const SEPARATOR: &str =
+            "\n================================================================================\n";
+
+        // Add main type definition
+        let mut result = self.get_definition_content(t)?.to_string();
+
+        // Add type definitions of the transitive dependencies, once each
+        let description = &t.type_description.type_description_msg;
+        let referenced: HashMap<&str, &IndividualTypeDescription> = description
+            .referenced_type_descriptions
+            .iter()
+            .map(|d| (&*d.type_name, d))
+            .collect();
+        let mut seen: HashSet<&str> = HashSet::from([t.get_type_name()]);
+        let mut dependencies = Vec::new();
+        self.collect_mcap_dependencies(
+            t,
+            &description.type_description,
+            &referenced,
+            &mut seen,
+            &mut dependencies,
+            0,
+        )?;
+        if self.mcap_dependency_order == McapDependencyOrder::Lexicographic {
+            dependencies.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        }
+
+        for (short_name, dep_info) in dependencies {
+            result.push_str(SEPARATOR);
+
+            result.push_str(dep_info.kind.as_ref());
+            result.push_str(": ");
+            result.push_str(&short_name);
+            result.push('\n');
+
+            result.push_str(&self.get_definition_content(dep_info)?);
+        }
+
+        Ok(result)
+    }
+
+    // Collect the dependencies of a type description (with their short names), recursively,
+    // in the order rosbag2 discovers them: depth-first, with the dependencies of each type
+    // in the order of their short names (e.g. "std_msgs/Header"), and each dependency once.
+    fn collect_mcap_dependencies<'d>(
+        &'d self,
+        t: &TypeInfo,
+        description: &'d IndividualTypeDescription,
+        referenced: &HashMap<&'d str, &'d IndividualTypeDescription>,
+        seen: &mut HashSet<&'d str>,
+        dependencies: &mut Vec<(String, &'d TypeInfo)>,
+        depth: usize,
+    ) -> Result<(), String> {
+        if depth >= MAX_NESTING_DEPTH {
+            return Err(format!(
+                "Too deep dependencies for type {} (via {})",
+                t.full_name, description.type_name
+            ));
+        }
+
+        let mut direct_dependencies: Vec<(String, &str)> = description
+            .fields
+            .iter()
+            .filter(|f| f.r#type.type_id.base_type() == FieldTypeId::NestedType)
+            .map(|f| &*f.r#type.nested_type_name)
+            .map(|name| (short_type_name(name), name))
+            .collect();
+        direct_dependencies.sort_unstable();
+        direct_dependencies.dedup();
+
+        for (short_name, dep_type_name) in direct_dependencies {
+            if !seen.insert(dep_type_name) {
+                continue;
+            }
+            let Some(&dep_description) = referenced.get(dep_type_name) else {
+                tracing::warn!(
+                    "Dependency {} of type {} not found in its referenced types!",
+                    dep_type_name,
+                    t.full_name
+                );
+                continue;
+            };
+            match self.get_type(dep_type_name) {
+                Some(dep_info) => dependencies.push((short_name, dep_info)),
+                // the request/response/event/goal/... types of services and actions
+                // are part of their definition: only look for their own dependencies
+                None if is_inner_type(t, dep_type_name) => {}
                 None => {
                     tracing::warn!(
                         "Dependency {} of type {} not found in registry!",
@@ -206,8 +1535,105 @@ impl<'a> Registry<'a> {
                     continue;
                 }
             }
+            self.collect_mcap_dependencies(
+                t,
+                dep_description,
+                referenced,
+                seen,
+                dependencies,
+                depth + 1,
+            )?;
         }
+        Ok(())
+    }
+}
+
+// Check if a type name is one of the inner types of a service or action (e.g. "pkg/srv/Foo_Request"
+// for "pkg/srv/Foo"), which are not registered as types on their own
+pub(crate) fn is_inner_type(t: &TypeInfo, type_name: &str) -> bool {
+    t.kind != TypeKind::MSG
+        && type_name
+            .strip_prefix(t.get_type_name())
+            .map_or(false, |suffix| suffix.starts_with('_'))
+}
+
+// Return the names of the types directly referenced in the fields of a type (or of its inner types
+// for a service or action), excluding its inner types
+pub(crate) fn direct_dependencies(t: &TypeInfo) -> impl Iterator<Item = &str> {
+    let description = &t.type_description.type_description_msg;
+    std::iter::once(&description.type_description)
+        .chain(
+            description
+                .referenced_type_descriptions
+                .iter()
+                .filter(move |d| is_inner_type(t, &d.type_name)),
+        )
+        .flat_map(|d| d.fields.iter())
+        .map(|field| &*field.r#type.nested_type_name)
+        .filter(move |type_name| !type_name.is_empty() && !is_inner_type(t, type_name))
+}
+
+// Return the short name of a type, e.g. "std_msgs/msg/Header" => "std_msgs/Header"
+fn short_type_name(type_name: &str) -> String {
+    let mut elements = type_name.split('/');
+    match (elements.next(), elements.next(), elements.next()) {
+        (Some(package), Some(_), Some(name)) => format!("{package}/{name}"),
+        _ => type_name.to_string(),
+    }
+}
+
+// Check if a file or directory name matches one of the ignore patterns
+fn is_ignored(name: &std::ffi::OsStr, ignore_patterns: &[String]) -> bool {
+    let name = name.to_string_lossy();
+    let ignored = ignore_patterns
+        .iter()
+        .any(|pattern| matches_pattern(&name, pattern));
+    if ignored {
+        tracing::debug!("Ignoring {name}");
+    }
+    ignored
+}
+
+// Check if a name matches a pattern, where '*' matches any characters and '?' any single character
+fn matches_pattern(name: &str, pattern: &str) -> bool {
+    let name: Vec<char> = name.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut n, mut p) = (0, 0);
+    // position in pattern of the last '*', and position in name it matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // let the last '*' match one more character
+                Some((star_p, star_n)) => {
+                    backtrack = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
 
-        result
+// Deduce the type name from the path of a definition file in a ROS 2 share directory,
+// e.g. "<prefix>/share/std_msgs/msg/String.msg" => "std_msgs/msg/String" (escaped as a key expression)
+fn type_name_from_path(path: &std::path::Path, kind: &TypeKind) -> Option<String> {
+    let name = path.file_stem()?.to_str()?;
+    let kind_dir = path.parent()?;
+    let package = kind_dir.parent()?.file_name()?.to_str()?;
+    let kind_str = kind_dir.file_name()?.to_str()?;
+    if !kind_str.eq_ignore_ascii_case(kind.as_ref()) {
+        return None;
     }
+    Some(type_name::escape(&format!("{package}/{kind_str}/{name}")).into_owned())
 }
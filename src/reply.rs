@@ -0,0 +1,783 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::Serialize;
+use serde_json::{json, Value};
+use zenoh::bytes::Encoding;
+
+use crate::{
+    binary_schema, canonical_json, dds_xml, default_instance, documentation, idl, interface,
+    introspection,
+    registry::{self, Registry},
+    ros1, rosbridge, snapshot, source_lines, tarball,
+    type_description::{
+        IndividualTypeDescription, RosIndividualTypeDescription, RosTypeDescription,
+        TypeDescription, TypeSource,
+    },
+    type_info::TypeInfo,
+    ReplyFormat,
+};
+
+// The payload of a reply for a type, borrowed from the registry when possible
+pub(crate) enum Payload<'t> {
+    Borrowed(&'t str),
+    Shared(Arc<str>),
+    Owned(String),
+    Binary(Vec<u8>),
+}
+
+impl<'t> Payload<'t> {
+    // Return the canonical JSON (RFC 8785) of a JSON payload
+    pub(crate) fn canonicalize(self) -> Result<Payload<'t>, String> {
+        let json = std::str::from_utf8(&self).map_err(|e| format!("Invalid JSON payload: {e}"))?;
+        canonical_json::canonicalize(json).map(Payload::Owned)
+    }
+}
+
+impl Deref for Payload<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Payload::Borrowed(s) => s.as_bytes(),
+            Payload::Shared(s) => s.as_bytes(),
+            Payload::Owned(s) => s.as_bytes(),
+            Payload::Binary(b) => b,
+        }
+    }
+}
+
+// Cache-control metadata attached to the replies on the registry content, for the clients caches
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CacheControl {
+    pub epoch: u64,   // epoch of the registry content, changing each time it's (re)loaded
+    pub max_age: u64, // time (in seconds) during which a reply can be reused without revalidation
+}
+
+impl CacheControl {
+    // Return the attachment of a reply with this content hash,
+    // e.g. "etag=<hash>;epoch=<epoch>;max_age=<seconds>[;not_modified]"
+    pub(crate) fn attachment(&self, etag: &str, not_modified: bool) -> String {
+        let mut attachment = format!("etag={etag};epoch={};max_age={}", self.epoch, self.max_age);
+        if not_modified {
+            attachment.push_str(";not_modified");
+        }
+        attachment
+    }
+}
+
+// Options for the rendering of the types
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct RenderOptions {
+    // fail to render a MCAP schema if some dependencies are not found in the registry
+    pub strict_mcap: bool,
+    // add the JSON type descriptions to the tar archives
+    pub tar_json: bool,
+    // add the sources of the type and its dependencies to the full type descriptions
+    pub type_sources: bool,
+    // render the type descriptions with the exact shape of the type_description_interfaces messages
+    pub ros_msg: bool,
+    // render the JSON formats as canonical JSON (RFC 8785)
+    pub canonical: bool,
+    // render the default values of the fields in the type descriptions as typed JSON values
+    pub typed_defaults: bool,
+}
+
+// Rendering of a type in a reply format, independent of the way the reply is sent
+pub(crate) trait ReplyRenderer: Send + Sync {
+    // The encoding of the rendered payload
+    fn encoding(&self) -> Encoding;
+
+    // Render a type of the registry
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        options: &RenderOptions,
+    ) -> Result<Payload<'t>, String>;
+}
+
+impl ReplyFormat {
+    // Return the renderer for this format
+    pub(crate) fn renderer(self) -> &'static dyn ReplyRenderer {
+        match self {
+            ReplyFormat::TypeDescription => &TypeDescriptionRenderer,
+            ReplyFormat::FullTypeDescription => &FullTypeDescriptionRenderer,
+            ReplyFormat::Definition => &DefinitionRenderer,
+            ReplyFormat::Mcap => &McapRenderer,
+            ReplyFormat::Hash => &HashRenderer,
+            ReplyFormat::Path => &PathRenderer,
+            ReplyFormat::DefaultInstance => &DefaultInstanceRenderer,
+            ReplyFormat::Ros1Definition => &Ros1DefinitionRenderer,
+            ReplyFormat::Ros1Md5sum => &Ros1Md5sumRenderer,
+            ReplyFormat::Summary => &SummaryRenderer,
+            ReplyFormat::Rosbridge => &RosbridgeRenderer,
+            ReplyFormat::Binary => &BinaryRenderer,
+            ReplyFormat::InterfaceShow => &InterfaceShowRenderer,
+            ReplyFormat::FastDdsXml => &FastDdsXmlRenderer,
+            ReplyFormat::ConnextXml => &ConnextXmlRenderer,
+            ReplyFormat::McapSchema => &McapSchemaRenderer,
+            ReplyFormat::Snapshot => &SnapshotRenderer,
+            ReplyFormat::Tar => &TarRenderer,
+            ReplyFormat::History => &HistoryRenderer,
+            ReplyFormat::Deps => &DepsRenderer,
+            ReplyFormat::Documented => &DocumentedRenderer,
+            ReplyFormat::SourceLines => &SourceLinesRenderer,
+            ReplyFormat::Introspection => &IntrospectionRenderer,
+            ReplyFormat::ExpandedIdl => &ExpandedIdlRenderer,
+            ReplyFormat::Pretty => &PrettyRenderer,
+        }
+    }
+}
+
+struct TypeDescriptionRenderer;
+
+impl ReplyRenderer for TypeDescriptionRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        options: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        let description = &t.type_description.type_description_msg.type_description;
+        let json = if options.ros_msg {
+            serde_json::to_string(&RosIndividualTypeDescription::from(description))
+        } else if options.typed_defaults {
+            serde_json::to_value(description).and_then(|mut json| {
+                set_typed_defaults(&mut json, description);
+                serde_json::to_string(&json)
+            })
+        } else {
+            return t.get_description_json().map(Payload::Borrowed);
+        };
+        json.map(Payload::Owned).map_err(|e| {
+            format!(
+                "Failed to serialize type description of {}: {e}",
+                t.full_name
+            )
+        })
+    }
+}
+
+// Replace the default values of the fields of a serialized type description by their typed JSON
+// values, keeping the strings which can't be parsed as the type of their field
+fn set_typed_defaults(json: &mut Value, description: &IndividualTypeDescription) {
+    let Some(Value::Array(fields)) = json.get_mut("fields") else {
+        return;
+    };
+    for (json, field) in fields.iter_mut().zip(&description.fields) {
+        match default_instance::typed_default_value(field) {
+            Ok(Some(value)) => json["default_value"] = value,
+            Ok(None) => {}
+            Err(e) => tracing::debug!("{e}"),
+        }
+    }
+}
+
+// Return a serialized full type description with the typed default values of the fields of the
+// type and of its dependencies
+fn with_typed_defaults(mut json: Value, description: &TypeDescription) -> Value {
+    if let Some(type_description) = json.get_mut("type_description") {
+        set_typed_defaults(type_description, &description.type_description);
+    }
+    if let Some(Value::Array(referenced)) = json.get_mut("referenced_type_descriptions") {
+        for (json, description) in referenced
+            .iter_mut()
+            .zip(&description.referenced_type_descriptions)
+        {
+            set_typed_defaults(json, description);
+        }
+    }
+    json
+}
+
+struct FullTypeDescriptionRenderer;
+
+impl ReplyRenderer for FullTypeDescriptionRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        options: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        if !options.type_sources && !options.ros_msg && !options.typed_defaults {
+            return t.get_full_description_json().map(Payload::Borrowed);
+        }
+        // the description (with the shape of the ROS message, if requested) followed by the type
+        // sources, if requested, as in a GetTypeDescription response
+        #[derive(Serialize)]
+        struct FullDescription<D> {
+            #[serde(flatten)]
+            description: D,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            type_sources: Option<Vec<TypeSource>>,
+        }
+        let description = &t.type_description.type_description_msg;
+        let type_sources = if options.type_sources {
+            Some(registry.get_type_sources(t)?)
+        } else {
+            None
+        };
+        let json = if options.ros_msg {
+            serde_json::to_string(&FullDescription {
+                description: RosTypeDescription::from(description),
+                type_sources,
+            })
+        } else if options.typed_defaults {
+            serde_json::to_value(description).and_then(|json| {
+                serde_json::to_string(&FullDescription {
+                    description: with_typed_defaults(json, description),
+                    type_sources,
+                })
+            })
+        } else {
+            serde_json::to_string(&FullDescription {
+                description,
+                type_sources,
+            })
+        };
+        json.map(Payload::Owned).map_err(|e| {
+            format!(
+                "Failed to serialize full type description of {}: {e}",
+                t.full_name
+            )
+        })
+    }
+}
+
+struct DefinitionRenderer;
+
+impl ReplyRenderer for DefinitionRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::TEXT_PLAIN
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        registry.get_definition_content(t).map(Payload::Shared)
+    }
+}
+
+struct McapRenderer;
+
+impl ReplyRenderer for McapRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::TEXT_PLAIN
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        options: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        if options.strict_mcap {
+            let missing = registry.get_missing_dependencies(t);
+            if !missing.is_empty() {
+                return Err(format!(
+                    "Can't generate a complete MCAP schema for {}: missing dependencies {:?}",
+                    t.full_name, missing
+                ));
+            }
+        }
+        registry.get_mcap_schema(t).map(|schema| match schema {
+            Cow::Borrowed(schema) => Payload::Borrowed(schema),
+            Cow::Owned(schema) => Payload::Owned(schema),
+        })
+    }
+}
+
+// The fields of a MCAP Schema record, as written by rosbag2
+#[derive(Serialize)]
+struct McapSchema<'a> {
+    name: &'a str,
+    encoding: &'a str,
+    data: String,
+}
+
+struct McapSchemaRenderer;
+
+impl ReplyRenderer for McapSchemaRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        options: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        let data = McapRenderer.render(registry, t, options)?;
+        let schema = McapSchema {
+            name: t.get_type_name(),
+            encoding: mcap_schema_encoding(&t.definition_path),
+            data: BASE64_STANDARD.encode(&*data),
+        };
+        serde_json::to_string(&schema)
+            .map(Payload::Owned)
+            .map_err(|e| format!("Failed to serialize MCAP schema of {}: {e}", t.full_name))
+    }
+}
+
+// The MCAP schema encoding, according to the definition file format
+pub(crate) fn mcap_schema_encoding(definition_path: &Path) -> &'static str {
+    match definition_path.extension() {
+        Some(extension) if extension == "idl" => "ros2idl",
+        _ => "ros2msg",
+    }
+}
+
+struct SnapshotRenderer;
+
+impl ReplyRenderer for SnapshotRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_OCTET_STREAM
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _options: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        snapshot::snapshot_entry(registry, t)
+            .and_then(|entry| snapshot::encode_entry(&entry))
+            .map(Payload::Binary)
+    }
+}
+
+struct TarRenderer;
+
+impl ReplyRenderer for TarRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::from("application/gzip")
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        options: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        tarball::build_tarball(registry, &[t], options.tar_json).map(Payload::Binary)
+    }
+}
+
+struct HistoryRenderer;
+
+impl ReplyRenderer for HistoryRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        // the superseded or removed versions, followed by the current one (if any)
+        let mut versions: Vec<serde_json::Value> = registry
+            .get_type_history(t)
+            .iter()
+            .map(
+                |v| json!({"type_hash": v.type_info.type_hash, "since": v.since, "until": v.until}),
+            )
+            .collect();
+        if let Some(current) = registry.get_type(t.get_type_name()) {
+            versions.push(json!({
+                "type_hash": current.type_hash,
+                "since": registry.get_type_epoch(current),
+                "until": null
+            }));
+        }
+        serde_json::to_string(&versions)
+            .map(Payload::Owned)
+            .map_err(|e| format!("Failed to serialize history of {}: {e}", t.full_name))
+    }
+}
+
+struct DepsRenderer;
+
+impl ReplyRenderer for DepsRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        // the referenced types (without the inner types of a service or action), with the hashes
+        // listed in the type description
+        let description = &t.type_description;
+        let deps: Vec<serde_json::Value> = description
+            .type_description_msg
+            .referenced_type_descriptions
+            .iter()
+            .map(|d| &*d.type_name)
+            .filter(|type_name| !registry::is_inner_type(t, type_name))
+            .map(|type_name| {
+                let hash = description
+                    .type_hashes
+                    .iter()
+                    .find(|h| h.type_name == type_name)
+                    .map(|h| h.hash_string.as_str());
+                let kind = type_name.split('/').nth(1);
+                json!({"type_name": type_name, "hash": hash, "kind": kind})
+            })
+            .collect();
+        serde_json::to_string(&deps)
+            .map(Payload::Owned)
+            .map_err(|e| format!("Failed to serialize dependencies of {}: {e}", t.full_name))
+    }
+}
+
+struct DocumentedRenderer;
+
+impl ReplyRenderer for DocumentedRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        let documented = documentation::documented_type(registry, t)?;
+        serde_json::to_string(&documented)
+            .map(Payload::Owned)
+            .map_err(|e| format!("Failed to serialize documentation of {}: {e}", t.full_name))
+    }
+}
+
+struct SourceLinesRenderer;
+
+impl ReplyRenderer for SourceLinesRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        let source_lines = source_lines::source_lines(registry, t)?;
+        serde_json::to_string(&source_lines)
+            .map(Payload::Owned)
+            .map_err(|e| format!("Failed to serialize source lines of {}: {e}", t.full_name))
+    }
+}
+
+struct IntrospectionRenderer;
+
+impl ReplyRenderer for IntrospectionRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        let descriptor = introspection::descriptor(t)?;
+        serde_json::to_string(&descriptor)
+            .map(Payload::Owned)
+            .map_err(|e| format!("Failed to serialize descriptor of {}: {e}", t.full_name))
+    }
+}
+
+struct ExpandedIdlRenderer;
+
+impl ReplyRenderer for ExpandedIdlRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::TEXT_PLAIN
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        idl::expanded_idl(registry, t).map(Payload::Owned)
+    }
+}
+
+struct PrettyRenderer;
+
+impl ReplyRenderer for PrettyRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::TEXT_PLAIN
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        Ok(Payload::Owned(
+            t.type_description.type_description_msg.pretty(),
+        ))
+    }
+}
+
+struct HashRenderer;
+
+impl ReplyRenderer for HashRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::TEXT_PLAIN
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        Ok(Payload::Borrowed(&t.type_hash))
+    }
+}
+
+struct PathRenderer;
+
+impl ReplyRenderer for PathRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::TEXT_PLAIN
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        Ok(Payload::Owned(path_to_reply(&t.definition_path)))
+    }
+}
+
+// Convert a path for a reply, with '/' separators on all platforms
+pub(crate) fn path_to_reply(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if std::path::MAIN_SEPARATOR == '\\' {
+        path.replace('\\', "/")
+    } else {
+        path.into_owned()
+    }
+}
+
+struct DefaultInstanceRenderer;
+
+impl ReplyRenderer for DefaultInstanceRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        serde_json::to_string(&default_instance::default_instance(
+            &t.type_description.type_description_msg,
+        ))
+        .map(Payload::Owned)
+        .map_err(|e| {
+            format!(
+                "Failed to serialize default instance of {}: {e}",
+                t.full_name
+            )
+        })
+    }
+}
+
+struct Ros1DefinitionRenderer;
+
+impl ReplyRenderer for Ros1DefinitionRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::TEXT_PLAIN
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        ros1::ros1_definition(registry, t).map(Payload::Owned)
+    }
+}
+
+struct Ros1Md5sumRenderer;
+
+impl ReplyRenderer for Ros1Md5sumRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::TEXT_PLAIN
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        ros1::ros1_md5sum(registry, t).map(Payload::Owned)
+    }
+}
+
+struct SummaryRenderer;
+
+impl ReplyRenderer for SummaryRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        let description = &t.type_description.type_description_msg;
+        let summary = json!({
+            "type_name": t.get_type_name(),
+            "kind": t.kind.as_ref().to_lowercase(),
+            "package": t.package_name,
+            "package_version": t.package_version,
+            "type_hash": t.type_hash,
+            "schema_version": t.schema_version,
+            "fields": description.type_description.fields.len(),
+            "dependencies": description
+                .referenced_type_descriptions
+                .iter()
+                .map(|dep| &*dep.type_name)
+                .collect::<Vec<_>>(),
+            "definition_path": path_to_reply(&t.definition_path),
+        });
+        serde_json::to_string(&summary)
+            .map(Payload::Owned)
+            .map_err(|e| format!("Failed to serialize summary of {}: {e}", t.full_name))
+    }
+}
+
+struct RosbridgeRenderer;
+
+impl ReplyRenderer for RosbridgeRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_JSON
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        serde_json::to_string(&rosbridge::message_details(registry, t))
+            .map(Payload::Owned)
+            .map_err(|e| format!("Failed to serialize typedefs of {}: {e}", t.full_name))
+    }
+}
+
+struct BinaryRenderer;
+
+impl ReplyRenderer for BinaryRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_OCTET_STREAM
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        binary_schema::encode(t).map(Payload::Binary)
+    }
+}
+
+struct InterfaceShowRenderer;
+
+impl ReplyRenderer for InterfaceShowRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::TEXT_PLAIN
+    }
+
+    fn render<'t>(
+        &self,
+        registry: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        interface::interface_show(registry, t).map(Payload::Owned)
+    }
+}
+
+struct FastDdsXmlRenderer;
+
+impl ReplyRenderer for FastDdsXmlRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_XML
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        dds_xml::fastdds_types_xml(t).map(Payload::Owned)
+    }
+}
+
+struct ConnextXmlRenderer;
+
+impl ReplyRenderer for ConnextXmlRenderer {
+    fn encoding(&self) -> Encoding {
+        Encoding::APPLICATION_XML
+    }
+
+    fn render<'t>(
+        &self,
+        _: &Registry,
+        t: &'t TypeInfo,
+        _: &RenderOptions,
+    ) -> Result<Payload<'t>, String> {
+        dds_xml::connext_types_xml(t).map(Payload::Owned)
+    }
+}
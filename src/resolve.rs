@@ -0,0 +1,238 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Resolution of the field type tokens of the .msg/.srv/.action definitions as the rosidl parser
+// does, for the external parsers of raw definitions delegating the name resolution to the registry.
+// Available under the @ros2_resolve/<token>[?package=<context_package>] key space, e.g.:
+//   @ros2_resolve/Header                          => std_msgs/msg/Header
+//   @ros2_resolve/Point32[]?package=geometry_msgs => unbounded sequence of geometry_msgs/msg/Point32
+//   @ros2_resolve/string<=10                      => bounded string of max 10 characters
+use std::sync::Arc;
+
+use serde::Serialize;
+use zenoh::{bytes::Encoding, key_expr::keyexpr, query::Query};
+
+use crate::{
+    access_log::QueryStats, field_type::FieldTypeId, keformat_ros2_resolve, query_reply_ke,
+    registry::Registry, send_reply, send_reply_err, strip_namespace, type_description::FieldType,
+    type_name, ErrorCode,
+};
+
+// Type resolved for an unqualified "Header", as by the ROS 1 parsers (genmsg)
+const HEADER_TYPE: &str = "std_msgs/msg/Header";
+
+// Offsets between the ids of a basic type and of its array and sequences variants
+const ARRAY_ID_OFFSET: u64 = 48;
+const BOUNDED_SEQUENCE_ID_OFFSET: u64 = 96;
+const UNBOUNDED_SEQUENCE_ID_OFFSET: u64 = 144;
+
+// A resolved token: its field type, as in the type descriptions, and the type hash of its nested
+// type if found in the registry
+#[derive(Debug, Serialize)]
+struct Resolution<'a> {
+    token: &'a str,
+    r#type: FieldType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    type_hash: Option<&'a str>,
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+pub(crate) async fn handle_ros2_resolve_query(
+    query: Query,
+    registry: &mut Registry<'_>,
+) -> QueryStats {
+    tracing::debug!("Received query: {}", query.key_expr());
+    let mut stats = QueryStats::default();
+    let ke = match keformat_ros2_resolve::parse(strip_namespace(query.key_expr())) {
+        Ok(ke) => ke,
+        Err(_) => {
+            tracing::error!(
+                "Received a query on '{}' but it doesn't match the '@ros2_resolve/**' queryable!",
+                query.key_expr()
+            );
+            return stats;
+        }
+    };
+    let Some(token) = ke.token() else {
+        return stats;
+    };
+    let token = type_name::unescape(token.as_str()).into_owned();
+    let package = query.parameters().get("package").filter(|p| !p.is_empty());
+
+    let resolved = match resolve(registry, &token, package) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            send_reply_err(&query, ErrorCode::InvalidParameter, e).await;
+            return stats;
+        }
+    };
+    if let Ok(type_ke) = keyexpr::new(type_name::escape(&resolved.nested_type_name).as_ref()) {
+        registry.load_pending_matching(type_ke);
+    }
+    let type_hash = registry
+        .get_type(&resolved.nested_type_name)
+        .map(|t| t.type_hash.as_str());
+    let resolution = Resolution {
+        token: &token,
+        r#type: resolved,
+        type_hash,
+    };
+    match serde_json::to_string(&resolution) {
+        Ok(json) => {
+            stats.matched = 1;
+            stats.bytes_sent += send_reply(
+                &query,
+                query_reply_ke(&query, registry.get_namespace()),
+                json,
+                Encoding::APPLICATION_JSON,
+            )
+            .await;
+        }
+        Err(e) => {
+            send_reply_err(
+                &query,
+                ErrorCode::SerializationFailed,
+                format!("Failed to serialize resolution of '{token}': {e}"),
+            )
+            .await
+        }
+    }
+    stats
+}
+
+// Resolve a field type token (e.g. "Point32[<=3]"), the unqualified nested types being resolved
+// in the context package if specified
+pub(crate) fn resolve(
+    registry: &mut Registry,
+    token: &str,
+    package: Option<&str>,
+) -> Result<FieldType, String> {
+    // the array or sequence suffix, e.g. "[3]", "[<=3]" or "[]"
+    let (base, collection) = match token.strip_suffix(']').and_then(|t| t.rsplit_once('[')) {
+        Some((base, bound)) => (base, Some(bound)),
+        None => (token, None),
+    };
+    let (base_id, string_capacity, nested_type_name) = resolve_base_type(registry, base, package)?;
+    let (type_id, capacity) = match collection {
+        None => (base_id as u64, 0),
+        Some("") => (base_id as u64 + UNBOUNDED_SEQUENCE_ID_OFFSET, 0),
+        Some(bound) => match bound.strip_prefix("<=") {
+            Some(max) => (
+                base_id as u64 + BOUNDED_SEQUENCE_ID_OFFSET,
+                parse_bound(token, max)?,
+            ),
+            None => (base_id as u64 + ARRAY_ID_OFFSET, parse_bound(token, bound)?),
+        },
+    };
+    Ok(FieldType {
+        type_id: FieldTypeId::from_repr(type_id)
+            .ok_or(format!("Invalid field type token '{token}'"))?,
+        capacity,
+        string_capacity,
+        nested_type_name: Arc::from(nested_type_name),
+    })
+}
+
+// Resolve a base type token (without array or sequence suffix), returning its type id, its string
+// capacity (for bounded strings) and the name of its nested type (if not a primitive type)
+fn resolve_base_type(
+    registry: &mut Registry,
+    base: &str,
+    package: Option<&str>,
+) -> Result<(FieldTypeId, u32, String), String> {
+    // bounded strings, e.g. "string<=10"
+    if let Some((string_type, max)) = base.split_once("<=") {
+        let type_id = match string_type {
+            "string" => FieldTypeId::BoundedString,
+            "wstring" => FieldTypeId::BoundedWString,
+            _ => return Err(format!("Only strings can be bounded, not '{base}'")),
+        };
+        return Ok((type_id, parse_bound(base, max)?, String::new()));
+    }
+    let primitive = match base {
+        "bool" => Some(FieldTypeId::Boolean),
+        "byte" => Some(FieldTypeId::Byte),
+        // "char" is an alias of uint8 in the .msg definitions
+        "char" | "uint8" => Some(FieldTypeId::UInt8),
+        "int8" => Some(FieldTypeId::Int8),
+        "int16" => Some(FieldTypeId::Int16),
+        "uint16" => Some(FieldTypeId::UInt16),
+        "int32" => Some(FieldTypeId::Int32),
+        "uint32" => Some(FieldTypeId::UInt32),
+        "int64" => Some(FieldTypeId::Int64),
+        "uint64" => Some(FieldTypeId::UInt64),
+        "float32" => Some(FieldTypeId::Float),
+        "float64" => Some(FieldTypeId::Double),
+        "string" => Some(FieldTypeId::String),
+        "wstring" => Some(FieldTypeId::WString),
+        _ => None,
+    };
+    if let Some(type_id) = primitive {
+        return Ok((type_id, 0, String::new()));
+    }
+
+    let nested_type_name = match base.split('/').collect::<Vec<_>>()[..] {
+        [pkg, kind, name] if !pkg.is_empty() && !kind.is_empty() && !name.is_empty() => {
+            base.to_string()
+        }
+        [pkg, name] if !pkg.is_empty() && !name.is_empty() => format!("{pkg}/msg/{name}"),
+        [name] if !name.is_empty() => resolve_unqualified(registry, name, package)?,
+        _ => return Err(format!("Invalid type name '{base}'")),
+    };
+    Ok((FieldTypeId::NestedType, 0, nested_type_name))
+}
+
+// Resolve an unqualified type name: in the context package (as rosidl does), "Header" being
+// resolved as "std_msgs/msg/Header" unless the context package defines its own, or without context
+// package in the only package defining a message with this name
+fn resolve_unqualified(
+    registry: &mut Registry,
+    name: &str,
+    package: Option<&str>,
+) -> Result<String, String> {
+    let candidates_ke = format!("{}/msg/{}", package.unwrap_or("*"), type_name::escape(name));
+    let candidates_ke =
+        keyexpr::new(&candidates_ke).map_err(|e| format!("Invalid type name '{name}': {e}"))?;
+    registry.load_pending_matching(candidates_ke);
+    let mut candidates: Vec<String> = registry
+        .iter_types(candidates_ke)
+        .map(|t| t.get_type_name().to_string())
+        .collect();
+    match (package, name) {
+        (Some(_), "Header") if candidates.is_empty() => Ok(HEADER_TYPE.to_string()),
+        (Some(package), _) => Ok(format!("{package}/msg/{name}")),
+        (None, "Header") => Ok(HEADER_TYPE.to_string()),
+        (None, _) if candidates.len() == 1 => Ok(candidates.remove(0)),
+        (None, _) if candidates.is_empty() => Err(format!(
+            "No message type named '{name}' in the registry - specify its package or a context \
+             package (with the 'package' parameter)"
+        )),
+        (None, _) => {
+            candidates.sort_unstable();
+            Err(format!(
+                "Ambiguous type name '{name}', defined as {} - specify its package or a context \
+                 package (with the 'package' parameter)",
+                candidates.join(", ")
+            ))
+        }
+    }
+}
+
+fn parse_bound(token: &str, bound: &str) -> Result<u32, String> {
+    match bound.trim().parse::<u32>() {
+        Ok(bound) if bound > 0 => Ok(bound),
+        _ => Err(format!(
+            "Invalid bound '{bound}' in field type token '{token}'"
+        )),
+    }
+}
@@ -0,0 +1,278 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::path::Path;
+
+use zenoh::key_expr::keyexpr;
+
+use crate::{
+    field_type::FieldTypeId,
+    registry::Registry,
+    type_description::{Field, FieldType, MAX_NESTING_DEPTH},
+    type_info::{TypeInfo, TypeKind},
+    type_name,
+};
+
+// ROS 2 Header is bridged to the ROS 1 Header, which has an extra "seq" field
+const ROS2_HEADER_TYPE: &str = "std_msgs/msg/Header";
+const ROS1_HEADER_DEFINITION: &str = "uint32 seq\ntime stamp\nstring frame_id";
+
+// Dummy field added by rosidl to empty structures, not existing in ROS 1
+const EMPTY_STRUCTURE_FIELD: &str = "structure_needs_at_least_one_member";
+
+// Convert the definition of a ROS 2 message type into a ROS 1 message definition
+pub(crate) fn ros1_definition(registry: &Registry, t: &TypeInfo) -> Result<String, String> {
+    check_is_msg(t)?;
+    if t.full_name.as_str() == ROS2_HEADER_TYPE {
+        return Ok(ROS1_HEADER_DEFINITION.to_string());
+    }
+
+    let mut lines = ros1_constants(registry, t)?;
+    for field in fields(t) {
+        lines.push(format!(
+            "{} {}",
+            ros1_field_type(&field.r#type, registry)?,
+            field.name
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+// Compute the ROS 1 md5sum of the converted ROS 1 definition, following the roslib algorithm
+// (see genmsg's compute_md5_text()): nested types are replaced with their own md5sum.
+pub(crate) fn ros1_md5sum(registry: &Registry, t: &TypeInfo) -> Result<String, String> {
+    md5sum(registry, t, &mut Vec::new())
+}
+
+// `stack` is the list of the types being hashed, to detect circular references
+fn md5sum(registry: &Registry, t: &TypeInfo, stack: &mut Vec<String>) -> Result<String, String> {
+    if stack.iter().any(|name| name == t.full_name.as_str()) || stack.len() >= MAX_NESTING_DEPTH {
+        return Err(format!(
+            "Circular or too deep reference to type {} (via {})",
+            t.full_name,
+            stack.join(" -> ")
+        ));
+    }
+    stack.push(t.full_name.to_string());
+    let text = md5_text(registry, t, stack);
+    stack.pop();
+    Ok(format!("{:x}", md5::compute(text?)))
+}
+
+fn md5_text(registry: &Registry, t: &TypeInfo, stack: &mut Vec<String>) -> Result<String, String> {
+    check_is_msg(t)?;
+    if t.full_name.as_str() == ROS2_HEADER_TYPE {
+        return Ok(ROS1_HEADER_DEFINITION.to_string());
+    }
+
+    let mut lines = ros1_constants(registry, t)?;
+    for field in fields(t) {
+        let field_type = &field.r#type;
+        if field_type.type_id.base_type() == FieldTypeId::NestedType
+            && ros1_builtin_type(&field_type.nested_type_name).is_none()
+        {
+            let dep = registry
+                .get_type(&field_type.nested_type_name)
+                .ok_or(format!(
+                    "Dependency {} of type {} not found in registry",
+                    field_type.nested_type_name, t.full_name
+                ))?;
+            lines.push(format!("{} {}", md5sum(registry, dep, stack)?, field.name));
+        } else {
+            lines.push(format!(
+                "{} {}",
+                ros1_field_type(field_type, registry)?,
+                field.name
+            ));
+        }
+    }
+    Ok(lines.join("\n").trim().to_string())
+}
+
+fn check_is_msg(t: &TypeInfo) -> Result<(), String> {
+    if t.kind == TypeKind::MSG {
+        Ok(())
+    } else {
+        Err(format!(
+            "Conversion to ROS 1 is only supported for messages, not for {}",
+            t.full_name
+        ))
+    }
+}
+
+fn fields(t: &TypeInfo) -> impl Iterator<Item = &Field> {
+    t.type_description
+        .type_description_msg
+        .type_description
+        .fields
+        .iter()
+        .filter(|f| &*f.name != EMPTY_STRUCTURE_FIELD)
+}
+
+// Extract the constants from the original definition, as "<type> <NAME>=<value>" lines
+fn ros1_constants(registry: &Registry, t: &TypeInfo) -> Result<Vec<String>, String> {
+    Ok(registry
+        .get_definition_content(t)?
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.starts_with('#') {
+                return None;
+            }
+            let (ros2_type, declaration) = line.split_once(char::is_whitespace)?;
+            let (name, value) = declaration.split_once('=')?;
+            // skip fields with a default value containing a '=' (e.g. string s "a=b")
+            if ros2_type.contains('[') || name.trim().contains(char::is_whitespace) {
+                return None;
+            }
+            let ros1_type = match ros2_type {
+                "float32" | "float64" | "bool" | "byte" | "char" => ros2_type,
+                t if t.starts_with("string") => "string",
+                t if t.starts_with("int") || t.starts_with("uint") => t,
+                _ => return None,
+            };
+            // strip trailing comment, except for strings which may contain a '#'
+            let value = match value.split_once('#') {
+                Some((v, _)) if ros1_type != "string" => v,
+                _ => value,
+            };
+            Some(format!("{ros1_type} {}={}", name.trim(), value.trim()))
+        })
+        .collect())
+}
+
+// ROS 1 built-in types that are nested types in ROS 2
+fn ros1_builtin_type(nested_type_name: &str) -> Option<&'static str> {
+    match nested_type_name {
+        "builtin_interfaces/msg/Time" => Some("time"),
+        "builtin_interfaces/msg/Duration" => Some("duration"),
+        _ => None,
+    }
+}
+
+// Return the ROS 1 type of a field, e.g. "float64[3]", "string[]" or "geometry_msgs/Point"
+fn ros1_field_type(field_type: &FieldType, registry: &Registry) -> Result<String, String> {
+    let base_type = field_type.type_id.base_type();
+    let ros1_type = match base_type {
+        FieldTypeId::NestedType => match ros1_builtin_type(&field_type.nested_type_name) {
+            Some(builtin) => builtin.to_string(),
+            None => registry
+                .get_type(&field_type.nested_type_name)
+                .map(|dep| dep.get_short_type_name())
+                .ok_or(format!(
+                    "Nested type {} not found in registry",
+                    field_type.nested_type_name
+                ))?,
+        },
+        FieldTypeId::Int8 => "int8".into(),
+        FieldTypeId::UInt8 => "uint8".into(),
+        FieldTypeId::Int16 => "int16".into(),
+        FieldTypeId::UInt16 => "uint16".into(),
+        FieldTypeId::Int32 => "int32".into(),
+        FieldTypeId::UInt32 => "uint32".into(),
+        FieldTypeId::Int64 => "int64".into(),
+        FieldTypeId::UInt64 => "uint64".into(),
+        FieldTypeId::Float => "float32".into(),
+        FieldTypeId::Double => "float64".into(),
+        FieldTypeId::Char => "char".into(),
+        FieldTypeId::Boolean => "bool".into(),
+        FieldTypeId::Byte => "byte".into(),
+        FieldTypeId::String | FieldTypeId::FixedString | FieldTypeId::BoundedString => {
+            "string".into()
+        }
+        _ => return Err(format!("Type {base_type:?} has no equivalent in ROS 1")),
+    };
+
+    if field_type.type_id.is_array() {
+        Ok(format!("{ros1_type}[{}]", field_type.capacity))
+    } else if field_type.type_id.is_collection() {
+        // ROS 1 has no bounded sequences
+        Ok(format!("{ros1_type}[]"))
+    } else {
+        Ok(ros1_type)
+    }
+}
+
+// Export the ros1_bridge mapping rules of all the messages convertible to ROS 1 (assuming their
+// ROS 1 counterparts have the same package and message names) to a YAML file, as expected in the
+// `ros1_bridge` export of a package.xml. Each rule maps the fields one to one and is preceded by a
+// report on the fields conversion, the messages not convertible being only reported in comments.
+// Return the number of exported rules.
+pub(crate) fn export_ros1_bridge_mapping(
+    registry: &mut Registry,
+    path: &Path,
+) -> Result<usize, String> {
+    let all_types = keyexpr::new("**").expect("valid key expression");
+    registry.load_pending_matching(all_types);
+
+    let mut rules = String::from("# ros1_bridge mapping rules generated by ros2-types-registry\n");
+    let mut count = 0;
+    for t in registry.iter_types(all_types) {
+        if t.kind != TypeKind::MSG || ros1_builtin_type(t.full_name.as_str()).is_some() {
+            continue;
+        }
+        let name = t.get_type_name();
+        if let Err(e) = ros1_definition(registry, t) {
+            rules.push_str(&format!("\n# {name}: not mapped - {e}\n"));
+            continue;
+        }
+        rules.push_str(&format!("\n# {name}\n"));
+        for field in fields(t) {
+            if let Some(note) = field_conversion_note(&field.r#type) {
+                rules.push_str(&format!("#   {}: {note}\n", field.name));
+            }
+        }
+        if t.full_name.as_str() == ROS2_HEADER_TYPE {
+            rules.push_str("#   seq: only in ROS 1, dropped\n");
+        }
+        let package = &t.package_name;
+        let short_name = type_name::unescape(&t.short_name).into_owned();
+        rules.push_str(&format!(
+            "- ros1_package_name: '{package}'\n  ros1_message_name: '{short_name}'\n  \
+             ros2_package_name: '{package}'\n  ros2_message_name: '{short_name}'\n"
+        ));
+        let mut mapped = fields(t).peekable();
+        if mapped.peek().is_some() {
+            rules.push_str("  fields_1_to_2:\n");
+            for field in mapped {
+                rules.push_str(&format!("    {0}: '{0}'\n", field.name));
+            }
+        }
+        count += 1;
+    }
+    std::fs::write(path, rules).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(count)
+}
+
+// Describe how a field is converted to ROS 1, if not exactly the same
+fn field_conversion_note(field_type: &FieldType) -> Option<String> {
+    let base_type = field_type.type_id.base_type();
+    if let Some(builtin) = ros1_builtin_type(&field_type.nested_type_name) {
+        return Some(format!(
+            "{} converted to ROS 1 {builtin}",
+            field_type.nested_type_name
+        ));
+    }
+    if field_type.type_id.is_bounded_sequence() {
+        return Some(format!(
+            "bounded sequence (max {}) converted to an unbounded array",
+            field_type.capacity
+        ));
+    }
+    match base_type {
+        FieldTypeId::FixedString | FieldTypeId::BoundedString => Some(format!(
+            "bounded string (max {}) converted to an unbounded string",
+            field_type.string_capacity
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,168 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use serde::Serialize;
+
+use crate::{
+    field_type::FieldTypeId,
+    registry::Registry,
+    type_description::{Field, FieldType, IndividualTypeDescription},
+    type_info::TypeInfo,
+};
+
+// Dummy field added by rosidl to empty structures, not reported by rosapi
+const EMPTY_STRUCTURE_FIELD: &str = "structure_needs_at_least_one_member";
+
+// The reply of rosapi's "message_details" service, as expected by rosbridge clients (e.g. roslibjs)
+#[derive(Serialize)]
+pub(crate) struct MessageDetails<'a> {
+    typedefs: Vec<TypeDef<'a>>,
+}
+
+// The definition of a type, as in rosapi's TypeDef message:
+// the fields attributes are in parallel arrays, with "fieldarraylen" being
+// -1 for non-array fields, 0 for sequences and the size for fixed size arrays
+#[derive(Serialize)]
+struct TypeDef<'a> {
+    r#type: &'a str,
+    fieldnames: Vec<&'a str>,
+    fieldtypes: Vec<String>,
+    fieldarraylen: Vec<i64>,
+    examples: Vec<String>,
+    constnames: Vec<String>,
+    constvalues: Vec<String>,
+}
+
+// Build the rosapi-like details of a type: the typedef of the type itself,
+// followed by the typedefs of all its (transitive) dependencies
+pub(crate) fn message_details<'a>(registry: &Registry, t: &'a TypeInfo) -> MessageDetails<'a> {
+    let description = &t.type_description.type_description_msg;
+    MessageDetails {
+        typedefs: std::iter::once(&description.type_description)
+            .chain(description.referenced_type_descriptions.iter())
+            .map(|d| typedef(registry, d))
+            .collect(),
+    }
+}
+
+fn typedef<'a>(registry: &Registry, d: &'a IndividualTypeDescription) -> TypeDef<'a> {
+    let fields: Vec<&Field> = d
+        .fields
+        .iter()
+        .filter(|f| &*f.name != EMPTY_STRUCTURE_FIELD)
+        .collect();
+    let (constnames, constvalues) = constants(registry, &d.type_name).into_iter().unzip();
+    TypeDef {
+        r#type: &d.type_name,
+        fieldnames: fields.iter().map(|f| &*f.name).collect(),
+        fieldtypes: fields.iter().map(|f| field_type(&f.r#type)).collect(),
+        fieldarraylen: fields.iter().map(|f| array_len(&f.r#type)).collect(),
+        examples: fields.iter().map(|f| example(f)).collect(),
+        constnames,
+        constvalues,
+    }
+}
+
+// Return the type of the elements of a field, with the IDL names used by rosapi (e.g. "double", "octet")
+fn field_type(field_type: &FieldType) -> String {
+    match field_type.type_id.base_type() {
+        FieldTypeId::NestedType => field_type.nested_type_name.to_string(),
+        FieldTypeId::Int8 => "int8".into(),
+        FieldTypeId::UInt8 => "uint8".into(),
+        FieldTypeId::Int16 => "int16".into(),
+        FieldTypeId::UInt16 => "uint16".into(),
+        FieldTypeId::Int32 => "int32".into(),
+        FieldTypeId::UInt32 => "uint32".into(),
+        FieldTypeId::Int64 => "int64".into(),
+        FieldTypeId::UInt64 => "uint64".into(),
+        FieldTypeId::Float => "float".into(),
+        FieldTypeId::Double => "double".into(),
+        FieldTypeId::LongDouble => "long double".into(),
+        FieldTypeId::Char => "char".into(),
+        FieldTypeId::WChar => "wchar".into(),
+        FieldTypeId::Boolean => "boolean".into(),
+        FieldTypeId::Byte => "octet".into(),
+        FieldTypeId::String => "string".into(),
+        FieldTypeId::WString => "wstring".into(),
+        FieldTypeId::FixedString | FieldTypeId::BoundedString => {
+            format!("string<{}>", field_type.string_capacity)
+        }
+        FieldTypeId::FixedWString | FieldTypeId::BoundedWString => {
+            format!("wstring<{}>", field_type.string_capacity)
+        }
+        other => format!("{other:?}"),
+    }
+}
+
+fn array_len(field_type: &FieldType) -> i64 {
+    if field_type.type_id.is_array() {
+        field_type.capacity.into()
+    } else if field_type.type_id.is_collection() {
+        0
+    } else {
+        -1
+    }
+}
+
+// An example value of a field, formatted as rosapi does (i.e. as Python's str() of the default value)
+fn example(field: &Field) -> String {
+    let type_id = field.r#type.type_id;
+    if let Some(default_value) = field.default_value.as_deref().filter(|s| !s.is_empty()) {
+        return default_value.to_string();
+    }
+    if type_id.is_collection() {
+        return "[]".into();
+    }
+    match type_id {
+        FieldTypeId::NestedType => "{}".into(),
+        FieldTypeId::Float | FieldTypeId::Double | FieldTypeId::LongDouble => "0.0".into(),
+        FieldTypeId::Boolean => "False".into(),
+        FieldTypeId::String
+        | FieldTypeId::WString
+        | FieldTypeId::FixedString
+        | FieldTypeId::FixedWString
+        | FieldTypeId::BoundedString
+        | FieldTypeId::BoundedWString => String::new(),
+        _ => "0".into(),
+    }
+}
+
+// Extract the constants (names and values) from the original definition of a type, if found in registry
+fn constants(registry: &Registry, type_name: &str) -> Vec<(String, String)> {
+    let Some(content) = registry
+        .get_type(type_name)
+        .and_then(|t| registry.get_definition_content(t).ok())
+    else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.starts_with('#') {
+                return None;
+            }
+            let (const_type, declaration) = line.split_once(char::is_whitespace)?;
+            let (name, value) = declaration.split_once('=')?;
+            // skip fields with a default value containing a '=' (e.g. string s "a=b")
+            if const_type.contains('[') || name.trim().contains(char::is_whitespace) {
+                return None;
+            }
+            // strip trailing comment, except for strings which may contain a '#'
+            let value = match value.split_once('#') {
+                Some((v, _)) if !const_type.starts_with("string") => v,
+                _ => value,
+            };
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,161 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+// The self-test of the registry (`--self-test`), a one-command confidence check of a deployment
+// after changes of its workspaces: for each loaded type, the pipeline of its replies is exercised,
+// and the failing steps are reported per type:
+//  - "description": the JSON type descriptions serialization, and the parsing back of the full one
+//  - "type_hash": the verification of its type hash, recomputed from its description (and from
+//    the parsed back description), and of the hashes of its dependencies against the registry
+//  - "mcap_schema": the generation of its MCAP schema, with all its dependencies
+//  - "default_instance": the generation of its default instance, valid against its description
+// (a round-trip of the default instance through CDR would require a CDR serializer, which the
+// registry doesn't have)
+use serde::Serialize;
+use zenoh::key_expr::keyexpr;
+
+use crate::{
+    default_instance, registry::Registry, type_description::TypeDescription, type_info::TypeInfo,
+    validation,
+};
+
+// Report of the self-test of a registry
+#[derive(Debug, Serialize)]
+pub(crate) struct SelfTestReport {
+    pub namespace: Option<String>,
+    pub types: usize,
+    pub failed: usize,
+    pub failures: Vec<TypeFailures>,
+}
+
+// The failed steps of the self-test of a type
+#[derive(Debug, Serialize)]
+pub(crate) struct TypeFailures {
+    pub type_name: String,
+    pub steps: Vec<StepFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct StepFailure {
+    pub step: &'static str,
+    pub error: String,
+}
+
+// Run the self-test of all the types of a registry (parsing the pending ones first)
+pub(crate) fn self_test(registry: &mut Registry) -> SelfTestReport {
+    let all_types = keyexpr::new("**").expect("valid key expression");
+    registry.load_pending_matching(all_types);
+
+    let mut report = SelfTestReport {
+        namespace: registry.get_namespace().map(|ns| ns.to_string()),
+        types: 0,
+        failed: 0,
+        failures: Vec::new(),
+    };
+    for t in registry.iter_types(all_types) {
+        report.types += 1;
+        let steps: Vec<StepFailure> = [
+            ("description", check_description(t)),
+            ("type_hash", check_type_hash(registry, t)),
+            ("mcap_schema", check_mcap_schema(registry, t)),
+            ("default_instance", check_default_instance(t)),
+        ]
+        .into_iter()
+        .filter_map(|(step, result)| result.err().map(|error| StepFailure { step, error }))
+        .collect();
+        if !steps.is_empty() {
+            tracing::warn!("Self-test of {} failed: {steps:?}", t.full_name);
+            report.failed += 1;
+            report.failures.push(TypeFailures {
+                type_name: t.get_type_name().to_string(),
+                steps,
+            });
+        }
+    }
+    report
+}
+
+fn check_description(t: &TypeInfo) -> Result<(), String> {
+    t.get_description_json()?;
+    let json = t.get_full_description_json()?;
+    serde_json::from_str::<TypeDescription>(json)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to parse back the full type description: {e}"))
+}
+
+fn check_type_hash(registry: &Registry, t: &TypeInfo) -> Result<(), String> {
+    let description = &t.type_description.type_description_msg;
+    let computed = description.compute_type_hash();
+    if computed != t.type_hash {
+        return Err(format!(
+            "Type hash {} doesn't match the hash computed from the type description: {computed}",
+            t.type_hash
+        ));
+    }
+    let parsed: TypeDescription = serde_json::from_str(t.get_full_description_json()?)
+        .map_err(|e| format!("Failed to parse back the full type description: {e}"))?;
+    let round_tripped = parsed.compute_type_hash();
+    if round_tripped != t.type_hash {
+        return Err(format!(
+            "Type hash {} doesn't match the hash computed from the serialized type description: {round_tripped}",
+            t.type_hash
+        ));
+    }
+    // the hashes of the dependencies must be those of the types of the registry
+    let mismatches: Vec<String> = t
+        .type_description
+        .type_hashes
+        .iter()
+        .filter_map(|h| {
+            let dependency = registry.get_type(&h.type_name)?;
+            (dependency.type_hash != h.hash_string).then(|| {
+                format!(
+                    "{} ({} in the registry, {} in the type description)",
+                    h.type_name, dependency.type_hash, h.hash_string
+                )
+            })
+        })
+        .collect();
+    if !mismatches.is_empty() {
+        return Err(format!(
+            "Dependencies with another type hash in the registry: {}",
+            mismatches.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+fn check_mcap_schema(registry: &Registry, t: &TypeInfo) -> Result<(), String> {
+    let missing = registry.get_missing_dependencies(t);
+    if !missing.is_empty() {
+        return Err(format!(
+            "Dependencies not found in the registry: {}",
+            missing.join(", ")
+        ));
+    }
+    registry.build_mcap_schema(t).map(|_| ())
+}
+
+fn check_default_instance(t: &TypeInfo) -> Result<(), String> {
+    let description = &t.type_description.type_description_msg;
+    let instance = default_instance::default_instance(description);
+    let report = validation::validate(&instance, description);
+    if report.valid {
+        return Ok(());
+    }
+    let errors: Vec<String> = report
+        .errors
+        .iter()
+        .map(|e| format!("{}: {}", e.path, e.message))
+        .collect();
+    Err(format!("Invalid default instance: {}", errors.join(", ")))
+}
@@ -0,0 +1,215 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+// The standalone registry as a Windows service, managed by the Service Control Manager (SCM):
+// `--install-service` registers it with the other arguments of its command line (plus `--service`,
+// with which the SCM starts it), `--uninstall-service` removes it. Under the SCM, the Stop and
+// Shutdown controls stop the registry, and the ParamChange control (e.g.
+// `sc control <NAME> paramchange`) rescans it, as SIGHUP on Unix.
+use std::{
+    ffi::OsString,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use futures::channel::{mpsc, oneshot};
+use windows_service::{
+    define_windows_service,
+    service::{
+        ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+        ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+    },
+    service_control_handler::{self, ServiceControlHandlerResult},
+    service_dispatcher,
+    service_manager::{ServiceManager, ServiceManagerAccess},
+};
+
+use crate::{args::Args, telemetry::LogFilterHandle};
+
+const SERVICE_TYPE: ServiceType = ServiceType::OWN_PROCESS;
+
+// The registry to run once the SCM calls the service main function (which has no context): the
+// runtime to run it on, its arguments and its logging
+static PENDING: Mutex<Option<(tokio::runtime::Handle, Args, LogFilterHandle)>> = Mutex::new(None);
+
+// The senders of the reload requests to each registry
+static RELOAD_SENDERS: Mutex<Vec<mpsc::UnboundedSender<()>>> = Mutex::new(Vec::new());
+
+static SERVICE_NAME: OnceLock<String> = OnceLock::new();
+
+define_windows_service!(ffi_service_main, service_main);
+
+// Register the registry as an automatically started service, run with those arguments
+pub(crate) fn install(name: &str, arguments: Vec<OsString>) -> Result<(), String> {
+    let manager = ServiceManager::local_computer(
+        None::<&str>,
+        ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
+    )
+    .map_err(|e| format!("Failed to connect to the Service Control Manager: {e}"))?;
+    let executable_path =
+        std::env::current_exe().map_err(|e| format!("Failed to get the executable path: {e}"))?;
+    let info = ServiceInfo {
+        name: name.into(),
+        display_name: format!("ROS 2 types registry ({name})").into(),
+        service_type: SERVICE_TYPE,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path,
+        launch_arguments: arguments,
+        dependencies: Vec::new(),
+        // the LocalSystem account
+        account_name: None,
+        account_password: None,
+    };
+    let service = manager
+        .create_service(&info, ServiceAccess::CHANGE_CONFIG)
+        .map_err(|e| format!("Failed to install service '{name}': {e}"))?;
+    service
+        .set_description("Serves the ROS 2 types descriptions and definitions over Zenoh")
+        .map_err(|e| format!("Failed to set the description of service '{name}': {e}"))
+}
+
+// Stop and remove the service
+pub(crate) fn uninstall(name: &str) -> Result<(), String> {
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        .map_err(|e| format!("Failed to connect to the Service Control Manager: {e}"))?;
+    let service = manager
+        .open_service(
+            name,
+            ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE,
+        )
+        .map_err(|e| format!("Failed to open service '{name}': {e}"))?;
+    // the service is marked for deletion, and removed once stopped
+    service
+        .delete()
+        .map_err(|e| format!("Failed to uninstall service '{name}': {e}"))?;
+    let status = service
+        .query_status()
+        .map_err(|e| format!("Failed to query the status of service '{name}': {e}"))?;
+    if status.current_state != ServiceState::Stopped {
+        service
+            .stop()
+            .map_err(|e| format!("Failed to stop service '{name}': {e}"))?;
+    }
+    Ok(())
+}
+
+// Run the registry under the SCM, on this runtime, until the service is stopped (blocking the
+// calling thread, as required by the SCM)
+pub(crate) fn run(
+    name: &str,
+    runtime: tokio::runtime::Handle,
+    args: Args,
+    log_filter_handle: LogFilterHandle,
+) -> Result<(), String> {
+    let _ = SERVICE_NAME.set(name.to_string());
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some((runtime, args, log_filter_handle));
+    }
+    service_dispatcher::start(name, ffi_service_main).map_err(|e| {
+        format!("Failed to run service '{name}' (not started by the Service Control Manager?): {e}")
+    })
+}
+
+// Return a receiver of the reload requests of the SCM
+pub(crate) fn reload_requests() -> mpsc::UnboundedReceiver<()> {
+    let (tx, rx) = mpsc::unbounded();
+    if let Ok(mut senders) = RELOAD_SENDERS.lock() {
+        senders.push(tx);
+    }
+    rx
+}
+
+fn notify_reload() {
+    if let Ok(mut senders) = RELOAD_SENDERS.lock() {
+        senders.retain(|tx| tx.unbounded_send(()).is_ok());
+    }
+}
+
+fn service_main(_arguments: Vec<OsString>) {
+    if let Err(e) = run_service() {
+        tracing::error!("{e}");
+    }
+}
+
+fn run_service() -> Result<(), String> {
+    let name = SERVICE_NAME.get().map(String::as_str).unwrap_or_default();
+    let (runtime, args, log_filter_handle) = PENDING
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take())
+        .ok_or("Service started without registry to run")?;
+
+    let (stop_tx, stop_rx) = oneshot::channel();
+    let mut stop_tx = Some(stop_tx);
+    let status_handle = service_control_handler::register(name, move |control| match control {
+        ServiceControl::Stop | ServiceControl::Shutdown => {
+            if let Some(stop_tx) = stop_tx.take() {
+                let _ = stop_tx.send(());
+            }
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::ParamChange => {
+            notify_reload();
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    })
+    .map_err(|e| format!("Failed to register the control handler of service '{name}': {e}"))?;
+    let set_status = |current_state, controls_accepted, exit_code| {
+        status_handle
+            .set_service_status(ServiceStatus {
+                service_type: SERVICE_TYPE,
+                current_state,
+                controls_accepted,
+                exit_code,
+                checkpoint: 0,
+                wait_hint: Duration::default(),
+                process_id: None,
+            })
+            .map_err(|e| format!("Failed to set the status of service '{name}': {e}"))
+    };
+
+    set_status(
+        ServiceState::Running,
+        ServiceControlAccept::STOP
+            | ServiceControlAccept::SHUTDOWN
+            | ServiceControlAccept::PARAM_CHANGE,
+        ServiceExitCode::Win32(0),
+    )?;
+    tracing::info!("Service '{name}' running");
+    // the registry runs until a failure or the stop of the service
+    let result = runtime.block_on(async {
+        match futures::future::select(Box::pin(crate::start(args, log_filter_handle)), stop_rx)
+            .await
+        {
+            futures::future::Either::Left((result, _)) => result,
+            futures::future::Either::Right(_) => {
+                tracing::info!("Service '{name}' stopped");
+                Ok(())
+            }
+        }
+    });
+    let exit_code = match &result {
+        Ok(()) => ServiceExitCode::Win32(0),
+        Err(e) => {
+            tracing::error!("{e}");
+            ServiceExitCode::ServiceSpecific(1)
+        }
+    };
+    set_status(
+        ServiceState::Stopped,
+        ServiceControlAccept::empty(),
+        exit_code,
+    )
+}
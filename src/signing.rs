@@ -0,0 +1,57 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Optional signing of the replies payloads with an ed25519 key, the signature being sent in the
+// replies attachments (as "signature=<base64>"). It lets the consumers verify that a payload comes
+// from the trusted registry and wasn't altered, e.g. when stored by an intermediary.
+use std::{path::Path, sync::OnceLock};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+
+// The signing key, set at startup if configured
+static SIGNING_KEY: OnceLock<Ed25519KeyPair> = OnceLock::new();
+
+// Load the signing key from a PKCS#8 file, PEM or DER encoded (e.g. generated with
+// `openssl genpkey -algorithm ed25519 -out key.pem`), returning its public key (base64)
+pub(crate) fn init(path: &Path) -> Result<String, String> {
+    let content =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let der = match std::str::from_utf8(&content) {
+        Ok(pem) if pem.trim_start().starts_with("-----BEGIN") => {
+            let base64: String = pem
+                .lines()
+                .filter(|line| !line.starts_with("-----"))
+                .map(str::trim)
+                .collect();
+            BASE64_STANDARD
+                .decode(base64)
+                .map_err(|e| format!("Invalid PEM file {}: {e}", path.display()))?
+        }
+        _ => content,
+    };
+    let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(&der)
+        .map_err(|e| format!("Invalid ed25519 PKCS#8 key in {}: {e}", path.display()))?;
+    let public_key = BASE64_STANDARD.encode(key_pair.public_key().as_ref());
+    SIGNING_KEY
+        .set(key_pair)
+        .map_err(|_| "Signing key already set".to_string())?;
+    Ok(public_key)
+}
+
+// Return the signature of a payload (base64), if a signing key is set
+pub(crate) fn sign(payload: &[u8]) -> Option<String> {
+    SIGNING_KEY
+        .get()
+        .map(|key_pair| BASE64_STANDARD.encode(key_pair.sign(payload).as_ref()))
+}
@@ -0,0 +1,133 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Snapshot of the whole registry content in a single binary file, to move its exact state to
+// another host (e.g. an air-gapped analysis machine) or archive it with a release.
+// The file starts with the "RTS1" magic, followed by the bincode encoding of the list of entries.
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use zenoh::key_expr::keyexpr;
+
+use crate::{registry::Registry, type_info::TypeInfo};
+
+// Magic number (with the format version) at the start of the snapshot files
+const SNAPSHOT_MAGIC: &[u8; 4] = b"RTS1";
+
+// The snapshot file embedded at build time (see build.rs)
+#[cfg(feature = "embedded")]
+static EMBEDDED_SNAPSHOT: &[u8] = include_bytes!(env!("ROS2_TYPES_REGISTRY_EMBEDDED_SNAPSHOT"));
+
+// A type in a snapshot
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SnapshotEntry {
+    pub type_name: String,        // e.g. "std_msgs/msg/String"
+    pub kind: String,             // "MSG", "SRV" or "ACTION"
+    pub type_hash: String,        // the type hash string, checked at import
+    pub description: String,      // the HashedTypeDescription, as in the .json file
+    pub definition: String,       // content of the original .msg/.srv/.action file
+    pub json_path: PathBuf,       // original path of the .json file
+    pub definition_path: PathBuf, // original path of the .msg/.srv/.action file
+}
+
+// Write a snapshot of all the types of the registry to a file, returning the number of types
+pub(crate) fn export_snapshot(registry: &mut Registry, path: &Path) -> Result<usize, String> {
+    let all_types = keyexpr::new("**").expect("valid key expression");
+    registry.load_pending_matching(all_types);
+
+    let mut entries = registry
+        .iter_types(all_types)
+        .map(|t| snapshot_entry(registry, t))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_unstable_by(|a, b| a.type_name.cmp(&b.type_name));
+
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    writer
+        .write_all(SNAPSHOT_MAGIC)
+        .and_then(|()| {
+            bincode::serialize_into(&mut writer, &entries)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+        })
+        .and_then(|()| writer.flush())
+        .map_err(|e| format!("Failed to write snapshot {}: {e}", path.display()))?;
+    Ok(entries.len())
+}
+
+// Return the snapshot entry of a type
+pub(crate) fn snapshot_entry(registry: &Registry, t: &TypeInfo) -> Result<SnapshotEntry, String> {
+    let description = serde_json::to_string(&t.type_description).map_err(|e| {
+        format!(
+            "Failed to serialize type description of {}: {e}",
+            t.full_name
+        )
+    })?;
+    let definition = registry.get_definition_content(t)?;
+    Ok(SnapshotEntry {
+        type_name: t.get_type_name().to_string(),
+        kind: t.kind.as_ref().to_string(),
+        type_hash: t.type_hash.clone(),
+        description,
+        definition: definition.to_string(),
+        json_path: t.json_path.clone(),
+        definition_path: t.definition_path.clone(),
+    })
+}
+
+// Encode a single snapshot entry (e.g. to transfer a type to a mirror registry)
+pub(crate) fn encode_entry(entry: &SnapshotEntry) -> Result<Vec<u8>, String> {
+    bincode::serialize(entry).map_err(|e| {
+        format!(
+            "Failed to encode snapshot entry of {}: {e}",
+            entry.type_name
+        )
+    })
+}
+
+// Decode a single snapshot entry
+pub(crate) fn decode_entry(bytes: &[u8]) -> Result<SnapshotEntry, String> {
+    bincode::deserialize(bytes).map_err(|e| format!("Failed to decode snapshot entry: {e}"))
+}
+
+// Read the entries of a snapshot file
+pub(crate) fn read_snapshot(path: &Path) -> Result<Vec<SnapshotEntry>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    decode_snapshot(BufReader::new(file))
+        .map_err(|e| format!("Failed to read snapshot {}: {e}", path.display()))
+}
+
+// Return the entries of the snapshot embedded at build time, if built with the "embedded" feature
+pub(crate) fn embedded_snapshot() -> Result<Option<Vec<SnapshotEntry>>, String> {
+    #[cfg(feature = "embedded")]
+    {
+        decode_snapshot(EMBEDDED_SNAPSHOT)
+            .map(Some)
+            .map_err(|e| format!("Failed to read embedded snapshot: {e}"))
+    }
+    #[cfg(not(feature = "embedded"))]
+    Ok(None)
+}
+
+fn decode_snapshot(mut reader: impl Read) -> Result<Vec<SnapshotEntry>, String> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(|e| e.to_string())?;
+    if &magic != SNAPSHOT_MAGIC {
+        return Err("not a snapshot (or with an unsupported version)".into());
+    }
+    bincode::deserialize_from(reader).map_err(|e| e.to_string())
+}
@@ -0,0 +1,203 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Mapping of the fields of a type to their declarations in the original definition files, for the
+// editors integrations jumping from a runtime field path (e.g. "pose.position.x") to its source.
+// The fields of the nested types are mapped recursively, to the definition files of those types.
+use serde::Serialize;
+
+use crate::{
+    documentation::{split_comment, ACTION_SECTIONS, SRV_SECTIONS},
+    registry::Registry,
+    reply::path_to_reply,
+    type_description::{IndividualTypeDescription, MAX_NESTING_DEPTH},
+    type_info::{TypeInfo, TypeKind},
+};
+
+// A type with its fields source lines, in sections (one for a message, the request and response
+// of a service, the goal, result and feedback of an action)
+#[derive(Debug, Serialize)]
+pub(crate) struct SourceLines<'a> {
+    type_name: &'a str,
+    sections: Vec<SourceLinesSection>,
+}
+
+#[derive(Debug, Serialize)]
+struct SourceLinesSection {
+    type_name: String,
+    fields: Vec<FieldSource>,
+}
+
+// The declaration of a field: the definition file of the type declaring it and the line number
+// (starting at 1) in this file
+#[derive(Debug, Serialize)]
+struct FieldSource {
+    field: String,
+    type_name: String,
+    path: String,
+    line: usize,
+}
+
+pub(crate) fn source_lines<'t>(
+    registry: &Registry,
+    t: &'t TypeInfo,
+) -> Result<SourceLines<'t>, String> {
+    check_is_msg_syntax(t)?;
+    let type_name = t.get_type_name();
+    let suffixes: &[&str] = match t.kind {
+        TypeKind::MSG => &[""],
+        TypeKind::SRV => SRV_SECTIONS,
+        TypeKind::ACTION => ACTION_SECTIONS,
+    };
+    let declarations = field_declarations(registry, t)?;
+    let mut sections = Vec::new();
+    for (suffix, declarations) in suffixes.iter().zip(declarations) {
+        let section_name = format!("{type_name}{suffix}");
+        let mut fields = Vec::new();
+        map_fields(
+            registry,
+            t,
+            &section_name,
+            &declarations,
+            "",
+            &mut Vec::new(),
+            &mut fields,
+        )?;
+        sections.push(SourceLinesSection {
+            type_name: section_name,
+            fields,
+        });
+    }
+    Ok(SourceLines {
+        type_name,
+        sections,
+    })
+}
+
+// Map the declared fields of a (section) type of `t`, and recursively the fields of their nested
+// types, prefixing their paths with `prefix`.
+// `stack` is the list of the types being mapped, to detect circular references
+fn map_fields(
+    registry: &Registry,
+    t: &TypeInfo,
+    type_name: &str,
+    declarations: &[(String, usize)],
+    prefix: &str,
+    stack: &mut Vec<String>,
+    fields: &mut Vec<FieldSource>,
+) -> Result<(), String> {
+    if stack.iter().any(|name| name == type_name) || stack.len() >= MAX_NESTING_DEPTH {
+        return Err(format!(
+            "Circular or too deep reference to type {type_name} (via {})",
+            stack.join(" -> ")
+        ));
+    }
+    stack.push(type_name.to_string());
+    let description = individual_description(t, type_name);
+    for (name, line) in declarations {
+        let field = format!("{prefix}{name}");
+        fields.push(FieldSource {
+            field: field.clone(),
+            type_name: type_name.to_string(),
+            path: path_to_reply(&t.definition_path),
+            line: *line,
+        });
+        let nested_type_name = description
+            .and_then(|d| d.fields.iter().find(|f| &*f.name == name))
+            .map(|f| &*f.r#type.nested_type_name)
+            .unwrap_or_default();
+        if nested_type_name.is_empty() {
+            continue;
+        }
+        let Some(nested) = registry.get_type(nested_type_name) else {
+            tracing::warn!(
+                "Nested type {nested_type_name} of {type_name} not found in registry - its fields are not mapped"
+            );
+            continue;
+        };
+        if check_is_msg_syntax(nested).is_err() {
+            continue;
+        }
+        let nested_declarations = field_declarations(registry, nested)?;
+        if let Some(nested_declarations) = nested_declarations.first() {
+            map_fields(
+                registry,
+                nested,
+                nested.get_type_name(),
+                nested_declarations,
+                &format!("{field}."),
+                stack,
+                fields,
+            )?;
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+// Return the description of a type or of one of its inner types
+fn individual_description<'a>(
+    t: &'a TypeInfo,
+    type_name: &str,
+) -> Option<&'a IndividualTypeDescription> {
+    let description = &t.type_description.type_description_msg;
+    std::iter::once(&description.type_description)
+        .chain(description.referenced_type_descriptions.iter())
+        .find(|d| &*d.type_name == type_name)
+}
+
+// Return the names and line numbers of the fields declared in each section of the definition
+// (the constants being skipped)
+fn field_declarations(
+    registry: &Registry,
+    t: &TypeInfo,
+) -> Result<Vec<Vec<(String, usize)>>, String> {
+    let content = registry.get_definition_content(t)?;
+    let mut sections = vec![Vec::new()];
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line == "---" {
+            sections.push(Vec::new());
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (declaration, _) = split_comment(line);
+        let Some((field_type, rest)) = declaration.split_once(char::is_whitespace) else {
+            continue;
+        };
+        // a constant declares a "<NAME>=<value>", with a non-array type
+        if let Some((constant_name, _)) = rest.split_once('=') {
+            if !field_type.contains('[') && !constant_name.trim().contains(char::is_whitespace) {
+                continue;
+            }
+        }
+        let name = rest.split_whitespace().next().unwrap_or_default();
+        sections
+            .last_mut()
+            .expect("at least one section")
+            .push((name.to_string(), index + 1));
+    }
+    Ok(sections)
+}
+
+fn check_is_msg_syntax(t: &TypeInfo) -> Result<(), String> {
+    match t.definition_path.extension() {
+        Some(extension) if extension == "idl" => Err(format!(
+            "Source lines are only available for .msg/.srv/.action definitions, not for {}",
+            t.definition_path.display()
+        )),
+        _ => Ok(()),
+    }
+}
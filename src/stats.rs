@@ -0,0 +1,193 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use zenoh::query::Query;
+
+use crate::{
+    access_log::{AccessLog, AccessLogEntry, QueryStats},
+    ReplyFormat,
+};
+
+// Upper bounds (in ms) of the latency histogram buckets (the last bucket is unbounded)
+const LATENCY_BUCKETS_MS: &[u64] = &[1, 5, 10, 50, 100, 500, 1000, 5000];
+
+// Statistics on the queries received by the registry
+#[derive(Debug)]
+pub(crate) struct Statistics {
+    // number of replies per type and format
+    popularity: HashMap<(String, ReplyFormat), u64>,
+    // latency histogram per queryable (e.g. "@ros2_types")
+    latencies: BTreeMap<String, LatencyHistogram>,
+    // queries taking longer than this threshold are reported with a warning
+    slow_query_threshold: Duration,
+}
+
+// Histogram of the queries handling durations
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct LatencyHistogram {
+    pub count: u64,
+    pub sum_us: u128,
+    pub max_us: u128,
+    pub buckets: Vec<LatencyBucket>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct LatencyBucket {
+    pub le_ms: Option<u64>, // upper bound of the bucket, None for the unbounded last bucket
+    pub count: u64,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            buckets: LATENCY_BUCKETS_MS
+                .iter()
+                .map(|ms| Some(*ms))
+                .chain(std::iter::once(None))
+                .map(|le_ms| LatencyBucket { le_ms, count: 0 })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn record(&mut self, duration: Duration) {
+        let us = duration.as_micros();
+        self.count += 1;
+        self.sum_us += us;
+        self.max_us = self.max_us.max(us);
+        if let Some(bucket) = self
+            .buckets
+            .iter_mut()
+            .find(|b| b.le_ms.map_or(true, |ms| us <= u128::from(ms) * 1000))
+        {
+            bucket.count += 1;
+        }
+    }
+}
+
+// A query being handled, for monitoring purposes
+pub(crate) struct QueryRecord {
+    key_expr: String,
+    start: Instant,
+    access_log_entry: Option<AccessLogEntry>,
+}
+
+impl QueryRecord {
+    // Start monitoring a received query
+    pub fn start(query: &Query, access_log: Option<&AccessLog>) -> Self {
+        Self {
+            key_expr: query.key_expr().to_string(),
+            start: Instant::now(),
+            access_log_entry: access_log.map(|log| log.start(query)),
+        }
+    }
+
+    // Complete the monitoring of a handled query, updating the statistics and the access log
+    pub fn finish(
+        self,
+        stats: QueryStats,
+        statistics: &mut Statistics,
+        access_log: Option<&AccessLog>,
+    ) {
+        statistics.record_query_latency(&self.key_expr, self.start.elapsed());
+        if let (Some(log), Some(entry)) = (access_log, self.access_log_entry) {
+            log.finish(entry, stats);
+        }
+    }
+}
+
+// An entry of the popularity report
+#[derive(Debug, Serialize)]
+pub(crate) struct PopularityEntry<'a> {
+    pub type_name: &'a str,
+    pub format: &'a str,
+    pub count: u64,
+}
+
+impl Statistics {
+    pub fn new(slow_query_threshold: Duration) -> Self {
+        Self {
+            popularity: HashMap::new(),
+            latencies: BTreeMap::new(),
+            slow_query_threshold,
+        }
+    }
+
+    // Reset the accumulated statistics, returning the number of queries recorded until then
+    pub fn reset(&mut self) -> u64 {
+        let queries = self.latencies.values().map(|h| h.count).sum();
+        self.popularity.clear();
+        self.latencies.clear();
+        queries
+    }
+
+    // Record the time spent handling a query, warning if it's a slow query
+    pub fn record_query_latency(&mut self, key_expr: &str, duration: Duration) {
+        if duration > self.slow_query_threshold {
+            tracing::warn!(
+                "Slow query: {key_expr} took {} ms (threshold: {} ms)",
+                duration.as_millis(),
+                self.slow_query_threshold.as_millis()
+            );
+        }
+        // histograms are per queryable, i.e. per first chunk of the key expression after the
+        // tenant namespace, if any (the first one starting with '@')
+        let queryable = key_expr
+            .split('/')
+            .find(|chunk| chunk.starts_with('@'))
+            .unwrap_or_default();
+        self.latencies
+            .entry(queryable.to_string())
+            .or_insert_with(LatencyHistogram::new)
+            .record(duration);
+    }
+
+    // Return the latency histograms per queryable
+    pub fn latencies(&self) -> &BTreeMap<String, LatencyHistogram> {
+        &self.latencies
+    }
+
+    // Record that a type was requested with a format
+    pub fn record_type_query(&mut self, type_name: &str, format: ReplyFormat) {
+        *self
+            .popularity
+            .entry((type_name.to_string(), format))
+            .or_default() += 1;
+    }
+
+    // Return the N most requested types and formats, by decreasing count
+    pub fn top_types(&self, n: usize) -> Vec<PopularityEntry> {
+        let mut entries: Vec<PopularityEntry> = self
+            .popularity
+            .iter()
+            .map(|((type_name, format), count)| PopularityEntry {
+                type_name,
+                format: format.as_ref(),
+                count: *count,
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.type_name.cmp(b.type_name))
+                .then_with(|| a.format.cmp(b.format))
+        });
+        entries.truncate(n);
+        entries
+    }
+}
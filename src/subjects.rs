@@ -0,0 +1,435 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// A compatibility layer with the semantics of the Kafka (Confluent) Schema Registry on top of the
+// versioned store: the subjects are the type names, and their versions are numbered from 1 in the
+// order in which they were loaded. Available under the @ros2_subjects/** key space:
+//   @ros2_subjects/list                                  => GET /subjects
+//   @ros2_subjects/config                                => GET /config
+//   @ros2_subjects/<type_name>/versions                  => GET /subjects/<subject>/versions
+//   @ros2_subjects/<type_name>/versions/<version|latest> => GET /subjects/<subject>/versions/<version>
+//   @ros2_subjects/<type_name>/compatibility             => POST /compatibility/subjects/<subject>/versions/latest
+// The compatibility of the versions is positional, as the CDR encoding of the ROS messages: a reader
+// version can read the data of a writer version if each of its fields (recursively in the nested
+// types) is at the same position in the writer version with the same name and type. The writer
+// version can only have additional fields at the end of the top-level type.
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::json;
+use strum::{AsRefStr, EnumString};
+use zenoh::{
+    bytes::Encoding,
+    key_expr::{keyexpr, OwnedKeyExpr},
+    query::Query,
+};
+
+use crate::{
+    access_log::QueryStats,
+    keformat_ros2_subjects, query_reply_ke,
+    registry::Registry,
+    send_reply, send_reply_err, strip_namespace,
+    type_description::{self, IndividualTypeDescription, TypeDescription, MAX_NESTING_DEPTH},
+    type_info::TypeInfo,
+    type_name, ErrorCode,
+};
+
+// Commands available in the @ros2_subjects/** key space
+const SUBJECTS_COMMANDS: &[&str] = &[
+    "list",
+    "config",
+    "<type_name>/versions",
+    "<type_name>/versions/<version>",
+    "<type_name>/compatibility",
+];
+
+// Schema type of the replied versions (the schema being the .msg/.srv/.action definition)
+const SCHEMA_TYPE: &str = "ROS2MSG";
+
+// Compatibility mode between the versions of a subject, as in the Confluent Schema Registry
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, AsRefStr, EnumString)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE", ascii_case_insensitive)]
+pub(crate) enum CompatibilityMode {
+    None, // no compatibility check
+    #[default]
+    Backward, // the new version can read the data of the previous one
+    BackwardTransitive, // the new version can read the data of all the previous ones
+    Forward, // the previous version can read the data of the new one
+    ForwardTransitive, // all the previous versions can read the data of the new one
+    Full, // both backward and forward with the previous version
+    FullTransitive, // both backward and forward with all the previous versions
+}
+
+impl CompatibilityMode {
+    fn is_transitive(&self) -> bool {
+        matches!(
+            self,
+            Self::BackwardTransitive | Self::ForwardTransitive | Self::FullTransitive
+        )
+    }
+
+    fn is_backward(&self) -> bool {
+        matches!(
+            self,
+            Self::Backward | Self::BackwardTransitive | Self::Full | Self::FullTransitive
+        )
+    }
+
+    fn is_forward(&self) -> bool {
+        matches!(
+            self,
+            Self::Forward | Self::ForwardTransitive | Self::Full | Self::FullTransitive
+        )
+    }
+}
+
+// A version of a subject
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SubjectVersion<'a> {
+    subject: &'a str,
+    version: usize,
+    type_hash: &'a str,
+    schema_type: &'a str,
+    schema: &'a str,
+}
+
+#[tracing::instrument(skip_all, fields(key_expr = %query.key_expr()))]
+pub(crate) async fn handle_ros2_subjects_query(
+    query: Query,
+    registry: &mut Registry<'_>,
+    mode: CompatibilityMode,
+) -> QueryStats {
+    tracing::debug!("Received query: {}", query.key_expr());
+    let mut stats = QueryStats::default();
+    let ke = match keformat_ros2_subjects::parse(strip_namespace(query.key_expr())) {
+        Ok(ke) => ke,
+        Err(_) => {
+            tracing::error!(
+                "Received a query on '{}' but it doesn't match the '@ros2_subjects/**' queryable!",
+                query.key_expr()
+            );
+            return stats;
+        }
+    };
+    let Some(command) = ke.command() else {
+        return stats;
+    };
+    let command = command.as_str();
+
+    let reply = match command {
+        "list" => {
+            let all_types = keyexpr::new("**").expect("valid key expression");
+            registry.load_pending_matching(all_types);
+            let subjects: BTreeSet<String> = registry
+                .iter_types(all_types)
+                .map(|t| &*t.full_name)
+                .chain(registry.iter_history_names())
+                .map(|name| type_name::unescape(name.as_str()).into_owned())
+                .collect();
+            Ok(json!(subjects))
+        }
+        "config" => Ok(json!({"compatibilityLevel": mode.as_ref()})),
+        c => match parse_subject_command(c) {
+            Some((subject, operation)) => {
+                registry.load_pending_matching(&subject);
+                subject_reply(&query, registry, &subject, operation, mode)
+            }
+            None => Err((
+                ErrorCode::UnknownOperation,
+                format!("Unknown command '{command}' - available: {SUBJECTS_COMMANDS:?}"),
+            )),
+        },
+    };
+
+    match reply.and_then(|json| {
+        serde_json::to_string(&json).map_err(|e| {
+            (
+                ErrorCode::SerializationFailed,
+                format!("Failed to serialize reply: {e}"),
+            )
+        })
+    }) {
+        Ok(json) => {
+            stats.matched = 1;
+            stats.bytes_sent += send_reply(
+                &query,
+                query_reply_ke(&query, registry.get_namespace()),
+                json,
+                Encoding::APPLICATION_JSON,
+            )
+            .await;
+        }
+        Err((code, e)) => send_reply_err(&query, code, e).await,
+    }
+    stats
+}
+
+// Split a command on a subject into the subject (a type name without wildcards) and the operation
+// (e.g. "versions/latest")
+fn parse_subject_command(command: &str) -> Option<(OwnedKeyExpr, &str)> {
+    let (subject, operation) = ["/versions", "/compatibility"]
+        .iter()
+        .find_map(|op| command.find(op).map(|pos| command.split_at(pos)))?;
+    let subject = OwnedKeyExpr::new(subject).ok()?;
+    if subject.is_wild() {
+        return None;
+    }
+    Some((subject, &operation[1..]))
+}
+
+fn subject_reply(
+    query: &Query,
+    registry: &Registry,
+    subject: &keyexpr,
+    operation: &str,
+    mode: CompatibilityMode,
+) -> Result<serde_json::Value, (ErrorCode, String)> {
+    let subject_name = type_name::unescape(subject.as_str());
+    let (dropped, versions) = registry.get_type_versions(subject);
+    if versions.is_empty() {
+        return Err((
+            ErrorCode::NotFound,
+            format!("Subject '{subject_name}' not found"),
+        ));
+    }
+    // the version numbers, starting at 1 for the first version loaded (even if removed by the
+    // history GC since then)
+    let first = dropped + 1;
+    let last = dropped + versions.len();
+
+    match operation {
+        "versions" => Ok(json!((first..=last).collect::<Vec<_>>())),
+        "compatibility" => {
+            // the 'compatibility' parameter overrides the configured mode
+            let mode = match query.parameters().get("compatibility") {
+                Some(m) => m.parse::<CompatibilityMode>().map_err(|_| {
+                    (
+                        ErrorCode::InvalidParameter,
+                        format!("Unknown compatibility mode '{m}'"),
+                    )
+                })?,
+                None => mode,
+            };
+            // the candidate version is the rosidl JSON type description in the query payload,
+            // or the latest version if none
+            let candidate = match query.payload().map(|p| p.try_to_string()) {
+                Some(Ok(json)) => Some(
+                    type_description::from_json(&json)
+                        .map(|(description, _)| description)
+                        .map_err(|e| {
+                            (
+                                ErrorCode::InvalidPayload,
+                                format!("Invalid JSON type description in query payload: {e}"),
+                            )
+                        })?,
+                ),
+                Some(Err(e)) => {
+                    return Err((
+                        ErrorCode::InvalidPayload,
+                        format!("Query payload is not a valid UTF-8 string: {e}"),
+                    ))
+                }
+                None => None,
+            };
+            let (candidate, previous) = match &candidate {
+                Some(candidate) => (&candidate.type_description_msg, &versions[..]),
+                None => (
+                    &versions[versions.len() - 1]
+                        .type_description
+                        .type_description_msg,
+                    &versions[..versions.len() - 1],
+                ),
+            };
+            let messages = check_compatibility(candidate, previous, first, mode);
+            Ok(json!({
+                "compatibilityLevel": mode.as_ref(),
+                "is_compatible": messages.is_empty(),
+                "messages": messages,
+            }))
+        }
+        op => {
+            let version = match op.strip_prefix("versions/") {
+                Some("latest") => last,
+                Some(v) => v.parse::<usize>().map_err(|_| {
+                    (
+                        ErrorCode::InvalidParameter,
+                        format!("Invalid version '{v}' - expected a number or 'latest'"),
+                    )
+                })?,
+                None => {
+                    return Err((
+                        ErrorCode::UnknownOperation,
+                        format!("Unknown operation '{op}' - available: {SUBJECTS_COMMANDS:?}"),
+                    ))
+                }
+            };
+            let t = version
+                .checked_sub(first)
+                .and_then(|index| versions.get(index))
+                .ok_or((
+                    ErrorCode::NotFound,
+                    format!("Version {version} of subject '{subject_name}' not found"),
+                ))?;
+            subject_version(registry, &subject_name, version, t)
+        }
+    }
+}
+
+fn subject_version(
+    registry: &Registry,
+    subject: &str,
+    version: usize,
+    t: &TypeInfo,
+) -> Result<serde_json::Value, (ErrorCode, String)> {
+    let definition = registry
+        .get_definition_content(t)
+        .map_err(|e| (ErrorCode::RenderingFailed, e))?;
+    serde_json::to_value(SubjectVersion {
+        subject,
+        version,
+        type_hash: &t.type_hash,
+        schema_type: SCHEMA_TYPE,
+        schema: &definition,
+    })
+    .map_err(|e| {
+        (
+            ErrorCode::SerializationFailed,
+            format!("Failed to serialize version {version} of subject '{subject}': {e}"),
+        )
+    })
+}
+
+// Check the compatibility of a candidate version with the previous versions (oldest first,
+// the first one having the number `first`), returning the incompatibilities
+fn check_compatibility(
+    candidate: &TypeDescription,
+    previous: &[&TypeInfo],
+    first: usize,
+    mode: CompatibilityMode,
+) -> Vec<String> {
+    let checked = match (mode, previous.len()) {
+        (CompatibilityMode::None, _) | (_, 0) => 0..0,
+        (m, n) if m.is_transitive() => 0..n,
+        (_, n) => n - 1..n,
+    };
+    let mut messages = Vec::new();
+    for index in checked {
+        let version = &previous[index].type_description.type_description_msg;
+        let number = first + index;
+        if mode.is_backward() {
+            messages.extend(
+                can_read(candidate, version)
+                    .into_iter()
+                    .map(|m| format!("can't read version {number}: {m}")),
+            );
+        }
+        if mode.is_forward() {
+            messages.extend(
+                can_read(version, candidate)
+                    .into_iter()
+                    .map(|m| format!("not readable by version {number}: {m}")),
+            );
+        }
+    }
+    messages
+}
+
+// Return the fields of the reader description which are not at the same position in the writer one
+// with the same name and type, and the additional fields of the writer nested types
+fn can_read(reader: &TypeDescription, writer: &TypeDescription) -> Vec<String> {
+    let mut messages = Vec::new();
+    compare_fields(
+        reader,
+        &reader.type_description,
+        writer,
+        &writer.type_description,
+        "",
+        &mut messages,
+    );
+    messages
+}
+
+fn compare_fields(
+    reader: &TypeDescription,
+    reader_type: &IndividualTypeDescription,
+    writer: &TypeDescription,
+    writer_type: &IndividualTypeDescription,
+    prefix: &str,
+    messages: &mut Vec<String>,
+) {
+    if prefix.matches('.').count() >= MAX_NESTING_DEPTH {
+        messages.push(format!("too deep nesting at '{prefix}'"));
+        return;
+    }
+    // the additional fields at the end of the top-level type are just not read, while those of
+    // a nested type would shift the following fields
+    if !prefix.is_empty() {
+        for field in writer_type.fields.iter().skip(reader_type.fields.len()) {
+            messages.push(format!("additional field '{prefix}{}'", field.name));
+        }
+    }
+    for (index, field) in reader_type.fields.iter().enumerate() {
+        let path = format!("{prefix}{}", field.name);
+        let Some(writer_field) = writer_type.fields.get(index) else {
+            messages.push(format!("missing field '{path}'"));
+            continue;
+        };
+        if writer_field.name != field.name {
+            messages.push(format!(
+                "field '{path}' is at the position of field '{prefix}{}'",
+                writer_field.name
+            ));
+            continue;
+        }
+        let (r, w) = (&field.r#type, &writer_field.r#type);
+        if r.type_id != w.type_id
+            || r.capacity != w.capacity
+            || r.string_capacity != w.string_capacity
+            || r.nested_type_name != w.nested_type_name
+        {
+            messages.push(format!("field '{path}' has another type"));
+            continue;
+        }
+        if r.nested_type_name.is_empty() {
+            continue;
+        }
+        match (
+            referenced(reader, &r.nested_type_name),
+            referenced(writer, &w.nested_type_name),
+        ) {
+            (Some(reader_nested), Some(writer_nested)) => compare_fields(
+                reader,
+                reader_nested,
+                writer,
+                writer_nested,
+                &format!("{path}."),
+                messages,
+            ),
+            _ => messages.push(format!(
+                "description of type {} of field '{path}' not found",
+                r.nested_type_name
+            )),
+        }
+    }
+}
+
+fn referenced<'a>(
+    description: &'a TypeDescription,
+    type_name: &str,
+) -> Option<&'a IndividualTypeDescription> {
+    description
+        .referenced_type_descriptions
+        .iter()
+        .find(|d| &*d.type_name == type_name)
+}
@@ -0,0 +1,71 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// A gzip'd tar archive of the definitions (and optionally the JSON type descriptions) of several
+// types, for the provisioning scripts to pull e.g. all the interfaces of a package in one reply.
+// Each type has a `<package>/<kind>/<Name>.<msg|srv|action|idl>` entry (and a
+// `<package>/<kind>/<Name>.json` entry). The archive is deterministic (sorted entries, zero mtime),
+// so its content hash only changes with the types.
+use flate2::{write::GzEncoder, Compression};
+
+use crate::{registry::Registry, type_info::TypeInfo};
+
+// Build the archive of those types
+pub(crate) fn build_tarball(
+    registry: &Registry,
+    types: &[&TypeInfo],
+    with_json: bool,
+) -> Result<Vec<u8>, String> {
+    let mut types = types.to_vec();
+    types.sort_unstable_by(|a, b| a.full_name.cmp(&b.full_name));
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for t in types {
+        let extension = t
+            .definition_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or(t.kind.as_ref());
+        let definition = registry.get_definition_content(t)?;
+        append_entry(
+            &mut builder,
+            &format!("{}.{extension}", t.get_type_name()),
+            definition.as_bytes(),
+        )?;
+        if with_json {
+            append_entry(
+                &mut builder,
+                &format!("{}.json", t.get_type_name()),
+                t.get_description_json()?.as_bytes(),
+            )?;
+        }
+    }
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|e| format!("Failed to build tar archive: {e}"))
+}
+
+fn append_entry(
+    builder: &mut tar::Builder<GzEncoder<Vec<u8>>>,
+    path: &str,
+    content: &[u8],
+) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(0);
+    builder
+        .append_data(&mut header, path, content)
+        .map_err(|e| format!("Failed to add {path} to tar archive: {e}"))
+}
@@ -0,0 +1,101 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use anyhow::anyhow;
+use tracing_subscriber::{
+    layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+use crate::args::Args;
+
+// Default log filter if RUST_LOG is not set
+const DEFAULT_LOG_FILTER: &str = "info";
+
+// Handle allowing to change the log filter at runtime
+pub(crate) type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+// Initialize logging, returning a handle to change the log filter at runtime.
+// If an OTLP endpoint is configured, the tracing spans are also exported to this
+// OpenTelemetry collector.
+#[cfg_attr(not(feature = "otlp"), allow(unused_variables))]
+pub(crate) fn init_logging(args: &Args) -> anyhow::Result<LogFilterHandle> {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let (filter, handle) = reload::Layer::new(filter);
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer());
+
+    #[cfg(feature = "otlp")]
+    let subscriber = subscriber.with(otlp_layer(args)?);
+
+    subscriber
+        .try_init()
+        .map_err(|err| anyhow!("failed to initialize logging: {err}"))?;
+
+    #[cfg(feature = "otlp")]
+    if let Some(endpoint) = &args.otlp_endpoint {
+        tracing::info!("Exporting traces to OTLP endpoint {endpoint}");
+    }
+    Ok(handle)
+}
+
+// Change the log filter (e.g. "info,zenoh_plugin_ros2_types_registry::registry=debug")
+pub(crate) fn set_log_filter(handle: &LogFilterHandle, filter: &str) -> Result<(), String> {
+    let filter = EnvFilter::try_new(filter).map_err(|e| format!("Invalid log filter: {e}"))?;
+    handle
+        .reload(filter)
+        .map_err(|e| format!("Failed to change log filter: {e}"))
+}
+
+// Return the current log filter
+pub(crate) fn get_log_filter(handle: &LogFilterHandle) -> Result<String, String> {
+    handle
+        .with_current(|filter| filter.to_string())
+        .map_err(|e| format!("Failed to get log filter: {e}"))
+}
+
+// Create the layer exporting the tracing spans via OTLP, if an endpoint is configured
+#[cfg(feature = "otlp")]
+fn otlp_layer<S>(
+    args: &Args,
+) -> anyhow::Result<
+    Option<tracing_opentelemetry::OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>>,
+>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::{trace::TracerProvider as _, KeyValue};
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+
+    let Some(endpoint) = args.otlp_endpoint.as_deref() else {
+        return Ok(None);
+    };
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|err| anyhow!("failed to create OTLP exporter for {endpoint}: {err}"))?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            env!("CARGO_PKG_NAME"),
+        )]))
+        .build();
+    let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+    opentelemetry::global::set_tracer_provider(provider);
+
+    Ok(Some(tracing_opentelemetry::layer().with_tracer(tracer)))
+}
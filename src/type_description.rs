@@ -11,53 +11,339 @@
 //   Julien Enoch, <julien.enoch@zettascale.tech>
 //
 //
+use std::sync::Arc;
+
+use ring::digest::{digest, SHA256};
 use serde::{Deserialize, Serialize};
 
-use crate::field_type::FieldTypeId;
+use crate::{
+    field_type::FieldTypeId,
+    interner::{self, deserialize_interned},
+};
+
+// Parse a HashedTypeDescription from JSON, ignoring the unknown fields (e.g. added by newer
+// rosidl versions) and returning their paths along with the description.
+// The fields absent from the JSON files generated by some distros (e.g. the field default values
+// or the strings capacities) are normalized to their default values.
+pub fn from_json(json: &str) -> Result<(HashedTypeDescription, Vec<String>), serde_json::Error> {
+    let mut unknown_fields = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let description = serde_ignored::deserialize(&mut deserializer, |path| {
+        unknown_fields.push(path.to_string())
+    })?;
+    deserializer.end()?;
+    Ok((description, unknown_fields))
+}
+
+// Maximum nesting depth of types when traversing their dependencies, to guard against
+// runaway recursion with malformed descriptions
+pub const MAX_NESTING_DEPTH: usize = 64;
+
+// Versions of the type hashes (and thus of the HashedTypeDescription schema) supported
+pub const SUPPORTED_SCHEMA_VERSIONS: &[&str] = &["RIHS01"];
+
+// Return the schema version of a type hash, i.e. its prefix (e.g. "RIHS01" for "RIHS01_1234...")
+pub fn schema_version(type_hash: &str) -> &str {
+    type_hash.split('_').next().unwrap_or_default()
+}
+
+// Prefix of the type hashes computed with the version 1 of the ROS type hash algorithm
+const RIHS01_PREFIX: &str = "RIHS01_";
+
+// Compute the type hash (RIHS01) from the hashable representations of a type description and of
+// its referenced descriptions (sorted by name): the SHA-256 of the hashable representation of the
+// TypeDescription, as computed by rosidl
+pub(crate) fn type_hash(hashable_description: &str, hashable_referenced: &[String]) -> String {
+    let hashable = format!(
+        "{{\"type_description\": {hashable_description}, \"referenced_type_descriptions\": [{}]}}",
+        hashable_referenced.join(", ")
+    );
+    format!(
+        "{RIHS01_PREFIX}{}",
+        digest(&SHA256, hashable.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    )
+}
+
+// The hashable representation of a type description, as serialized by rosidl for the type hash:
+// without the fields default values, the keys in the order of the type_description_interfaces
+// messages, and the default (', ', ': ') separators of Python's json.dumps()
+pub(crate) fn hashable_description(description: &IndividualTypeDescription) -> String {
+    let fields: Vec<String> = description
+        .fields
+        .iter()
+        .map(|field| {
+            let field_type = &field.r#type;
+            format!(
+                "{{\"name\": {}, \"type\": {{\"type_id\": {}, \"capacity\": {}, \"string_capacity\": {}, \"nested_type_name\": {}}}}}",
+                json_string(&field.name),
+                field_type.type_id as u64,
+                field_type.capacity,
+                field_type.string_capacity,
+                json_string(&field_type.nested_type_name)
+            )
+        })
+        .collect();
+    format!(
+        "{{\"type_name\": {}, \"fields\": [{}]}}",
+        json_string(&description.type_name),
+        fields.join(", ")
+    )
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
 
 // Structure compliant with the rso2cli JSON schema defined in
 // https://github.com/ros2/rosidl/blob/kilted/rosidl_generator_type_description/resource/HashedTypeDescription.schema.json
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
 pub struct HashedTypeDescription {
     pub type_description_msg: TypeDescription,
     pub type_hashes: Vec<TypeNameAndHash>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
 pub struct TypeNameAndHash {
     pub type_name: String,
     pub hash_string: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
 pub struct TypeDescription {
     pub type_description: IndividualTypeDescription,
     pub referenced_type_descriptions: Vec<IndividualTypeDescription>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
 pub struct IndividualTypeDescription {
-    pub type_name: String,
+    #[serde(deserialize_with = "deserialize_interned")]
+    pub type_name: Arc<str>,
     pub fields: Vec<Field>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
 pub struct Field {
+    #[serde(default)]
     pub default_value: Option<String>,
-    pub name: String,
+    #[serde(deserialize_with = "deserialize_interned")]
+    pub name: Arc<str>,
     pub r#type: FieldType,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
 pub struct FieldType {
     pub type_id: FieldTypeId,
+    #[serde(default)]
     pub capacity: u32,
+    #[serde(default)]
     pub string_capacity: u32,
-    pub nested_type_name: String,
+    #[serde(
+        default = "interner::empty",
+        deserialize_with = "interner::deserialize_interned"
+    )]
+    pub nested_type_name: Arc<str>,
+}
+
+impl TypeDescription {
+    // Recompute the type hash of the type from its description and its referenced descriptions
+    pub(crate) fn compute_type_hash(&self) -> String {
+        let mut referenced: Vec<&IndividualTypeDescription> =
+            self.referenced_type_descriptions.iter().collect();
+        referenced.sort_by(|a, b| a.type_name.cmp(&b.type_name));
+        let hashable_referenced: Vec<String> =
+            referenced.into_iter().map(hashable_description).collect();
+        type_hash(
+            &hashable_description(&self.type_description),
+            &hashable_referenced,
+        )
+    }
+
+    // Return the fields of the type and of its dependencies with their pretty-printed types, one
+    // block per type (e.g. "std_msgs/msg/Header\n  builtin_interfaces/msg/Time stamp\n  ...")
+    pub(crate) fn pretty(&self) -> String {
+        let mut pretty = String::new();
+        for description in
+            std::iter::once(&self.type_description).chain(self.referenced_type_descriptions.iter())
+        {
+            if !pretty.is_empty() {
+                pretty.push('\n');
+            }
+            pretty.push_str(&description.type_name);
+            pretty.push('\n');
+            for field in &description.fields {
+                pretty.push_str(&format!("  {} {}", field.r#type.pretty(), field.name));
+                if let Some(default_value) =
+                    field.default_value.as_deref().filter(|v| !v.is_empty())
+                {
+                    pretty.push(' ');
+                    pretty.push_str(default_value);
+                }
+                pretty.push('\n');
+            }
+        }
+        pretty
+    }
+}
+
+impl FieldType {
+    // Return the type as ROS users read it: the element type as in the .msg definitions (with the
+    // full name of a nested type), followed by the size of an array or wrapped in a sequence,
+    // e.g. "float64", "string<=10[5]", "sequence<geometry_msgs/msg/Point, 16>" or "sequence<int32>"
+    pub fn pretty(&self) -> String {
+        let element = match self.type_id.msg_type_name() {
+            Some(name) if self.type_id.is_bounded_string() => {
+                format!("{name}<={}", self.string_capacity)
+            }
+            Some(name) => name.to_string(),
+            None => self.nested_type_name.to_string(),
+        };
+        if self.type_id.is_array() {
+            format!("{element}[{}]", self.capacity)
+        } else if self.type_id.is_bounded_sequence() {
+            format!("sequence<{element}, {}>", self.capacity)
+        } else if self.type_id.is_unbounded_sequence() {
+            format!("sequence<{element}>")
+        } else {
+            element
+        }
+    }
+}
+
+// Views of the descriptions with the exact field names, order and types of the
+// type_description_interfaces messages (e.g. the type ids as integers and the absent default values
+// as empty strings), for the clients deserializing them into ROS message classes
+#[derive(Debug, Serialize)]
+pub struct RosTypeDescription<'a> {
+    pub type_description: RosIndividualTypeDescription<'a>,
+    pub referenced_type_descriptions: Vec<RosIndividualTypeDescription<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RosIndividualTypeDescription<'a> {
+    pub type_name: &'a str,
+    pub fields: Vec<RosField<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RosField<'a> {
+    pub name: &'a str,
+    pub r#type: RosFieldType<'a>,
+    pub default_value: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RosFieldType<'a> {
+    pub type_id: u8,
+    pub capacity: u64,
+    pub string_capacity: u64,
+    pub nested_type_name: &'a str,
+}
+
+impl<'a> From<&'a TypeDescription> for RosTypeDescription<'a> {
+    fn from(description: &'a TypeDescription) -> Self {
+        RosTypeDescription {
+            type_description: (&description.type_description).into(),
+            referenced_type_descriptions: description
+                .referenced_type_descriptions
+                .iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a IndividualTypeDescription> for RosIndividualTypeDescription<'a> {
+    fn from(description: &'a IndividualTypeDescription) -> Self {
+        RosIndividualTypeDescription {
+            type_name: &description.type_name,
+            fields: description
+                .fields
+                .iter()
+                .map(|field| RosField {
+                    name: &field.name,
+                    r#type: RosFieldType {
+                        type_id: field.r#type.type_id as u8,
+                        capacity: field.r#type.capacity.into(),
+                        string_capacity: field.r#type.string_capacity.into(),
+                        nested_type_name: &field.r#type.nested_type_name,
+                    },
+                    default_value: field.default_value.as_deref().unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+}
+
+// Equivalent of type_description_interfaces/msg/TypeSource: the raw contents of the file
+// from which a type was generated, as returned by the GetTypeDescription service
+#[derive(Debug, Serialize)]
+pub struct TypeSource {
+    pub type_name: String,
+    // the format of the file ("msg", "srv" or "action"), or "implicit" for the inner types of
+    // services and actions, defined by the file of the service or action (with empty contents)
+    pub encoding: String,
+    pub raw_file_contents: Arc<str>,
+}
+
+impl TypeDescription {
+    // Check the consistency of the fields types of the type and its referenced types,
+    // returning the list of the violations (e.g. an array without capacity)
+    pub fn check_field_types(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        for description in
+            std::iter::once(&self.type_description).chain(self.referenced_type_descriptions.iter())
+        {
+            for field in &description.fields {
+                if let Err(e) = self.check_field_type(&field.r#type) {
+                    violations.push(format!("{}.{}: {e}", description.type_name, field.name));
+                }
+            }
+        }
+        violations
+    }
+
+    fn check_field_type(&self, field_type: &FieldType) -> Result<(), String> {
+        let type_id = field_type.type_id;
+        let base_type = type_id.base_type();
+        if base_type == FieldTypeId::NotSet {
+            return Err(format!("invalid type {type_id:?}"));
+        }
+        if (type_id.is_array() || type_id.is_bounded_sequence()) && field_type.capacity == 0 {
+            return Err(format!("{type_id:?} must have a capacity"));
+        }
+        if !type_id.is_collection() && field_type.capacity != 0 {
+            return Err(format!(
+                "{type_id:?} can't have a capacity ({})",
+                field_type.capacity
+            ));
+        }
+        if type_id.is_bounded_string() && field_type.string_capacity == 0 {
+            return Err(format!("{type_id:?} must have a string capacity"));
+        }
+        if base_type == FieldTypeId::NestedType {
+            if field_type.nested_type_name.is_empty() {
+                return Err(format!("{type_id:?} must have a nested type name"));
+            }
+            if !self
+                .referenced_type_descriptions
+                .iter()
+                .any(|d| d.type_name == field_type.nested_type_name)
+            {
+                return Err(format!(
+                    "nested type {} is not in the referenced types",
+                    field_type.nested_type_name
+                ));
+            }
+        } else if !field_type.nested_type_name.is_empty() {
+            return Err(format!(
+                "{type_id:?} can't have a nested type name ({})",
+                field_type.nested_type_name
+            ));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,99 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Advanced filter of the types, sent as a JSON document in the payload of the queries on
+// `@ros2_types/**`, for selections that don't fit into key expressions and parameters.
+// All the criteria set in the filter must be satisfied, e.g.:
+//   {"packages": ["sensor_msgs"], "kinds": ["MSG"], "name_regex": "Stamped$",
+//    "fields": [{"name": "header", "nested_type": "std_msgs/msg/Header"}]}
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::type_info::TypeInfo;
+
+// The JSON filter document
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FilterDocument {
+    packages: Vec<String>,       // the type is in one of those packages
+    kinds: Vec<String>,          // the type is of one of those kinds ("MSG", "SRV" or "ACTION")
+    name_regex: Option<String>,  // the type name (e.g. "std_msgs/msg/String") matches this regex
+    hashes: Vec<String>,         // the type has one of those type hashes
+    fields: Vec<FieldPredicate>, // the type has a field matching each of those predicates
+}
+
+// A predicate on a field of a type
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FieldPredicate {
+    name: Option<String>,        // the field has this name
+    nested_type: Option<String>, // the field is of this nested type (e.g. "std_msgs/msg/Header")
+}
+
+// A parsed filter, with its regex compiled
+#[derive(Debug)]
+pub(crate) struct TypeFilter {
+    document: FilterDocument,
+    name_regex: Option<Regex>,
+}
+
+impl TypeFilter {
+    // Parse a JSON filter document
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let document: FilterDocument =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON filter: {e}"))?;
+        let name_regex = document
+            .name_regex
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| format!("Invalid 'name_regex' in filter: {e}"))?;
+        Ok(TypeFilter {
+            document,
+            name_regex,
+        })
+    }
+
+    // Return true if the type satisfies all the criteria of the filter
+    pub fn matches(&self, t: &TypeInfo) -> bool {
+        let doc = &self.document;
+        (doc.packages.is_empty() || doc.packages.contains(&t.package_name))
+            && (doc.kinds.is_empty()
+                || doc
+                    .kinds
+                    .iter()
+                    .any(|k| k.eq_ignore_ascii_case(t.kind.as_ref())))
+            && self
+                .name_regex
+                .as_ref()
+                .map_or(true, |re| re.is_match(t.get_type_name()))
+            && (doc.hashes.is_empty() || doc.hashes.contains(&t.type_hash))
+            && doc.fields.iter().all(|predicate| {
+                t.type_description
+                    .type_description_msg
+                    .type_description
+                    .fields
+                    .iter()
+                    .any(|field| {
+                        predicate
+                            .name
+                            .as_ref()
+                            .map_or(true, |name| **name == *field.name)
+                            && predicate
+                                .nested_type
+                                .as_ref()
+                                .map_or(true, |nested| **nested == *field.r#type.nested_type_name)
+                    })
+            })
+    }
+}
@@ -10,12 +10,21 @@
 // Contributors:
 //   Julien Enoch, <julien.enoch@zettascale.tech>
 //
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+};
 
 use strum::{AsRefStr, EnumString};
 use zenoh_keyexpr::OwnedKeyExpr;
 
-use crate::type_description::HashedTypeDescription;
+use crate::{
+    type_description::{self, HashedTypeDescription},
+    type_name,
+};
 
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, AsRefStr, EnumString, PartialEq, Eq)]
@@ -27,15 +36,23 @@ pub(crate) enum TypeKind {
 }
 
 pub(crate) struct TypeInfo {
-    pub full_name: OwnedKeyExpr, // e.g. "std_msgs/msg/String", stored as KeyExpr to facilitate key expression matching
+    pub full_name: OwnedKeyExpr, // e.g. "std_msgs/msg/String", stored as KeyExpr to facilitate key expression matching (escaped if needed)
     pub package_name: String,    // e.g. "std_msgs" for "std_msgs/msg/String"
+    pub package_version: Option<String>, // the version in the package.xml of the package, if found
     pub short_name: String,      // e.g. "String" for "std_msgs/msg/String"
     pub kind: TypeKind,          // MSG, SRV, or ACTION
     pub type_description: HashedTypeDescription, // complete type description from the .json file
     pub type_hash: String,       // the type hash string
-    pub json_path: PathBuf,      // path to the .json file
+    pub schema_version: String, // the version of the type hash and description schema (e.g. "RIHS01")
+    pub json_path: PathBuf,     // path to the .json file
     pub definition_path: PathBuf, // path to the original .msg/.srv/.action file
-    pub definition_content: String, // content of the original .msg/.srv/.action file
+    pub definition_content: Option<Arc<str>>, // content of the original .msg/.srv/.action file
+    pub mcap_schema: OnceLock<String>, // the MCAP schema, generated on first use
+    pub description_json: OnceLock<String>, // the TypeDescription as JSON, serialized on first use
+    pub full_description_json: OnceLock<String>, // the full TypeDescription as JSON, serialized on first use
+    pub memory_size: usize,                      // estimated memory used by this type
+    pub memoized_bytes: Arc<AtomicUsize>, // total size of the memoized replies of the registry holding this type
+    pub last_access: AtomicU64, // logical time of the last access to this type, for LRU eviction
 }
 
 impl TypeInfo {
@@ -43,7 +60,7 @@ impl TypeInfo {
         full_name: OwnedKeyExpr,
         kind: TypeKind,
         type_description: HashedTypeDescription,
-        definition_content: String,
+        definition_content: Option<Arc<str>>,
         json_path: PathBuf,
         definition_path: PathBuf,
     ) -> Result<Self, String> {
@@ -83,7 +100,13 @@ impl TypeInfo {
         let type_hash = type_description
             .type_hashes
             .iter()
-            .find(|th| th.type_name == full_name.as_str())
+            .find(|th| {
+                *th.type_name
+                    == *type_description
+                        .type_description_msg
+                        .type_description
+                        .type_name
+            })
             .ok_or(format!(
                 "No hash found for type {} in {}",
                 full_name,
@@ -92,21 +115,102 @@ impl TypeInfo {
             .hash_string
             .clone();
 
+        let schema_version = type_description::schema_version(&type_hash).to_string();
+        if !type_description::SUPPORTED_SCHEMA_VERSIONS.contains(&schema_version.as_str()) {
+            tracing::warn!(
+                "Type {} has an unsupported schema version '{}' (supported: {:?}) - it might not be correctly interpreted",
+                full_name,
+                schema_version,
+                type_description::SUPPORTED_SCHEMA_VERSIONS
+            );
+        }
+
         Ok(Self {
             full_name,
             package_name,
+            package_version: None,
             short_name,
             kind,
             type_description,
             type_hash,
+            schema_version,
             json_path,
             definition_path,
             definition_content,
+            mcap_schema: OnceLock::new(),
+            description_json: OnceLock::new(),
+            full_description_json: OnceLock::new(),
+            memory_size: 0,
+            memoized_bytes: Arc::default(),
+            last_access: AtomicU64::new(0),
+        })
+    }
+
+    // Return the TypeDescription (without the referenced types) as JSON, serialized once
+    pub(crate) fn get_description_json(&self) -> Result<&str, String> {
+        if let Some(json) = self.description_json.get() {
+            return Ok(json);
+        }
+        let json =
+            serde_json::to_string(&self.type_description.type_description_msg.type_description)
+                .map_err(|e| {
+                    format!(
+                        "Failed to serialize type description of {}: {e}",
+                        self.full_name
+                    )
+                })?;
+        Ok(self.memoize(&self.description_json, json))
+    }
+
+    // Return the full TypeDescription (with the referenced types) as JSON, serialized once
+    pub fn get_full_description_json(&self) -> Result<&str, String> {
+        if let Some(json) = self.full_description_json.get() {
+            return Ok(json);
+        }
+        let json =
+            serde_json::to_string(&self.type_description.type_description_msg).map_err(|e| {
+                format!(
+                    "Failed to serialize full type description of {}: {e}",
+                    self.full_name
+                )
+            })?;
+        Ok(self.memoize(&self.full_description_json, json))
+    }
+
+    // Memoize a reply of this type, counting its size in the memoized bytes of its registry
+    pub(crate) fn memoize<'t>(&self, memo: &'t OnceLock<String>, reply: String) -> &'t str {
+        memo.get_or_init(|| {
+            self.memoized_bytes
+                .fetch_add(reply.len(), Ordering::Relaxed);
+            reply
         })
     }
 
+    // Return the size of the memoized replies of this type (not counted in memory_size, as
+    // generated on first use)
+    pub(crate) fn memoized_size(&self) -> usize {
+        [
+            &self.mcap_schema,
+            &self.description_json,
+            &self.full_description_json,
+        ]
+        .iter()
+        .filter_map(|memo| memo.get())
+        .map(String::len)
+        .sum()
+    }
+
+    // Return the original type name (full_name being escaped as a valid key expression)
+    pub(crate) fn get_type_name(&self) -> &str {
+        &self
+            .type_description
+            .type_description_msg
+            .type_description
+            .type_name
+    }
+
     // Return the short type name, e.g. "std_msgs/msg/String" becomes "std_msgs/String"
     pub(crate) fn get_short_type_name(&self) -> String {
-        format!("{}/{}", self.package_name, self.short_name)
+        type_name::unescape(&format!("{}/{}", self.package_name, self.short_name)).into_owned()
     }
 }
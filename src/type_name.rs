@@ -0,0 +1,151 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use std::{borrow::Cow, fmt::Write};
+
+// Characters of a type name which are not allowed in a key expression (or have a special
+// meaning), percent-encoded when the type name is converted to a key expression.
+// '%' itself is escaped to keep the conversion reversible.
+const ESCAPED_CHARS: &[char] = &['%', '*', '$', '?', '#'];
+
+// Character percent-encoded at the start of a chunk only, where it would make a verbatim chunk
+// (not matched by the wildcards)
+const VERBATIM_CHAR: char = '@';
+
+// The key expression chunk of an empty chunk of a type name (not allowed in a key expression),
+// which can't result from the escaping of another chunk ('%' being escaped)
+const EMPTY_CHUNK: &str = "%";
+
+// Convert a type name to a valid key expression, percent-encoding the characters
+// not allowed in key expressions (e.g. "pkg/msg/A*B" => "pkg/msg/A%2AB"), and the empty chunks
+// and the '@' starting a chunk (e.g. "pkg//@A" => "pkg/%/%40A")
+pub(crate) fn escape(type_name: &str) -> Cow<str> {
+    if !type_name.contains(ESCAPED_CHARS)
+        && !type_name
+            .split('/')
+            .any(|chunk| chunk.is_empty() || chunk.starts_with(VERBATIM_CHAR))
+    {
+        return Cow::Borrowed(type_name);
+    }
+    let mut escaped = String::with_capacity(type_name.len() + 8);
+    for (i, chunk) in type_name.split('/').enumerate() {
+        if i > 0 {
+            escaped.push('/');
+        }
+        if chunk.is_empty() {
+            escaped.push_str(EMPTY_CHUNK);
+            continue;
+        }
+        for (j, c) in chunk.chars().enumerate() {
+            if ESCAPED_CHARS.contains(&c) || (j == 0 && c == VERBATIM_CHAR) {
+                let _ = write!(escaped, "%{:02X}", c as u32);
+            } else {
+                escaped.push(c);
+            }
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+// Convert back a key expression to the original type name (the reverse of escape())
+pub(crate) fn unescape(ke: &str) -> Cow<str> {
+    if !ke.contains('%') {
+        return Cow::Borrowed(ke);
+    }
+    let chunks: Vec<Cow<str>> = ke.split('/').map(unescape_chunk).collect();
+    Cow::Owned(chunks.join("/"))
+}
+
+fn unescape_chunk(chunk: &str) -> Cow<str> {
+    if chunk == EMPTY_CHUNK {
+        return Cow::Borrowed("");
+    }
+    if !chunk.contains('%') {
+        return Cow::Borrowed(chunk);
+    }
+    let mut unescaped = String::with_capacity(chunk.len());
+    let mut rest = chunk;
+    while let Some(pos) = rest.find('%') {
+        unescaped.push_str(&rest[..pos]);
+        let at_chunk_start = pos == 0 && rest.len() == chunk.len();
+        let decoded = rest
+            .get(pos + 1..pos + 3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .map(char::from)
+            .filter(|c| ESCAPED_CHARS.contains(c) || (at_chunk_start && *c == VERBATIM_CHAR));
+        match decoded {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[pos + 3..];
+            }
+            None => {
+                // not an escape sequence: keep it as is
+                unescaped.push('%');
+                rest = &rest[pos + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    Cow::Owned(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trip(type_name: &str, expected_ke: &str) {
+        let ke = escape(type_name);
+        assert_eq!(ke, expected_ke);
+        assert_eq!(unescape(&ke), type_name);
+    }
+
+    #[test]
+    fn unchanged_type_name() {
+        assert_round_trip("std_msgs/msg/String", "std_msgs/msg/String");
+        assert!(matches!(escape("std_msgs/msg/String"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn escaped_chars() {
+        assert_round_trip("pkg/msg/A%B", "pkg/msg/A%25B");
+        assert_round_trip("pkg/msg/A*B", "pkg/msg/A%2AB");
+        assert_round_trip("pkg/msg/A%2AB", "pkg/msg/A%252AB");
+        assert_round_trip("pkg/msg/$?#", "pkg/msg/%24%3F%23");
+    }
+
+    #[test]
+    fn leading_verbatim_char() {
+        assert_round_trip("pkg/msg/@A", "pkg/msg/%40A");
+        assert_round_trip("@pkg/msg/A@B", "%40pkg/msg/A@B");
+        assert_round_trip("pkg/msg/A%40B", "pkg/msg/A%2540B");
+    }
+
+    #[test]
+    fn empty_chunks() {
+        assert_round_trip("pkg//A", "pkg/%/A");
+        assert_round_trip("/msg/A/", "%/msg/A/%");
+        assert_round_trip("", "%");
+    }
+
+    #[test]
+    fn percent_chunk() {
+        assert_round_trip("pkg/%/A", "pkg/%25/A");
+        assert_round_trip("pkg/%%/A", "pkg/%25%25/A");
+    }
+
+    #[test]
+    fn invalid_escape_sequences_kept() {
+        assert_eq!(unescape("pkg/msg/A%2"), "pkg/msg/A%2");
+        assert_eq!(unescape("pkg/msg/A%41"), "pkg/msg/A%41");
+        assert_eq!(unescape("pkg/msg/A%40"), "pkg/msg/A%40");
+    }
+}
@@ -0,0 +1,248 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    field_type::FieldTypeId,
+    type_description::{FieldType, IndividualTypeDescription, TypeDescription},
+};
+
+// Report of the validation of a JSON message instance against a type description
+#[derive(Debug, Serialize)]
+pub(crate) struct ValidationReport {
+    pub type_name: String,
+    pub valid: bool,
+    pub errors: Vec<ValidationError>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ValidationError {
+    pub path: String, // path of the faulty field, e.g. "header.stamp.sec" or "data[2]"
+    pub message: String,
+}
+
+// Validate a JSON message instance against a type description, reporting missing and unknown
+// fields, type mismatches and bound violations
+pub(crate) fn validate(instance: &Value, description: &TypeDescription) -> ValidationReport {
+    let mut errors = Vec::new();
+    validate_individual(
+        instance,
+        &description.type_description,
+        &description.referenced_type_descriptions,
+        "",
+        &mut errors,
+    );
+    ValidationReport {
+        type_name: description.type_description.type_name.to_string(),
+        valid: errors.is_empty(),
+        errors,
+    }
+}
+
+fn validate_individual(
+    instance: &Value,
+    t: &IndividualTypeDescription,
+    referenced: &[IndividualTypeDescription],
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let Some(object) = instance.as_object() else {
+        push_error(
+            errors,
+            path,
+            format!("expected an object of type {}", t.type_name),
+        );
+        return;
+    };
+
+    for field in &t.fields {
+        let field_path = if path.is_empty() {
+            field.name.to_string()
+        } else {
+            format!("{path}.{}", field.name)
+        };
+        match object.get(&*field.name) {
+            Some(value) => validate_field(value, &field.r#type, referenced, &field_path, errors),
+            None => push_error(errors, &field_path, "missing field".into()),
+        }
+    }
+
+    for name in object.keys() {
+        if !t.fields.iter().any(|f| *f.name == **name) {
+            let field_path = if path.is_empty() {
+                name.clone()
+            } else {
+                format!("{path}.{name}")
+            };
+            push_error(
+                errors,
+                &field_path,
+                format!("unknown field for type {}", t.type_name),
+            );
+        }
+    }
+}
+
+fn validate_field(
+    value: &Value,
+    field_type: &FieldType,
+    referenced: &[IndividualTypeDescription],
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let type_id = field_type.type_id;
+    if !type_id.is_collection() {
+        validate_element(value, field_type, referenced, path, errors);
+        return;
+    }
+
+    let Some(elements) = value.as_array() else {
+        push_error(errors, path, format!("expected an array for {type_id:?}"));
+        return;
+    };
+    let capacity = field_type.capacity as usize;
+    if type_id.is_array() && elements.len() != capacity {
+        push_error(
+            errors,
+            path,
+            format!(
+                "expected exactly {capacity} elements, found {}",
+                elements.len()
+            ),
+        );
+    } else if type_id.is_bounded_sequence() && elements.len() > capacity {
+        push_error(
+            errors,
+            path,
+            format!(
+                "expected at most {capacity} elements, found {}",
+                elements.len()
+            ),
+        );
+    }
+    for (i, element) in elements.iter().enumerate() {
+        validate_element(
+            element,
+            field_type,
+            referenced,
+            &format!("{path}[{i}]"),
+            errors,
+        );
+    }
+}
+
+// Validate a single (non-collection) value against the base type of the field type
+fn validate_element(
+    value: &Value,
+    field_type: &FieldType,
+    referenced: &[IndividualTypeDescription],
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let base_type = field_type.type_id.base_type();
+    match base_type {
+        FieldTypeId::NestedType => {
+            match referenced
+                .iter()
+                .find(|t| t.type_name == field_type.nested_type_name)
+            {
+                Some(nested) => validate_individual(value, nested, referenced, path, errors),
+                None => push_error(
+                    errors,
+                    path,
+                    format!(
+                        "nested type {} not found in type description",
+                        field_type.nested_type_name
+                    ),
+                ),
+            }
+        }
+        FieldTypeId::Float | FieldTypeId::Double | FieldTypeId::LongDouble => {
+            if !value.is_number() {
+                push_error(errors, path, format!("expected a number for {base_type:?}"));
+            }
+        }
+        FieldTypeId::Boolean => {
+            if !value.is_boolean() {
+                push_error(errors, path, "expected a boolean".into());
+            }
+        }
+        FieldTypeId::String
+        | FieldTypeId::WString
+        | FieldTypeId::FixedString
+        | FieldTypeId::FixedWString
+        | FieldTypeId::BoundedString
+        | FieldTypeId::BoundedWString => match value.as_str() {
+            Some(s) => {
+                let string_capacity = field_type.string_capacity as usize;
+                let len = s.chars().count();
+                if string_capacity > 0 && len > string_capacity {
+                    push_error(
+                        errors,
+                        path,
+                        format!("string length {len} exceeds bound {string_capacity}"),
+                    );
+                }
+            }
+            None => push_error(errors, path, format!("expected a string for {base_type:?}")),
+        },
+        FieldTypeId::NotSet => {
+            push_error(errors, path, "field type is not set".into());
+        }
+        _ => match integer_range(base_type) {
+            Some((min, max)) => match value
+                .as_i64()
+                .map(i128::from)
+                .or_else(|| value.as_u64().map(i128::from))
+            {
+                Some(i) if i < min || i > max => push_error(
+                    errors,
+                    path,
+                    format!("value {i} out of range [{min}, {max}] for {base_type:?}"),
+                ),
+                Some(_) => (),
+                None => push_error(
+                    errors,
+                    path,
+                    format!("expected an integer for {base_type:?}"),
+                ),
+            },
+            None => push_error(errors, path, format!("unsupported type {base_type:?}")),
+        },
+    }
+}
+
+// Range of the accepted values for integer-like types
+fn integer_range(type_id: FieldTypeId) -> Option<(i128, i128)> {
+    match type_id {
+        FieldTypeId::Int8 => Some((i8::MIN.into(), i8::MAX.into())),
+        FieldTypeId::UInt8 | FieldTypeId::Char | FieldTypeId::Byte => {
+            Some((u8::MIN.into(), u8::MAX.into()))
+        }
+        FieldTypeId::Int16 => Some((i16::MIN.into(), i16::MAX.into())),
+        FieldTypeId::UInt16 | FieldTypeId::WChar => Some((u16::MIN.into(), u16::MAX.into())),
+        FieldTypeId::Int32 => Some((i32::MIN.into(), i32::MAX.into())),
+        FieldTypeId::UInt32 => Some((u32::MIN.into(), u32::MAX.into())),
+        FieldTypeId::Int64 => Some((i64::MIN.into(), i64::MAX.into())),
+        FieldTypeId::UInt64 => Some((u64::MIN.into(), u64::MAX.into())),
+        _ => None,
+    }
+}
+
+fn push_error(errors: &mut Vec<ValidationError>, path: &str, message: String) {
+    errors.push(ValidationError {
+        path: path.to_string(),
+        message,
+    });
+}
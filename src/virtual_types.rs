@@ -0,0 +1,258 @@
+//
+// Copyright (c) 2025 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Contributors:
+//   Julien Enoch, <julien.enoch@zettascale.tech>
+//
+
+// Virtual types, defined in a JSON file as a type name and a list of fields referencing primitive
+// types or types of the registry, and served like the types of the ROS packages (e.g. to publish
+// aggregate telemetry structures without creating and building an interface package), e.g.:
+//   [{"type_name": "fleet_msgs/msg/Telemetry",
+//     "fields": [{"name": "header", "type": "std_msgs/Header"},
+//                {"name": "battery", "type": "sensor_msgs/BatteryState"},
+//                {"name": "temperatures", "type": "float32[<=8]"}]}]
+// Their type descriptions and type hashes (RIHS01) are computed as by rosidl.
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use serde_json::json;
+use zenoh::key_expr::keyexpr;
+
+use crate::{
+    registry::Registry,
+    resolve,
+    snapshot::SnapshotEntry,
+    type_description::{
+        hashable_description, type_hash, Field, FieldType, IndividualTypeDescription,
+    },
+    type_name,
+};
+
+// The definition of a virtual type (only messages are supported)
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct VirtualType {
+    type_name: String,         // e.g. "fleet_msgs/msg/Telemetry"
+    fields: Vec<VirtualField>, // the fields, in order
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VirtualField {
+    name: String,
+    r#type: String, // a field type as in a .msg definition, e.g. "Point32[]" or "string<=10"
+}
+
+// Read the definitions of the virtual types from a JSON file
+pub(crate) fn read_virtual_types(path: &Path) -> Result<Vec<VirtualType>, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid virtual types file {}: {e}", path.display()))
+}
+
+// Load the virtual types into the registry, in order (a virtual type can reference the previous
+// ones), returning the number of loaded types. The virtual types which can't be loaded (e.g. with
+// a nested type not found in the registry) are skipped with a warning.
+pub(crate) fn load_virtual_types(
+    registry: &mut Registry,
+    virtual_types: &[VirtualType],
+    path: &Path,
+) -> usize {
+    let mut count = 0;
+    for virtual_type in virtual_types {
+        match snapshot_entry(registry, virtual_type, path) {
+            Ok(entry) => count += registry.load_types_from_snapshot(vec![entry]),
+            Err(e) => tracing::warn!("Virtual type {} not loaded: {e}", virtual_type.type_name),
+        }
+    }
+    count
+}
+
+// Build the snapshot entry of a virtual type, with its generated type description and definition
+fn snapshot_entry(
+    registry: &mut Registry,
+    virtual_type: &VirtualType,
+    path: &Path,
+) -> Result<SnapshotEntry, String> {
+    let (package, short_name) =
+        match virtual_type.type_name.split('/').collect::<Vec<_>>()[..] {
+            [package, "msg", name] if !package.is_empty() && !name.is_empty() => (package, name),
+            _ => return Err(
+                "invalid type name - expected '<package>/msg/<Name>' (only messages are supported)"
+                    .into(),
+            ),
+        };
+    if registry.get_type(&virtual_type.type_name).is_some() {
+        return Err("a type with the same name is already loaded".into());
+    }
+
+    let mut fields = Vec::with_capacity(virtual_type.fields.len());
+    let mut definition = format!("# Virtual type defined in {}\n", path.display());
+    for field in &virtual_type.fields {
+        let field_type = resolve::resolve(registry, &field.r#type, Some(package))
+            .map_err(|e| format!("field '{}': {e}", field.name))?;
+        definition.push_str(&format!("{} {}\n", field.r#type, field.name));
+        fields.push(Field {
+            default_value: None,
+            name: Arc::from(field.name.as_str()),
+            r#type: field_type,
+        });
+    }
+    let description = IndividualTypeDescription {
+        type_name: Arc::from(virtual_type.type_name.as_str()),
+        fields,
+    };
+
+    // the descriptions and hashes of the transitive dependencies, from the nested types
+    let mut referenced: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    let mut hashable_referenced: BTreeMap<String, String> = BTreeMap::new();
+    let mut type_hashes: BTreeMap<String, String> = BTreeMap::new();
+    for field in &description.fields {
+        let nested_type_name = &*field.r#type.nested_type_name;
+        if nested_type_name.is_empty() || referenced.contains_key(nested_type_name) {
+            continue;
+        }
+        if let Ok(ke) = keyexpr::new(type_name::escape(nested_type_name).as_ref()) {
+            registry.load_pending_matching(ke);
+        }
+        let nested = registry.get_type(nested_type_name).ok_or(format!(
+            "nested type {nested_type_name} not found in registry"
+        ))?;
+        let nested_description = &nested.type_description.type_description_msg;
+        for d in std::iter::once(&nested_description.type_description)
+            .chain(&nested_description.referenced_type_descriptions)
+        {
+            referenced.insert(d.type_name.to_string(), description_json(d));
+            hashable_referenced.insert(d.type_name.to_string(), hashable_description(d));
+        }
+        for h in &nested.type_description.type_hashes {
+            type_hashes.insert(h.type_name.clone(), h.hash_string.clone());
+        }
+    }
+
+    // the type hash, with the referenced descriptions sorted by name
+    let hashable_referenced: Vec<String> = hashable_referenced.into_values().collect();
+    let type_hash = type_hash(&hashable_description(&description), &hashable_referenced);
+    type_hashes.insert(virtual_type.type_name.clone(), type_hash.clone());
+
+    let hashed_description = json!({
+        "type_description_msg": {
+            "type_description": description_json(&description),
+            "referenced_type_descriptions": referenced.into_values().collect::<Vec<_>>(),
+        },
+        "type_hashes": type_hashes
+            .into_iter()
+            .map(|(type_name, hash_string)| json!({"type_name": type_name, "hash_string": hash_string}))
+            .collect::<Vec<_>>(),
+    });
+
+    // the virtual definition path, as if the type was defined in a package next to the file
+    let definition_path = path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(format!("{package}/msg/{short_name}.msg"));
+    Ok(SnapshotEntry {
+        type_name: virtual_type.type_name.clone(),
+        kind: "MSG".into(),
+        type_hash,
+        description: hashed_description.to_string(),
+        definition,
+        json_path: PathBuf::from(path),
+        definition_path,
+    })
+}
+
+// The JSON of a type description, as in the .json files generated by rosidl
+fn description_json(description: &IndividualTypeDescription) -> serde_json::Value {
+    json!({
+        "type_name": &*description.type_name,
+        "fields": description
+            .fields
+            .iter()
+            .map(|field| {
+                json!({
+                    "name": &*field.name,
+                    "type": field_type_json(&field.r#type),
+                    "default_value": field.default_value.as_deref().unwrap_or_default(),
+                })
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn field_type_json(field_type: &FieldType) -> serde_json::Value {
+    json!({
+        "type_id": field_type.type_id as u64,
+        "capacity": field_type.capacity,
+        "string_capacity": field_type.string_capacity,
+        "nested_type_name": &*field_type.nested_type_name,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The type hashes in the .json files generated by rosidl for geometry_msgs (Jazzy)
+    const POINT_HASH: &str =
+        "RIHS01_6963084842a9b04494d6b2941d11444708d892da2f4b09843b9c43f42a7f6881";
+    const QUATERNION_HASH: &str =
+        "RIHS01_8a765f66778c8ff7c8ab94afcc590a2ed5325a1d9a076ffff38fbce36f458684";
+    const POSE_HASH: &str =
+        "RIHS01_d501954e9476cea2996984e812054b68026ae0bfae789d9a10b23daf35cc90fa";
+
+    fn virtual_types(json: &str) -> Vec<VirtualType> {
+        serde_json::from_str(json).expect("valid virtual types")
+    }
+
+    #[test]
+    fn type_hash_as_rosidl() {
+        let path = Path::new("virtual_types.json");
+        let mut registry = Registry::new();
+        let types = virtual_types(
+            r#"[{"type_name": "geometry_msgs/msg/Point",
+                 "fields": [{"name": "x", "type": "float64"},
+                            {"name": "y", "type": "float64"},
+                            {"name": "z", "type": "float64"}]},
+                {"type_name": "geometry_msgs/msg/Quaternion",
+                 "fields": [{"name": "x", "type": "float64"},
+                            {"name": "y", "type": "float64"},
+                            {"name": "z", "type": "float64"},
+                            {"name": "w", "type": "float64"}]}]"#,
+        );
+        assert_eq!(
+            snapshot_entry(&mut registry, &types[0], path).map(|e| e.type_hash),
+            Ok(POINT_HASH.to_string())
+        );
+        assert_eq!(load_virtual_types(&mut registry, &types, path), 2);
+        assert_eq!(
+            registry
+                .get_type("geometry_msgs/msg/Quaternion")
+                .map(|t| t.type_hash.as_str()),
+            Some(QUATERNION_HASH)
+        );
+
+        // with referenced types
+        let pose = virtual_types(
+            r#"[{"type_name": "geometry_msgs/msg/Pose",
+                 "fields": [{"name": "position", "type": "Point"},
+                            {"name": "orientation", "type": "Quaternion"}]}]"#,
+        );
+        assert_eq!(
+            snapshot_entry(&mut registry, &pose[0], path).map(|e| e.type_hash),
+            Ok(POSE_HASH.to_string())
+        );
+    }
+}